@@ -0,0 +1,82 @@
+//! Derive macros for raylib-rs. Use them through the `raylib` crate, which re-exports them.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, LitStr, parse_macro_input};
+
+/// Implements `ShaderUniforms` for a struct with named fields.
+///
+/// Every field is uploaded to the uniform of the same name and must implement `UniformValue`.
+/// `#[uniform(name = "lightPos")]` uploads to a differently named uniform, `#[uniform(skip)]` leaves the field out.
+#[proc_macro_derive(ShaderUniforms, attributes(uniform))]
+pub fn derive_shader_uniforms(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match shader_uniforms(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn shader_uniforms(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "ShaderUniforms can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "ShaderUniforms can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut names = Vec::new();
+    let mut members = Vec::new();
+    for field in &fields.named {
+        let member = field.ident.clone().unwrap();
+        let mut name = member.unraw().to_string();
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("uniform")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name = \"...\"` or `skip`"))
+                }
+            })?;
+        }
+        if !skip {
+            names.push(name);
+            members.push(member);
+        }
+    }
+
+    let ident = &input.ident;
+    let indices = 0..members.len();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::raylib::core::shaders::ShaderUniforms for #ident #ty_generics #where_clause {
+            const UNIFORM_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn upload(
+                &self,
+                shader: &mut impl ::raylib::core::shaders::RaylibShader,
+                locations: &[i32],
+            ) {
+                #(
+                    ::raylib::core::shaders::UniformValue::set_uniform(
+                        &self.#members,
+                        shader,
+                        locations[#indices],
+                    );
+                )*
+            }
+        }
+    })
+}
@@ -8,16 +8,33 @@ extern "C"
 #endif
 
 #include "utils_log.h"
-#include <stdio.h>	// Required for: vprintf()
-#include <string.h> // Required for: strcpy(), strcat()
+#include <stdarg.h> // Required for: va_list, va_copy()
+#include <stdio.h>	// Required for: vsnprintf()
+#include <stdlib.h> // Required for: malloc(), free()
 
-#define MAX_TRACELOG_BUFFER_SIZE 128 // As defined in utils.c from raylib
+#define MAX_TRACELOG_BUFFER_SIZE 256 // As MAX_TRACELOG_MSG_LENGTH in utils.c from raylib
 
 	void rayLogWrapperCallback(int logType, const char *text, va_list args)
 	{
 		char buffer[MAX_TRACELOG_BUFFER_SIZE] = {0};
-
-		vsnprintf(buffer, MAX_TRACELOG_BUFFER_SIZE, text, args);
+		va_list measure;
+
+		va_copy(measure, args);
+		int length = vsnprintf(buffer, MAX_TRACELOG_BUFFER_SIZE, text, measure);
+		va_end(measure);
+
+		// Messages that don't fit the buffer are formatted again into one that does
+		if (length >= MAX_TRACELOG_BUFFER_SIZE)
+		{
+			char *message = (char *)malloc(length + 1);
+			if (message != NULL)
+			{
+				vsnprintf(message, length + 1, text, args);
+				custom_trace_log_callback(logType, message);
+				free(message);
+				return;
+			}
+		}
 
 		custom_trace_log_callback(logType, buffer);
 	}
@@ -24,3 +24,24 @@ impl Default for TraceLogLevel {
         TraceLogLevel::LOG_INFO
     }
 }
+
+/// GLFW is compiled into raylib on desktop, but it is not part of `raylib.h` so bindgen never sees it.
+/// Only the bits needed before `InitWindow` are declared here.
+#[cfg(not(any(
+    feature = "sdl",
+    target_os = "android",
+    target_family = "wasm",
+    all(target_arch = "arm", target_os = "linux")
+)))]
+pub mod glfw {
+    use std::os::raw::c_int;
+
+    /// Init hint selecting the platform GLFW initializes with
+    pub const GLFW_PLATFORM: c_int = 0x0005_0003;
+    /// Platform without a window system. Contexts are created through OSMesa
+    pub const GLFW_PLATFORM_NULL: c_int = 0x0006_0005;
+
+    unsafe extern "C" {
+        pub fn glfwInitHint(hint: c_int, value: c_int);
+    }
+}
@@ -2,6 +2,9 @@
 mod audio_test {
     use crate::tests::*;
     use raylib::prelude::*;
+    use std::num::NonZeroUsize;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     #[test]
     fn test_init_audio() {
         let _ = RaylibAudio::init_audio_device();
@@ -26,4 +29,98 @@ mod audio_test {
         //         .expect("could not load music");
         // }
     }
+
+    ray_test!(test_procedural_stream);
+    fn test_procedural_stream(_thread: &RaylibThread) {
+        let Ok(audio) = RaylibAudio::init_audio_device() else {
+            return;
+        };
+        let frames = Arc::new(AtomicUsize::new(0));
+        let counted = frames.clone();
+        let mut phase = 0.0f32;
+        let stream = audio
+            .new_procedural_stream(44100, 2, move |samples| {
+                for frame in samples.chunks_mut(2) {
+                    frame.fill((phase * std::f32::consts::TAU).sin() * 0.1);
+                    phase = (phase + 440.0 / 44100.0).fract();
+                }
+                counted.fetch_add(samples.len() / 2, Ordering::Relaxed);
+            })
+            .expect("no free callback slot");
+        assert_eq!(stream.channels(), 2);
+        stream.play();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(frames.load(Ordering::Relaxed) > 0);
+        drop(stream);
+
+        // nothing runs the closure once the stream is gone
+        let after_drop = frames.load(Ordering::Relaxed);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(frames.load(Ordering::Relaxed), after_drop);
+
+        // running out of slots is an error, and dropping a stream frees its slot
+        let mut streams = Vec::new();
+        let error = loop {
+            match audio.new_procedural_stream(44100, 1, |_| {}) {
+                Ok(stream) => streams.push(stream),
+                Err(error) => break error,
+            }
+            assert!(streams.len() <= AUDIO_CALLBACK_SLOTS);
+        };
+        assert!(matches!(
+            error,
+            raylib::error::AudioCallbackError::NoFreeSlot(AUDIO_CALLBACK_SLOTS)
+        ));
+        streams.pop();
+        assert!(audio.new_procedural_stream(44100, 1, |_| {}).is_ok());
+    }
+
+    ray_test!(test_effect_chain_wave);
+    fn test_effect_chain_wave(_thread: &RaylibThread) {
+        let Ok(audio) = RaylibAudio::init_audio_device() else {
+            return;
+        };
+        let mut wave = audio
+            .new_wave("resources/audio/wave.ogg")
+            .expect("wave loading failed");
+        let channels = wave.channels();
+        let gain = Gain::new(0.0);
+        let mut chain = AudioEffectChain::new();
+        chain.push(Biquad::low_pass(500.0, 0.707)).push(gain);
+        chain.process_wave(&mut wave);
+        assert_eq!(wave.sample_size(), 32);
+        assert_eq!(wave.channels(), channels);
+        assert!(wave.load_samples().as_ref().iter().all(|s| *s == 0.0));
+    }
+
+    ray_test!(test_mixer_voices);
+    fn test_mixer_voices(_thread: &RaylibThread) {
+        let Ok(audio) = RaylibAudio::init_audio_device() else {
+            return;
+        };
+        let sound = audio
+            .new_sound("resources/audio/wave.ogg")
+            .expect("sound loading failed");
+        let mut mixer = AudioMixer::new();
+        let ui = mixer.add_bus("ui", BusId::SFX);
+        assert_eq!(mixer.bus("ui"), Some(ui));
+        let click = mixer
+            .add_sound(
+                &sound,
+                ui,
+                NonZeroUsize::new(2).unwrap(),
+                VoiceStealing::Reject,
+            )
+            .expect("could not alias sound");
+        assert!(mixer.play(click));
+        assert!(mixer.play(click));
+        assert!(!mixer.play(click));
+        assert_eq!(mixer.playing_voices(click), 2);
+
+        mixer.set_voice_stealing(click, VoiceStealing::Oldest);
+        assert!(mixer.play(click));
+        mixer.stop_bus(BusId::SFX);
+        mixer.update(0.016);
+        assert_eq!(mixer.playing_voices(click), 0);
+    }
 }
@@ -3,6 +3,7 @@ pub mod callback_tests {
     use std::{
         fs::OpenOptions,
         io::{Read, Write},
+        sync::{Arc, Mutex},
     };
 
     use crate::tests::*;
@@ -101,7 +102,7 @@ pub mod callback_tests {
             let mut handle = TEST_HANDLE.write().unwrap();
             let rl = handle.as_mut().unwrap();
             {
-                rl.set_trace_log_callback(custom_callback).unwrap();
+                set_trace_log_callback(custom_callback).keep();
                 for _ in 0..5 {
                     let noise = Image::gen_image_white_noise(10, 10, 1.0);
                     let _ = rl.load_texture_from_image(&thread, &noise).unwrap();
@@ -115,12 +116,7 @@ pub mod callback_tests {
             "\n{}\n",
             "Setting file data saver callback".bold().underline(),
         );
-        let mut handle = TEST_HANDLE.write().unwrap();
-        let rl = handle.as_mut().unwrap();
-        {
-            rl.set_save_file_data_callback(custom_save_file_data_callback)
-                .unwrap();
-        }
+        set_save_file_data_callback(custom_save_file_data_callback).keep();
     }
 
     pub fn set_file_text_saver(_: &RaylibThread) {
@@ -128,12 +124,7 @@ pub mod callback_tests {
             "\n{}\n",
             "Setting file text saver callback".bold().underline(),
         );
-        let mut handle = TEST_HANDLE.write().unwrap();
-        let rl = handle.as_mut().unwrap();
-        {
-            rl.set_save_file_text_callback(custom_save_file_text_callback)
-                .unwrap();
-        }
+        set_save_file_text_callback(custom_save_file_text_callback).keep();
     }
 
     pub fn set_file_data_loader(_: &RaylibThread) {
@@ -141,11 +132,24 @@ pub mod callback_tests {
             "\n{}\n",
             "Setting file data loader callback".bold().underline(),
         );
-        let mut handle = TEST_HANDLE.write().unwrap();
-        let rl = handle.as_mut().unwrap();
+        set_load_file_data_callback(custom_read_file_data_callback).keep();
+    }
+
+    ray_test!(test_trace_log_guard);
+    fn test_trace_log_guard(_: &RaylibThread) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let captured = messages.clone();
+        // longer than any fixed size buffer raylib formats into
+        let long = format!("{} end", "x".repeat(1000));
         {
-            rl.set_load_file_data_callback(custom_read_file_data_callback)
-                .unwrap();
+            let _guard = set_trace_log_callback(move |_, text| {
+                captured.lock().unwrap().push(text.to_owned())
+            });
+            trace_log(TraceLogLevel::LOG_WARNING, &long);
+            let _unset = unset_trace_log_callback();
+            trace_log(TraceLogLevel::LOG_WARNING, "not captured");
         }
+        trace_log(TraceLogLevel::LOG_WARNING, "not captured either");
+        assert_eq!(*messages.lock().unwrap(), vec![long]);
     }
 }
@@ -59,6 +59,19 @@ mod draw_test {
         );
     }
 
+    ray_draw_test!(test_snapshot_shapes);
+    fn test_snapshot_shapes(d: &mut RaylibDrawHandle, _: &TestAssets) {
+        d.clear_background(Color::WHITE);
+        d.draw_rectangle(10, 10, 100, 50, Color::RED);
+        d.draw_circle(200, 35, 25.0, Color::BLUE);
+        d.draw_triangle(
+            Vector2::new(300.0, 10.0),
+            Vector2::new(270.0, 60.0),
+            Vector2::new(330.0, 60.0),
+            Color::GREEN,
+        );
+        assert_frame_matches!("snapshot_shapes", 2u8);
+    }
     ray_draw_test!(test_rectangle);
     fn test_rectangle(d: &mut RaylibDrawHandle, _: &TestAssets) {
         d.clear_background(Color::WHITE);
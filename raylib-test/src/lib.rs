@@ -31,6 +31,9 @@ extern crate test;
 #[cfg(test)]
 #[macro_use]
 pub mod tests;
+#[cfg(test)]
+#[macro_use]
+pub mod snapshot;
 
 #[cfg(not(feature = "SUPPORT_CUSTOM_FRAME_CONTROL"))]
 #[cfg(not(feature = "automation_event_test"))]
@@ -8,22 +8,17 @@ pub(crate) mod manual_test {
 
         let mut rl = handle.as_mut().unwrap();
 
-        let mut prev_time = rl.get_time();
-        let mut cur_time = 0.0;
-        let mut wait_time = 0.0;
-        let mut delta_time = 0.0;
-        let mut update_draw_time = 0.0;
         let mut should_update = true;
 
         let default_font = rl.get_font_default();
 
         let target_fps = 60;
         while !rl.window_should_close() {
-            rl.poll_input_events();
+            let mut frame = rl.begin_manual_frame(thread);
 
             // place this in a block to simulate BeginDrawing/EndDrawing
             {
-                let mut d = rl.begin_drawing(&thread);
+                let mut d = frame.begin_drawing(thread);
 
                 d.draw_rectangle(
                     0,
@@ -41,24 +36,13 @@ pub(crate) mod manual_test {
                     should_update = !should_update;
                 }
 
-                d.draw_text_ex(&default_font,format!("You should be able to hold SPACE\n\nto turn the background red\n\nand release it to turn it white.\n\nPressing BACKSPACE will toggle whether the\n\nthe latter part of the code and\n\nmake it act weird.\n\nweird: {}, last wait_time: {}",!should_update,wait_time).as_str(),Vector2::new(25.0,25.0),25.0,3.0,Color::BLACK);
+                d.draw_text_ex(&default_font,format!("You should be able to hold SPACE\n\nto turn the background red\n\nand release it to turn it white.\n\nPressing BACKSPACE will toggle whether the\n\nthe latter part of the code and\n\nmake it act weird.\n\nweird: {}",!should_update).as_str(),Vector2::new(25.0,25.0),25.0,3.0,Color::BLACK);
             }
 
             if should_update {
-                rl.swap_screen_buffer();
-                cur_time = rl.get_time();
-                update_draw_time = cur_time - prev_time;
-                if target_fps > 0 {
-                    wait_time = (1.0 / target_fps as f64) - update_draw_time;
-                    if wait_time > 0.0 {
-                        rl.wait_time(wait_time);
-                        cur_time = rl.get_time();
-                        delta_time = cur_time - prev_time;
-                    }
-                } else {
-                    delta_time = update_draw_time;
-                }
-                prev_time = cur_time;
+                frame.set_target_fps(target_fps);
+            } else {
+                frame.skip_present();
             }
         }
         /*if (IsKeyPressed(KEY_SPACE)) pause = !pause;
@@ -10,6 +10,43 @@ mod core_test {
         assert!(std::path::Path::new("./screenshot.png").exists());
     }
 
+    ray_test!(test_automation_list_from_events);
+    fn test_automation_list_from_events(_: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        let events: Vec<_> = (0..3)
+            .map(|frame| AutomationEvent::new(frame, AutomationEventKind::KeyDown { key: 65 }))
+            .collect();
+        let list = rl
+            .load_automation_event_list_from_events(&events)
+            .expect("events fit in the list");
+        assert_eq!(list.count(), 3);
+        assert!(list.events() == events);
+
+        let too_many: Vec<_> = (0..=list.capacity())
+            .map(|frame| AutomationEvent::new(frame, AutomationEventKind::KeyDown { key: 65 }))
+            .collect();
+        assert!(matches!(
+            rl.load_automation_event_list_from_events(&too_many),
+            Err(raylib::error::AllocationError::ExceedsCapacity)
+        ));
+    }
+
+    ray_test!(test_load_shader_errors);
+    fn test_load_shader_errors(t: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        assert!(matches!(
+            rl.load_shader(t, None, Some("resources/missing.fs")),
+            Err(raylib::error::LoadShaderError::ReadFailed { .. })
+        ));
+        assert!(matches!(
+            rl.load_shader(t, None, Some("resources/just_exists.txt")),
+            Err(raylib::error::LoadShaderError::LoadFailed { .. })
+        ));
+        assert!(rl.load_shader(t, None, None).is_ok());
+    }
+
     ray_test!(test_screendata);
     fn test_screendata(t: &RaylibThread) {
         let mut handle = TEST_HANDLE.write().unwrap();
@@ -17,4 +54,24 @@ mod core_test {
         // make sure it doesn't seg fault
         let _ = rl.load_image_from_screen(t);
     }
+
+    #[derive(ShaderUniforms)]
+    #[allow(dead_code)]
+    struct Lighting {
+        #[uniform(name = "lightPos")]
+        light_pos: Vector3,
+        ambient: Vector4,
+        #[uniform(skip)]
+        dirty: bool,
+        count: i32,
+        mvp: Matrix,
+    }
+
+    #[test]
+    fn test_shader_uniforms_names() {
+        assert_eq!(
+            Lighting::UNIFORM_NAMES,
+            &["lightPos", "ambient", "count", "mvp"]
+        );
+    }
 }
@@ -29,6 +29,134 @@ mod model_test {
             .expect("could not load model animations");
     }
 
+    ray_test!(test_animation_blending);
+    fn test_animation_blending(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+
+        let mut model = rl
+            .load_model(&thread, "resources/guy/guy.iqm")
+            .expect("could not load model");
+        let anims = rl
+            .load_model_animations(&thread, "resources/guy/guyanim.iqm")
+            .expect("could not load model animations");
+        let bones = anims[0].bones();
+
+        let start = sample_animation(&anims[0], 0.0, true);
+        assert_eq!(start.as_slice(), anims[0].frame_poses()[0]);
+        let halfway = sample_animation(&anims[0], 0.5, true);
+        assert_eq!(halfway.len(), bones.len());
+
+        let mut machine = AnimationStateMachine::new("a", AnimationState::new(0, 60.0, true));
+        machine
+            .add_state("b", AnimationState::new(0, 30.0, false))
+            .add_transition("a", "b", 0.5);
+        assert!(!machine.transition_to("c"));
+        assert!(machine.transition_to("b"));
+        assert!(!machine.transition_to("a"));
+        machine.update(0.25, &anims);
+        assert!(machine.is_blending());
+        machine.update(0.5, &anims);
+        assert!(!machine.is_blending());
+
+        assert!(PoseBuffer::new(&[]).is_err());
+        let mut buffer = PoseBuffer::new(bones).expect("could not allocate pose");
+        buffer.set_pose(machine.pose());
+        assert_eq!(buffer.pose(), machine.pose());
+        rl.update_model_animation(&thread, &mut model, &buffer, 0);
+    }
+
+    ray_test!(test_bone_queries_and_skinning);
+    fn test_bone_queries_and_skinning(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+
+        let model = rl
+            .load_model(&thread, "resources/guy/guy.iqm")
+            .expect("could not load model");
+        let anims = rl
+            .load_model_animations(&thread, "resources/guy/guyanim.iqm")
+            .expect("could not load model animations");
+        let anim = &anims[0];
+
+        let name = anim.bones()[1].name();
+        assert_eq!(anim.bone_index(&name), Some(1));
+        assert_eq!(anim.bone_index("no such bone"), None);
+        let pose = anim.frame_poses()[0][1];
+        assert_eq!(
+            anim.bone_world_transform(&name, 0, Matrix::identity()),
+            Some(pose.to_matrix())
+        );
+        let world = anim
+            .bone_world_transform(&name, 0, Matrix::translate(1.0, 2.0, 3.0))
+            .unwrap();
+        assert_eq!(
+            (world.m12, world.m13, world.m14),
+            (
+                pose.translation.x + 1.0,
+                pose.translation.y + 2.0,
+                pose.translation.z + 3.0
+            )
+        );
+        assert_eq!(
+            anim.bone_world_transform(&name, usize::MAX, Matrix::identity()),
+            None
+        );
+
+        // the bind pose skins to the original positions
+        let mesh = &model.meshes()[0];
+        let bind = model.skinned_vertices(0, model.bind_poses());
+        for (a, b) in bind.iter().zip(mesh.vertices()) {
+            assert!(a.abs_diff_eq(*b, 1e-3));
+        }
+
+        let posed = model.skinned_vertices(0, anim.frame_poses()[0]);
+        assert_eq!(posed.len(), mesh.vertices().len());
+        let ray = Ray::new(Vector3::new(0.0, 100.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let _ = get_ray_collision_mesh_vertices(ray, mesh, &posed, model.transform());
+    }
+
+    ray_test!(test_mesh_builder);
+    fn test_mesh_builder(thread: &RaylibThread) {
+        let mut builder = MeshBuilder::new();
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            builder.vertex(Vector3::new(x, y, 0.0));
+            builder.normals.push(Vector3::Z);
+            builder.colors.push(Color::WHITE);
+        }
+        builder.quad(0, 1, 2, 3);
+
+        let mesh = builder.build(thread).expect("could not build mesh");
+        assert_eq!(mesh.vertices(), builder.vertices.as_slice());
+        assert_eq!(mesh.normals(), builder.normals.as_slice());
+        assert_eq!(mesh.as_ref().triangleCount, 2);
+        assert_ne!(mesh.as_ref().vaoId, 0);
+    }
+
+    ray_test!(test_dynamic_mesh);
+    fn test_dynamic_mesh(thread: &RaylibThread) {
+        let mut builder = MeshBuilder::new();
+        for x in 0..3 {
+            builder.vertex(Vector3::new(x as f32, 0.0, 0.0));
+            builder.colors.push(Color::WHITE);
+        }
+        let mut mesh = builder.build_dynamic(thread).expect("could not build mesh");
+
+        mesh.update_vertices(&[Vector3::ONE], 2).unwrap();
+        assert_eq!(mesh.vertices()[2], Vector3::ONE);
+        mesh.update_colors(&[Color::RED; 2], 1).unwrap();
+        assert_eq!(mesh.colors()[1], Color::RED);
+
+        assert!(matches!(
+            mesh.update_vertices(&[Vector3::ZERO; 2], 2),
+            Err(raylib::error::UpdateMeshError::OutOfBounds { count: 3, .. })
+        ));
+        assert!(matches!(
+            mesh.update_normals(&[Vector3::Z], 0),
+            Err(raylib::error::UpdateMeshError::MissingAttribute { .. })
+        ));
+    }
+
     ray_test!(test_model_from_generated_mesh);
     fn test_model_from_generated_mesh(thread: &RaylibThread) {
         let mut handle = TEST_HANDLE.write().unwrap();
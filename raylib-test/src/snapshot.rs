@@ -0,0 +1,271 @@
+//! Golden image assertions for draw tests.
+//!
+//! Baselines live in `snapshots/<name>.png` and are committed. A missing baseline is recorded from the current
+//! frame and the assertion passes, except under `CI`, where it fails; set `RAYLIB_UPDATE_SNAPSHOTS=1` to
+//! overwrite existing ones. On failure the captured frame and a diff image (mismatched pixels in red over a
+//! dimmed baseline) are written to `test_out/`.
+use raylib::ffi;
+use raylib::prelude::*;
+use std::path::Path;
+
+pub const SNAPSHOT_DIR: &str = "snapshots";
+pub const SNAPSHOT_OUT_DIR: &str = "test_out";
+
+/// How far a captured pixel may be from its baseline before it counts as a mismatch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// Each of r, g, b and a may differ by at most this much.
+    PerChannel(u8),
+    /// The CIE76 delta-E between the two colors may be at most this much (about 2.3 is a just noticeable
+    /// difference). Alpha is compared per channel with a tolerance of 1.
+    DeltaE(f32),
+}
+
+impl From<u8> for Tolerance {
+    fn from(v: u8) -> Tolerance {
+        Tolerance::PerChannel(v)
+    }
+}
+
+impl From<f32> for Tolerance {
+    fn from(v: f32) -> Tolerance {
+        Tolerance::DeltaE(v)
+    }
+}
+
+impl Tolerance {
+    pub fn matches(&self, a: Color, b: Color) -> bool {
+        match *self {
+            Tolerance::PerChannel(t) => {
+                a.r.abs_diff(b.r) <= t
+                    && a.g.abs_diff(b.g) <= t
+                    && a.b.abs_diff(b.b) <= t
+                    && a.a.abs_diff(b.a) <= t
+            }
+            Tolerance::DeltaE(t) => a.a.abs_diff(b.a) <= 1 && delta_e(a, b) <= t,
+        }
+    }
+}
+
+/// Result of comparing two equally sized pixel buffers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotDiff {
+    /// Indices of the pixels outside tolerance
+    pub mismatched: Vec<usize>,
+    /// Largest delta-E found, whatever the tolerance kind
+    pub max_delta_e: f32,
+}
+
+/// Compares `actual` against `expected` pixel by pixel.
+///
+/// # Panics
+///
+/// Panics if the buffers have different lengths.
+pub fn compare(actual: &[Color], expected: &[Color], tolerance: Tolerance) -> SnapshotDiff {
+    assert_eq!(actual.len(), expected.len(), "pixel buffers differ in size");
+    let mut mismatched = Vec::new();
+    let mut max_delta_e = 0.0f32;
+    for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+        max_delta_e = max_delta_e.max(delta_e(*a, *e));
+        if !tolerance.matches(*a, *e) {
+            mismatched.push(i);
+        }
+    }
+    SnapshotDiff {
+        mismatched,
+        max_delta_e,
+    }
+}
+
+/// CIE76 color difference between two sRGB colors, ignoring alpha.
+pub fn delta_e(a: Color, b: Color) -> f32 {
+    let (l1, a1, b1) = srgb_to_lab(a);
+    let (l2, a2, b2) = srgb_to_lab(b);
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+fn srgb_to_lab(c: Color) -> (f32, f32, f32) {
+    fn linear(v: u8) -> f32 {
+        let v = v as f32 / 255.0;
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    }
+    fn f(t: f32) -> f32 {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    }
+    let (r, g, b) = (linear(c.r), linear(c.g), linear(c.b));
+    // sRGB -> XYZ, normalized to the D65 white point
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// Captures what has been drawn to the screen so far this frame.
+pub fn capture_screen() -> Image {
+    unsafe {
+        ffi::rlDrawRenderBatchActive();
+        Image::from_raw(ffi::LoadImageFromScreen())
+    }
+}
+
+/// Captures the contents of a render texture.
+pub fn capture_render_texture(target: &impl RaylibRenderTexture2D) -> Image {
+    unsafe {
+        ffi::rlDrawRenderBatchActive();
+    }
+    let mut image = target
+        .texture()
+        .load_image()
+        .expect("could not read back render texture");
+    // render textures are stored bottom-up
+    image.flip_vertical();
+    image
+}
+
+/// Writes `image` to `path`, panicking if raylib could not.
+fn export(image: &Image, path: &str) {
+    let dir = Path::new(path)
+        .parent()
+        .expect("snapshot path has no directory");
+    std::fs::create_dir_all(dir).expect("could not create snapshot directory");
+    assert!(image.export_image(path), "could not write {}", path);
+}
+
+/// Compares `actual` against the baseline `snapshots/<name>.png`, recording it if it does not exist.
+///
+/// # Panics
+///
+/// Panics if the image does not match the baseline within `tolerance`, or if the baseline is missing and the
+/// `CI` environment variable is set.
+pub fn assert_matches_snapshot(actual: &Image, name: &str, tolerance: Tolerance) {
+    let baseline_path = format!("{}/{}.png", SNAPSHOT_DIR, name);
+    let update = std::env::var("RAYLIB_UPDATE_SNAPSHOTS").is_ok_and(|v| v == "1");
+    let missing = !Path::new(&baseline_path).exists();
+    if missing && !update && std::env::var_os("CI").is_some() {
+        let actual_path = format!("{}/{}.actual.png", SNAPSHOT_OUT_DIR, name);
+        export(actual, &actual_path);
+        panic!(
+            "snapshot {} has no baseline at {}, frame written to {}",
+            name, baseline_path, actual_path
+        );
+    }
+    if update || missing {
+        export(actual, &baseline_path);
+        println!("recorded snapshot {}", baseline_path);
+        return;
+    }
+
+    let expected = Image::load_image(&baseline_path).expect("could not load snapshot");
+    let actual_path = format!("{}/{}.actual.png", SNAPSHOT_OUT_DIR, name);
+    if actual.width() != expected.width() || actual.height() != expected.height() {
+        export(actual, &actual_path);
+        panic!(
+            "snapshot {} is {}x{} but the frame is {}x{}, frame written to {}",
+            name,
+            expected.width(),
+            expected.height(),
+            actual.width(),
+            actual.height(),
+            actual_path
+        );
+    }
+
+    let diff = compare(
+        &actual.get_image_data(),
+        &expected.get_image_data(),
+        tolerance,
+    );
+    if diff.mismatched.is_empty() {
+        return;
+    }
+
+    let diff_path = format!("{}/{}.diff.png", SNAPSHOT_OUT_DIR, name);
+    let mut diff_image = expected.clone();
+    diff_image.color_grayscale();
+    diff_image.color_brightness(-100);
+    for i in &diff.mismatched {
+        let i = *i as i32;
+        diff_image.draw_pixel(i % expected.width(), i / expected.width(), Color::RED);
+    }
+    export(actual, &actual_path);
+    export(&diff_image, &diff_path);
+    panic!(
+        "snapshot {} differs in {} of {} pixels (tolerance {:?}, max delta-E {:.2}), see {} and {}",
+        name,
+        diff.mismatched.len(),
+        expected.width() * expected.height(),
+        tolerance,
+        diff.max_delta_e,
+        actual_path,
+        diff_path
+    );
+}
+
+/// Asserts the current frame (or a render texture) matches a golden image.
+///
+/// ```ignore
+/// assert_frame_matches!("rectangles", 2u8); // per channel
+/// assert_frame_matches!("text", 2.3f32); // delta-E
+/// assert_frame_matches!(render_texture, "offscreen", Tolerance::PerChannel(0));
+/// ```
+macro_rules! assert_frame_matches {
+    ($name:expr, $tolerance:expr) => {
+        $crate::snapshot::assert_matches_snapshot(
+            &$crate::snapshot::capture_screen(),
+            $name,
+            $tolerance.into(),
+        )
+    };
+    ($target:expr, $name:expr, $tolerance:expr) => {
+        $crate::snapshot::assert_matches_snapshot(
+            &$crate::snapshot::capture_render_texture(&$target),
+            $name,
+            $tolerance.into(),
+        )
+    };
+}
+
+#[cfg(test)]
+mod snapshot_test {
+    use super::*;
+
+    #[test]
+    fn test_delta_e() {
+        assert_eq!(delta_e(Color::RED, Color::RED), 0.0);
+        // black to white is the full lightness range
+        let e = delta_e(Color::BLACK, Color::WHITE);
+        assert!((e - 100.0).abs() < 0.1, "delta-E was {}", e);
+        assert!(
+            delta_e(
+                Color::new(100, 100, 100, 255),
+                Color::new(101, 100, 100, 255)
+            ) < 1.0
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let expected = [Color::WHITE, Color::BLACK, Color::new(10, 20, 30, 255)];
+        let actual = [
+            Color::WHITE,
+            Color::new(2, 2, 2, 255),
+            Color::new(10, 20, 40, 255),
+        ];
+
+        let diff = compare(&actual, &expected, Tolerance::PerChannel(2));
+        assert_eq!(diff.mismatched, vec![2]);
+        let diff = compare(&actual, &expected, Tolerance::PerChannel(10));
+        assert!(diff.mismatched.is_empty());
+        let diff = compare(&actual, &expected, Tolerance::DeltaE(0.0));
+        assert_eq!(diff.mismatched, vec![1, 2]);
+    }
+}
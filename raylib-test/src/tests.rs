@@ -31,10 +31,11 @@ fn clone_testfn(testfn: &TestFn) -> TestFn {
 
 pub fn initialize_globals() -> (RaylibThread, TestAssets) {
     let mut handle = TEST_HANDLE.write().unwrap();
-    let (rl, thread) = raylib::init()
-        .size(TEST_WIDTH, TEST_HEIGHT)
-        .title("Hello, World")
-        .build();
+    let mut builder = raylib::init();
+    builder.size(TEST_WIDTH, TEST_HEIGHT).title("Hello, World");
+    #[cfg(feature = "headless")]
+    builder.headless();
+    let (rl, thread) = builder.build();
     *handle = Some(rl);
     let asset = TestAssets {
         font: handle
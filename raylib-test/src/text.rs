@@ -64,4 +64,142 @@ mod text_test {
             Color::RED,
         );
     }
+
+    ray_draw_test!(test_text_layout);
+    fn test_text_layout(d: &mut RaylibDrawHandle, assets: &TestAssets) {
+        d.clear_background(Color::WHITE);
+        let layout = TextLayout::new(
+            vec![
+                TextSpan::new("Hello ", &assets.font, 32.0, Color::BLACK),
+                TextSpan::new("wrapped", &assets.font_ex, 32.0, Color::RED).underline(true),
+                TextSpan::new(" and justified world", &assets.font, 24.0, Color::BLUE),
+            ],
+            LayoutOptions {
+                max_width: Some(200.0),
+                align: TextAlign::Justify,
+                clip: Some(Rectangle::new(0.0, 0.0, 200.0, 100.0)),
+                ..Default::default()
+            },
+        );
+        assert!(layout.lines().len() > 1);
+        let index = layout.hit_test(Vector2::new(1000.0, 0.0));
+        assert_eq!(index, layout.lines()[0].text.end);
+        d.draw_text_layout(&layout, Vector2::new(100.0, 100.0));
+        let mut caret = layout.caret_rect(index);
+        caret.x += 100.0;
+        caret.y += 100.0;
+        d.draw_rectangle_rec(caret, Color::BLACK);
+    }
+
+    ray_test!(test_dynamic_font);
+    fn test_dynamic_font(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        let mut font = rl
+            .load_dynamic_font(thread, "resources/pixeloid.ttf", 32)
+            .expect("couldn't load font");
+        assert!(font.is_cached('A'));
+        assert!(!font.is_cached('é'));
+
+        let glyphs = font.chars().len();
+        let size = font
+            .cache_text(thread, "héllo wörld")
+            .measure_text("héllo wörld", 32.0, 1.0);
+        assert!(size.x > 0.0);
+        assert!(font.is_cached('é') && font.is_cached('ö'));
+        assert_eq!(font.chars().len(), glyphs + 2);
+        assert_eq!(font.get_glyph_info('é').value, 'é' as i32);
+
+        // already cached text doesn't rasterize anything
+        font.cache_text(thread, "wörld");
+        assert_eq!(font.chars().len(), glyphs + 2);
+    }
+
+    ray_test!(test_sdf_font);
+    fn test_sdf_font(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        let font = Font::load_sdf(thread, "resources/pixeloid.ttf", 32, Some("SDF text"))
+            .expect("couldn't load SDF font");
+        // "SDF text" has two t's but only one glyph for them
+        assert_eq!(font.chars().len(), 7);
+        assert!(font.shader().is_shader_valid());
+
+        let style = SdfTextStyle {
+            outline_width: 1.0,
+            shadow: Some(SdfShadow::default()),
+            ..Default::default()
+        };
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::WHITE);
+        d.draw_text_sdf(
+            &font,
+            "SDF text",
+            Vector2::new(10.0, 10.0),
+            96.0,
+            0.0,
+            Color::RED,
+            &style,
+        );
+    }
+
+    #[cfg(feature = "shaping")]
+    ray_test!(test_shaping_font);
+    #[cfg(feature = "shaping")]
+    fn test_shaping_font(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        let mut font = rl
+            .load_shaping_font(thread, "resources/pixeloid.ttf", 32)
+            .expect("couldn't load font");
+        let text = font.shape(thread, "shaped\ntext");
+        assert_eq!(text.runs.len(), 2);
+        assert_eq!(text.glyphs().count(), 10);
+        assert!(text.size.x > 0.0 && text.size.y == 64.0);
+        for glyph in text.glyphs() {
+            assert_eq!(
+                font.get_glyph_info(glyph.codepoint).value,
+                glyph.codepoint as i32
+            );
+        }
+
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::WHITE);
+        d.draw_shaped_text(&font, &text, Vector2::new(10.0, 10.0), 32.0, Color::BLACK);
+    }
+
+    #[cfg(feature = "shaping")]
+    ray_test!(test_shaping_complex_scripts);
+    #[cfg(feature = "shaping")]
+    fn test_shaping_complex_scripts(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        // glyph ids are those of this DejaVu Sans file
+        let mut font = rl
+            .load_shaping_font(thread, "resources/DejaVuSans.ttf", 32)
+            .expect("couldn't load font");
+        let glyphs = |run: &GlyphRun| -> Vec<(u16, usize)> {
+            run.glyphs.iter().map(|g| (g.glyph_id, g.cluster)).collect()
+        };
+
+        let text = font.shape(thread, "abc بيت");
+        assert_eq!(text.runs.len(), 2);
+        assert!(!text.runs[0].rtl && text.runs[1].rtl);
+        assert_eq!(glyphs(&text.runs[0]), [(68, 0), (69, 1), (70, 2), (3, 3)]);
+        // drawn right to left, so the last letter comes first, and each letter takes its joined final, medial
+        // and initial form instead of the isolated 1368, 1395 and 1366
+        assert_eq!(glyphs(&text.runs[1]), [(5264, 8), (5358, 6), (5259, 4)]);
+        let x: Vec<f32> = text.glyphs().map(|g| g.position.x).collect();
+        assert!(x.windows(2).all(|w| w[0] < w[1]), "{x:?}");
+
+        // lam and alef join into one ligature glyph covering both letters
+        let text = font.shape(thread, "لا");
+        assert_eq!(glyphs(&text.runs[0]), [(5365, 0)]);
+
+        let text = font.shape(thread, "abc שלום");
+        assert_eq!(
+            glyphs(&text.runs[1]),
+            [(1332, 10), (1324, 8), (1331, 6), (1344, 4)]
+        );
+    }
 }
@@ -28,6 +28,27 @@ mod texture_test {
         i.export_image("test_out/billboard_texture.png");
     }
 
+    ray_test!(test_texture_load_vfs);
+    fn test_texture_load_vfs(thread: &RaylibThread) {
+        let mut handle = TEST_HANDLE.write().unwrap();
+        let rl = handle.as_mut().unwrap();
+        let mut pack = MemoryVfs::new();
+        pack.insert(
+            "sprites/billboard.png",
+            std::fs::read("resources/billboard.png").unwrap(),
+        );
+        let mut vfs = VfsOverlay::new();
+        vfs.mount("", DirectoryVfs::new("resources"))
+            .mount("pack", pack);
+        let _vfs = vfs.install();
+        rl.load_texture(thread, "pack/sprites/billboard.png")
+            .expect("could not load texture from the memory mount");
+        rl.load_texture(thread, "./alagard.png")
+            .expect("could not load texture from the directory mount");
+        rl.load_texture(thread, "resources/billboard.png")
+            .expect_err("loaded a texture from outside the mounts");
+    }
+
     ray_test!(test_render_texture);
     fn test_render_texture(t: &RaylibThread) {
         let mut handle = TEST_HANDLE.write().unwrap();
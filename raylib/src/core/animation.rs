@@ -0,0 +1,755 @@
+//! Blending and skinning skeletal animations.
+//!
+//! raylib poses a model from a single frame of a single [`ModelAnimation`]. The functions here sample animations
+//! at fractional frames and mix the resulting poses, and [`AnimationStateMachine`] crossfades between clips.
+//!
+//! A pose is one model-space [`Transform`] per bone, laid out like a frame of
+//! [`RaylibModelAnimation::frame_poses`]. Poses are mixed bone by bone relative to each bone's parent, so a
+//! masked upper-body layer stays attached to the legs underneath it. To show a pose, copy it into a
+//! [`PoseBuffer`] and pass that to [`RaylibHandle::update_model_animation`] as frame 0. [`skin_vertices`] applies a
+//! pose to mesh positions on the CPU instead, for picking and collision against animated models.
+//! ```ignore
+//! let mut machine = AnimationStateMachine::new("idle", AnimationState::new(IDLE, 60.0, true));
+//! machine
+//!     .add_state("run", AnimationState::new(RUN, 60.0, true))
+//!     .add_transition("idle", "run", 0.25)
+//!     .add_transition("run", "idle", 0.4);
+//! let mut buffer = PoseBuffer::new(anims[0].bones())?;
+//! let arms = BoneMask::branch(anims[0].bones(), "spine", 1.0).unwrap();
+//! while !rl.window_should_close() {
+//!     machine.transition_to(if rl.is_key_down(KeyboardKey::KEY_W) { "run" } else { "idle" });
+//!     let pose = machine.update(rl.get_frame_time(), &anims);
+//!     let wave = sample_animation(&anims[WAVE], wave_time * 60.0, true);
+//!     buffer.set_pose(&blend_poses(anims[0].bones(), pose, &wave, 1.0, Some(&arms)));
+//!     rl.update_model_animation(&thread, &mut model, &buffer, 0);
+//!     ...
+//! }
+//! ```
+//!
+//! [`RaylibHandle::update_model_animation`]: crate::core::RaylibHandle::update_model_animation
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use glam::{Mat4, Quat, Vec3};
+
+use crate::core::data::DataBuf;
+use crate::core::math::{Matrix, Quaternion, Transform, Vector3};
+use crate::core::models::{BoneInfo, ModelAnimation, RaylibModelAnimation};
+use crate::error::AllocationError;
+use crate::ffi;
+
+/// Translation, rotation and scale of a bone in a form that can be interpolated
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bone {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+}
+
+impl Bone {
+    fn from_transform(t: &Transform) -> Bone {
+        let r: ffi::Quaternion = t.rotation.into();
+        Bone {
+            translation: t.translation,
+            rotation: Quat::from_xyzw(r.x, r.y, r.z, r.w),
+            scale: t.scale,
+        }
+    }
+
+    fn to_transform(self) -> Transform {
+        let r = self.rotation;
+        Transform {
+            translation: self.translation,
+            rotation: Quaternion::new(r.x, r.y, r.z, r.w),
+            scale: self.scale,
+        }
+    }
+
+    /// `child`, given relative to this bone, in this bone's space
+    fn then(self, child: Bone) -> Bone {
+        Bone {
+            translation: self.translation + self.rotation * (self.scale * child.translation),
+            rotation: (self.rotation * child.rotation).normalize(),
+            scale: self.scale * child.scale,
+        }
+    }
+
+    /// Inverse of [`Bone::then`], `child` relative to this bone
+    fn relative(self, child: Bone) -> Bone {
+        let inverse = self.rotation.inverse();
+        Bone {
+            translation: inverse * (child.translation - self.translation) * recip(self.scale),
+            rotation: (inverse * child.rotation).normalize(),
+            scale: child.scale * recip(self.scale),
+        }
+    }
+
+    fn lerp(self, other: Bone, amount: f32) -> Bone {
+        Bone {
+            translation: self.translation.lerp(other.translation, amount),
+            rotation: self.rotation.slerp(other.rotation, amount),
+            scale: self.scale.lerp(other.scale, amount),
+        }
+    }
+}
+
+/// Component-wise reciprocal that keeps a zero scale (a hidden bone) at zero
+fn recip(v: Vec3) -> Vec3 {
+    Vec3::from_array(v.to_array().map(|c| if c == 0.0 { 0.0 } else { 1.0 / c }))
+}
+
+fn parent_of(bones: &[BoneInfo], bone: usize) -> Option<usize> {
+    bones
+        .get(bone)
+        .and_then(BoneInfo::parent)
+        .filter(|p| *p < bone)
+}
+
+fn to_local(bones: &[BoneInfo], pose: &[Transform]) -> Vec<Bone> {
+    (0..pose.len())
+        .map(|i| {
+            let bone = Bone::from_transform(&pose[i]);
+            match parent_of(bones, i) {
+                Some(p) => Bone::from_transform(&pose[p]).relative(bone),
+                None => bone,
+            }
+        })
+        .collect()
+}
+
+/// raylib stores parents before their children, so one pass is enough
+fn to_model(bones: &[BoneInfo], local: &[Bone]) -> Vec<Transform> {
+    let mut model: Vec<Bone> = Vec::with_capacity(local.len());
+    for (i, bone) in local.iter().enumerate() {
+        let bone = match parent_of(bones, i) {
+            Some(p) => model[p].then(*bone),
+            None => *bone,
+        };
+        model.push(bone);
+    }
+    model.into_iter().map(Bone::to_transform).collect()
+}
+
+/// Pose of `anim` at a fractional `frame`, interpolated between the two nearest frames. Looping animations
+/// wrap around from the last frame to the first, others hold their first and last frame outside their range.
+///
+/// Returns an empty pose if the animation has no frames.
+pub fn sample_animation<A: RaylibModelAnimation + ?Sized>(
+    anim: &A,
+    frame: f32,
+    looping: bool,
+) -> Vec<Transform> {
+    let frames = anim.frame_poses();
+    let Some(last) = frames.len().checked_sub(1) else {
+        return Vec::new();
+    };
+    let frame = if looping {
+        frame.rem_euclid(frames.len() as f32)
+    } else {
+        frame.clamp(0.0, last as f32)
+    };
+    let index = (frame as usize).min(last);
+    let next = if looping {
+        (index + 1) % frames.len()
+    } else {
+        (index + 1).min(last)
+    };
+    let amount = frame - index as f32;
+    if index == next || amount <= 0.0 {
+        return frames[index].to_vec();
+    }
+    blend_poses(anim.bones(), frames[index], frames[next], amount, None)
+}
+
+/// Interpolates from pose `from` to pose `to`, `weight` 0 giving `from` and 1 giving `to`. A mask scales the
+/// weight per bone.
+///
+/// Panics if the poses have different bone counts.
+pub fn blend_poses(
+    bones: &[BoneInfo],
+    from: &[Transform],
+    to: &[Transform],
+    weight: f32,
+    mask: Option<&BoneMask>,
+) -> Vec<Transform> {
+    assert_eq!(from.len(), to.len(), "poses have different bone counts");
+    let from = to_local(bones, from);
+    let to = to_local(bones, to);
+    let local: Vec<Bone> = from
+        .iter()
+        .zip(&to)
+        .enumerate()
+        .map(|(i, (a, b))| a.lerp(*b, weight * mask.map_or(1.0, |m| m.weight(i))))
+        .collect();
+    to_model(bones, &local)
+}
+
+/// Adds the difference between `additive` and `reference` on top of `base`, e.g. a breathing or recoil clip
+/// authored relative to its first frame. `weight` and the mask scale how much of the difference is applied.
+///
+/// Panics if the poses have different bone counts.
+pub fn add_pose(
+    bones: &[BoneInfo],
+    base: &[Transform],
+    additive: &[Transform],
+    reference: &[Transform],
+    weight: f32,
+    mask: Option<&BoneMask>,
+) -> Vec<Transform> {
+    assert!(
+        base.len() == additive.len() && base.len() == reference.len(),
+        "poses have different bone counts"
+    );
+    let base = to_local(bones, base);
+    let additive = to_local(bones, additive);
+    let reference = to_local(bones, reference);
+    let local: Vec<Bone> = base
+        .iter()
+        .zip(additive.iter().zip(&reference))
+        .enumerate()
+        .map(|(i, (base, (add, reference)))| {
+            let weight = weight * mask.map_or(1.0, |m| m.weight(i));
+            let rotation = (reference.rotation.inverse() * add.rotation).normalize();
+            Bone {
+                translation: base.translation + (add.translation - reference.translation) * weight,
+                rotation: (base.rotation * Quat::IDENTITY.slerp(rotation, weight)).normalize(),
+                scale: base.scale * Vec3::ONE.lerp(add.scale * recip(reference.scale), weight),
+            }
+        })
+        .collect();
+    to_model(bones, &local)
+}
+
+/// Per-bone weights for [`blend_poses`] and [`add_pose`]. A weight of 0 leaves the bone alone, 1 applies the
+/// layer fully.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoneMask {
+    weights: Vec<f32>,
+}
+
+impl BoneMask {
+    /// A mask with the same weight for every bone.
+    pub fn new(bone_count: usize, weight: f32) -> BoneMask {
+        BoneMask {
+            weights: vec![weight; bone_count],
+        }
+    }
+
+    /// A mask with `weight` on the bone called `name` and every bone below it, and 0 elsewhere. `None` if there
+    /// is no such bone.
+    pub fn branch(bones: &[BoneInfo], name: &str, weight: f32) -> Option<BoneMask> {
+        let mut mask = BoneMask::new(bones.len(), 0.0);
+        mask.set_branch(bones, name, weight).then_some(mask)
+    }
+
+    /// Sets `weight` on the bone called `name` and every bone below it. Returns false if there is no such bone.
+    pub fn set_branch(&mut self, bones: &[BoneInfo], name: &str, weight: f32) -> bool {
+        let Some(root) = bones.iter().position(|b| b.name() == name) else {
+            return false;
+        };
+        if self.weights.len() < bones.len() {
+            self.weights.resize(bones.len(), 0.0);
+        }
+        let mut in_branch = vec![false; bones.len()];
+        for i in root..bones.len() {
+            in_branch[i] = i == root || parent_of(bones, i).is_some_and(|p| in_branch[p]);
+            if in_branch[i] {
+                self.weights[i] = weight;
+            }
+        }
+        true
+    }
+
+    pub fn set_weight(&mut self, bone: usize, weight: f32) {
+        if self.weights.len() <= bone {
+            self.weights.resize(bone + 1, 0.0);
+        }
+        self.weights[bone] = weight;
+    }
+
+    /// Weight of `bone`, 0 for bones past the end of the mask.
+    #[inline]
+    pub fn weight(&self, bone: usize) -> f32 {
+        self.weights.get(bone).copied().unwrap_or(0.0)
+    }
+}
+
+/// A one-frame [`ModelAnimation`] holding a computed pose, for [`RaylibHandle::update_model_animation`] or
+/// [`RaylibHandle::update_model_animation_bones`] with frame 0.
+///
+/// [`RaylibHandle::update_model_animation`]: crate::core::RaylibHandle::update_model_animation
+/// [`RaylibHandle::update_model_animation_bones`]: crate::core::RaylibHandle::update_model_animation_bones
+#[derive(Debug)]
+pub struct PoseBuffer(ModelAnimation);
+
+impl PoseBuffer {
+    /// Allocates a buffer for the skeleton `bones`, starting out in the identity pose.
+    ///
+    /// # Errors
+    ///
+    /// [`AllocationError::SubMinSize`] if `bones` is empty, the same as [`DataBuf::alloc`].
+    pub fn new(bones: &[BoneInfo]) -> Result<PoseBuffer, AllocationError> {
+        if bones.is_empty() {
+            return Err(AllocationError::SubMinSize);
+        }
+        let infos: Vec<ffi::BoneInfo> = bones.iter().map(|b| b.0).collect();
+        let identity = Transform {
+            translation: Vec3::ZERO,
+            rotation: Quaternion::identity(),
+            scale: Vec3::ONE,
+        };
+        let poses = vec![ffi::Transform::from(identity); bones.len()];
+
+        // UnloadModelAnimation frees whatever was allocated if a later allocation fails
+        let mut anim = ModelAnimation(unsafe { std::mem::zeroed() });
+        anim.0.boneCount = bones.len() as i32;
+        anim.0.bones = DataBuf::leak_slice(&infos)?;
+        anim.0.framePoses = DataBuf::<*mut ffi::Transform>::alloc(1)?.leak().0.as_ptr();
+        unsafe { *anim.0.framePoses = DataBuf::leak_slice(&poses)? };
+        anim.0.frameCount = 1;
+        Ok(PoseBuffer(anim))
+    }
+
+    /// The pose currently in the buffer.
+    pub fn pose(&self) -> &[Transform] {
+        self.0.frame_poses_iter().next().unwrap_or_default()
+    }
+
+    /// Copies `pose` into the buffer. Panics if it doesn't have one transform per bone.
+    pub fn set_pose(&mut self, pose: &[Transform]) {
+        assert_eq!(
+            pose.len(),
+            self.0.0.boneCount as usize,
+            "pose has the wrong number of bones"
+        );
+        if let Some(frame) = self.0.frame_poses_iter_mut().next() {
+            frame.copy_from_slice(pose);
+        }
+    }
+}
+
+impl std::ops::Deref for PoseBuffer {
+    type Target = ModelAnimation;
+
+    fn deref(&self) -> &ModelAnimation {
+        &self.0
+    }
+}
+
+impl AsRef<ffi::ModelAnimation> for PoseBuffer {
+    fn as_ref(&self) -> &ffi::ModelAnimation {
+        &self.0.0
+    }
+}
+
+/// Matrices moving vertices from `bind_pose` into `pose`, one per bone. These are the `boneMatrices` raylib
+/// uploads for GPU skinning, for use with [`skin_vertices`].
+pub fn skinning_matrices(bind_pose: &[Transform], pose: &[Transform]) -> Vec<Matrix> {
+    bind_pose
+        .iter()
+        .zip(pose)
+        .map(|(bind, posed)| {
+            let bind: Mat4 = bind.to_matrix().into();
+            let posed: Mat4 = posed.to_matrix().into();
+            (posed * bind.inverse()).into()
+        })
+        .collect()
+}
+
+/// Skins `vertices` on the CPU, moving each one by up to four of `matrices` blended by its bone weights, the
+/// same way raylib's skinning shader does.
+///
+/// Weights are normalized after skipping bone ids outside of `matrices`. Vertices without bone ids or weights,
+/// or whose weights are all zero, keep their position.
+pub fn skin_vertices(
+    vertices: &[Vector3],
+    bone_ids: &[[u8; 4]],
+    bone_weights: &[[f32; 4]],
+    matrices: &[Matrix],
+) -> Vec<Vector3> {
+    let matrices: Vec<Mat4> = matrices.iter().map(|m| Mat4::from(*m)).collect();
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(i, &vertex)| {
+            let (Some(ids), Some(weights)) = (bone_ids.get(i), bone_weights.get(i)) else {
+                return vertex;
+            };
+            let mut skinned = Vector3::ZERO;
+            let mut total = 0.0;
+            for (id, weight) in ids.iter().zip(weights) {
+                if let Some(matrix) = matrices.get(*id as usize)
+                    && *weight != 0.0
+                {
+                    skinned += matrix.transform_point3(vertex) * *weight;
+                    total += *weight;
+                }
+            }
+            if total == 0.0 {
+                vertex
+            } else {
+                skinned / total
+            }
+        })
+        .collect()
+}
+
+/// A clip played by an [`AnimationStateMachine`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationState {
+    /// Index into the animations passed to [`AnimationStateMachine::update`]
+    pub animation: usize,
+    /// Playback rate in animation frames per second
+    pub fps: f32,
+    pub looping: bool,
+}
+
+impl AnimationState {
+    pub fn new(animation: usize, fps: f32, looping: bool) -> AnimationState {
+        AnimationState {
+            animation,
+            fps,
+            looping,
+        }
+    }
+
+    fn sample<A: RaylibModelAnimation>(&self, time: f32, animations: &[A]) -> Vec<Transform> {
+        sample_animation(&animations[self.animation], time * self.fps, self.looping)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum FadeSource<S> {
+    /// The state being left keeps playing while it fades out
+    State { state: S, time: f32 },
+    /// A crossfade was interrupted, fade out from where it was
+    Pose(Vec<Transform>),
+}
+
+#[derive(Debug, Clone)]
+struct Fade<S> {
+    source: FadeSource<S>,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// Plays one [`AnimationState`] at a time and crossfades to the next one when told to.
+///
+/// States are identified by any hashable key, e.g. a `&'static str` or an enum. A transition is only taken if
+/// it was added with [`add_transition`](Self::add_transition), or with the default blend time if one is set.
+/// Exit transitions start by themselves so that their crossfade ends as a non-looping clip does.
+#[derive(Debug, Clone)]
+pub struct AnimationStateMachine<S> {
+    states: HashMap<S, AnimationState>,
+    transitions: HashMap<(S, S), f32>,
+    exits: HashMap<S, (S, f32)>,
+    default_blend: Option<f32>,
+    current: S,
+    time: f32,
+    fade: Option<Fade<S>>,
+    pose: Vec<Transform>,
+}
+
+impl<S: Clone + Eq + Hash> AnimationStateMachine<S> {
+    /// Creates a state machine playing `state` from the start.
+    pub fn new(initial: S, state: AnimationState) -> Self {
+        AnimationStateMachine {
+            states: HashMap::from([(initial.clone(), state)]),
+            transitions: HashMap::new(),
+            exits: HashMap::new(),
+            default_blend: None,
+            current: initial,
+            time: 0.0,
+            fade: None,
+            pose: Vec::new(),
+        }
+    }
+
+    /// Adds a state, or replaces the clip of an existing one.
+    pub fn add_state(&mut self, name: S, state: AnimationState) -> &mut Self {
+        self.states.insert(name, state);
+        self
+    }
+
+    /// Allows going from `from` to `to`, crossfading over `duration` seconds.
+    pub fn add_transition(&mut self, from: S, to: S, duration: f32) -> &mut Self {
+        self.transitions.insert((from, to), duration);
+        self
+    }
+
+    /// Goes from `from` to `to` when the non-looping clip of `from` ends, crossfading over `duration` seconds.
+    /// Also allows transitioning explicitly.
+    pub fn add_exit_transition(&mut self, from: S, to: S, duration: f32) -> &mut Self {
+        self.transitions
+            .insert((from.clone(), to.clone()), duration);
+        self.exits.insert(from, (to, duration));
+        self
+    }
+
+    /// Crossfade duration for transitions between states that have none of their own. `None`, the default,
+    /// only allows the transitions that were added.
+    pub fn set_default_blend(&mut self, duration: Option<f32>) -> &mut Self {
+        self.default_blend = duration;
+        self
+    }
+
+    #[inline]
+    pub fn current(&self) -> &S {
+        &self.current
+    }
+
+    /// Seconds since the current state was entered.
+    #[inline]
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    #[inline]
+    pub fn is_blending(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// The pose computed by the last [`update`](Self::update).
+    #[inline]
+    pub fn pose(&self) -> &[Transform] {
+        &self.pose
+    }
+
+    /// Starts crossfading to `to`. Does nothing if it's already the current state. Returns false and stays in
+    /// the current state if `to` is unknown or can't be reached from it.
+    pub fn transition_to(&mut self, to: S) -> bool {
+        if to == self.current {
+            return true;
+        }
+        if !self.states.contains_key(&to) {
+            return false;
+        }
+        let duration = self
+            .transitions
+            .get(&(self.current.clone(), to.clone()))
+            .copied()
+            .or(self.default_blend);
+        match duration {
+            Some(duration) => {
+                self.start(to, duration);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn start(&mut self, to: S, duration: f32) {
+        let from = std::mem::replace(&mut self.current, to);
+        let source = if self.fade.is_some() {
+            FadeSource::Pose(self.pose.clone())
+        } else {
+            FadeSource::State {
+                state: from,
+                time: self.time,
+            }
+        };
+        self.time = 0.0;
+        self.fade = (duration > 0.0).then_some(Fade {
+            source,
+            elapsed: 0.0,
+            duration,
+        });
+    }
+
+    /// Advances by `dt` seconds and returns the new pose. `animations` must share one skeleton.
+    ///
+    /// Panics if a state refers to an animation outside of `animations`.
+    pub fn update<A: RaylibModelAnimation>(&mut self, dt: f32, animations: &[A]) -> &[Transform] {
+        self.time += dt;
+        if let Some(fade) = &mut self.fade {
+            fade.elapsed += dt;
+            if let FadeSource::State { time, .. } = &mut fade.source {
+                *time += dt;
+            }
+        }
+
+        let state = self.states[&self.current];
+        if let Some((to, duration)) = self.exits.get(&self.current).cloned()
+            && !state.looping
+            && state.fps > 0.0
+        {
+            let frames = animations[state.animation].as_ref().frameCount;
+            let end = (frames - 1).max(0) as f32 / state.fps;
+            if self.time >= end - duration {
+                self.start(to, duration);
+            }
+        }
+
+        let state = self.states[&self.current];
+        let current = state.sample(self.time, animations);
+        let bones = animations.first().map_or(&[][..], |a| a.bones());
+        self.pose = match self.fade.take() {
+            Some(fade) if fade.elapsed < fade.duration => {
+                let from = match &fade.source {
+                    FadeSource::State { state, time } => {
+                        self.states[state].sample(*time, animations)
+                    }
+                    FadeSource::Pose(pose) => pose.clone(),
+                };
+                let pose = if from.len() == current.len() {
+                    blend_poses(bones, &from, &current, fade.elapsed / fade.duration, None)
+                } else {
+                    current
+                };
+                self.fade = Some(fade);
+                pose
+            }
+            _ => current,
+        };
+        &self.pose
+    }
+}
+
+#[cfg(test)]
+mod animation_test {
+    use super::*;
+
+    fn bone(name: &str, parent: i32) -> BoneInfo {
+        let mut info = ffi::BoneInfo {
+            name: [0; 32],
+            parent,
+        };
+        for (d, s) in info.name.iter_mut().zip(name.bytes()) {
+            *d = s as _;
+        }
+        BoneInfo(info)
+    }
+
+    /// A root with a child one unit up, the child rotated by `angle` around Z
+    fn arm(angle: f32) -> Vec<Transform> {
+        let root = Bone {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        };
+        let child = Bone {
+            translation: Vec3::Y,
+            rotation: Quat::from_rotation_z(angle),
+            ..root
+        };
+        vec![root.to_transform(), child.to_transform()]
+    }
+
+    fn rotation(t: &Transform) -> Quat {
+        Bone::from_transform(t).rotation
+    }
+
+    #[test]
+    fn test_local_round_trip() {
+        let bones = [bone("root", -1), bone("child", 0), bone("tip", 1)];
+        let pose = vec![
+            Bone {
+                translation: Vec3::new(1.0, 2.0, 3.0),
+                rotation: Quat::from_rotation_y(0.5),
+                scale: Vec3::splat(2.0),
+            }
+            .to_transform(),
+            Bone {
+                translation: Vec3::new(0.0, 4.0, 0.0),
+                rotation: Quat::from_rotation_x(1.0),
+                scale: Vec3::ONE,
+            }
+            .to_transform(),
+            Bone {
+                translation: Vec3::new(-1.0, 0.0, 2.0),
+                rotation: Quat::from_rotation_z(-0.3),
+                scale: Vec3::splat(0.5),
+            }
+            .to_transform(),
+        ];
+        let back = to_model(&bones, &to_local(&bones, &pose));
+        for (a, b) in pose.iter().zip(&back) {
+            assert!(a.translation.abs_diff_eq(b.translation, 1e-5));
+            assert!(a.scale.abs_diff_eq(b.scale, 1e-5));
+            assert!(rotation(a).abs_diff_eq(rotation(b), 1e-5));
+        }
+    }
+
+    #[test]
+    fn test_blend_and_mask() {
+        let bones = [bone("root", -1), bone("hand", 0)];
+        let a = arm(0.0);
+        let b = arm(1.0);
+        let half = blend_poses(&bones, &a, &b, 0.5, None);
+        assert!(rotation(&half[1]).abs_diff_eq(Quat::from_rotation_z(0.5), 1e-5));
+
+        let mask = BoneMask::branch(&bones, "hand", 0.0).unwrap();
+        let masked = blend_poses(&bones, &a, &b, 1.0, Some(&mask));
+        assert!(rotation(&masked[1]).abs_diff_eq(Quat::IDENTITY, 1e-5));
+        assert!(BoneMask::branch(&bones, "foot", 1.0).is_none());
+
+        // a masked parent carries its children along
+        let mut moved = b.clone();
+        moved[0].translation = Vec3::X;
+        moved[1].translation += Vec3::X;
+        let mask = BoneMask::branch(&bones, "root", 1.0).unwrap();
+        let mut hand_only = mask.clone();
+        hand_only.set_weight(0, 0.0);
+        assert_eq!(mask.weight(1), 1.0);
+        let blended = blend_poses(&bones, &a, &moved, 1.0, Some(&hand_only));
+        assert!(blended[1].translation.abs_diff_eq(Vec3::Y, 1e-5));
+    }
+
+    #[test]
+    fn test_skinning() {
+        let identity = Bone {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        };
+        let bind = [
+            identity.to_transform(),
+            Bone {
+                translation: Vec3::Y,
+                ..identity
+            }
+            .to_transform(),
+        ];
+        let pose = [
+            identity.to_transform(),
+            Bone {
+                translation: Vec3::new(1.0, 1.0, 0.0),
+                rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+                scale: Vec3::ONE,
+            }
+            .to_transform(),
+        ];
+        let matrices = skinning_matrices(&bind, &pose);
+        let vertices = [Vec3::new(0.0, 2.0, 0.0); 4];
+        let ids = [[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 0, 0], [7, 1, 0, 0]];
+        let weights = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0, 0.0],
+            [0.0; 4],
+            [1.0, 1.0, 0.0, 0.0],
+        ];
+        let skinned = skin_vertices(&vertices, &ids, &weights, &matrices);
+        assert!(skinned[0].abs_diff_eq(Vec3::new(0.0, 1.0, 0.0), 1e-5));
+        assert!(skinned[1].abs_diff_eq(Vec3::new(0.0, 1.5, 0.0), 1e-5));
+        assert_eq!(skinned[2], vertices[2]);
+        assert!(skinned[3].abs_diff_eq(Vec3::new(0.0, 1.0, 0.0), 1e-5));
+        assert_eq!(skin_vertices(&vertices, &[], &[], &matrices), vertices);
+
+        let m: Mat4 = pose[1].to_matrix().into();
+        assert!(
+            m.transform_point3(Vec3::X)
+                .abs_diff_eq(Vec3::new(1.0, 2.0, 0.0), 1e-5)
+        );
+        assert_eq!(Mat4::from(Matrix::from(m)), m);
+    }
+
+    #[test]
+    fn test_additive() {
+        let bones = [bone("root", -1), bone("hand", 0)];
+        let base = arm(0.5);
+        let added = add_pose(&bones, &base, &arm(0.3), &arm(0.1), 1.0, None);
+        assert!(rotation(&added[1]).abs_diff_eq(Quat::from_rotation_z(0.7), 1e-5));
+        let half = add_pose(&bones, &base, &arm(0.3), &arm(0.1), 0.5, None);
+        assert!(rotation(&half[1]).abs_diff_eq(Quat::from_rotation_z(0.6), 1e-5));
+    }
+}
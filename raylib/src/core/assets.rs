@@ -0,0 +1,208 @@
+//! Shared, reference counted assets loaded once per file.
+//!
+//! [`AssetCache`] keys assets by canonical path, so `"player.png"` and `"./assets/../player.png"` share one GPU
+//! upload. Handles are cheap to clone and the asset is unloaded when the last handle drops, whether or not the
+//! cache is still around.
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+
+use crate::core::audio::{RaylibAudio, Sound};
+use crate::core::models::Model;
+use crate::core::text::Font;
+use crate::core::texture::Texture2D;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::error::{LoadFontError, LoadModelError, LoadSoundError, LoadTextureError};
+use crate::ffi;
+
+/// A shared asset. Derefs to the owned asset, so the borrow can't outlive the handle.
+#[derive(Debug)]
+pub struct AssetHandle<T> {
+    owner: Rc<T>,
+}
+
+pub type TextureHandle = AssetHandle<Texture2D>;
+pub type FontHandle = AssetHandle<Font>;
+pub type ModelHandle = AssetHandle<Model>;
+/// Sounds need no handle type, since an [`Rc`] already derefs to the [`Sound`].
+pub type SoundHandle<'aud> = Rc<Sound<'aud>>;
+
+impl<T> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        AssetHandle {
+            owner: self.owner.clone(),
+        }
+    }
+}
+
+impl<T> Deref for AssetHandle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.owner
+    }
+}
+
+impl<T> AssetHandle<T> {
+    /// Number of handles sharing this asset
+    #[inline]
+    #[must_use]
+    pub fn handle_count(this: &Self) -> usize {
+        Rc::strong_count(&this.owner)
+    }
+
+    /// True if both handles share the same asset
+    #[inline]
+    #[must_use]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.owner, &other.owner)
+    }
+}
+
+impl AsRef<ffi::Texture2D> for TextureHandle {
+    fn as_ref(&self) -> &ffi::Texture2D {
+        &self.owner.0
+    }
+}
+
+impl AsRef<ffi::Font> for FontHandle {
+    fn as_ref(&self) -> &ffi::Font {
+        &self.owner.0
+    }
+}
+
+impl AsRef<ffi::Model> for ModelHandle {
+    fn as_ref(&self) -> &ffi::Model {
+        &self.owner.0
+    }
+}
+
+/// Loads textures, fonts, models and sounds at most once per file while any handle to them is alive.
+#[derive(Debug, Default)]
+pub struct AssetCache<'aud> {
+    textures: HashMap<PathBuf, Weak<Texture2D>>,
+    fonts: HashMap<PathBuf, Weak<Font>>,
+    models: HashMap<PathBuf, Weak<Model>>,
+    sounds: HashMap<PathBuf, Weak<Sound<'aud>>>,
+}
+
+impl<'aud> AssetCache<'aud> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the texture at `path`, loading it if no handle to it is alive.
+    pub fn load_texture(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: impl AsRef<Path>,
+    ) -> Result<TextureHandle, LoadTextureError> {
+        let owner = get_or_load(&mut self.textures, path.as_ref(), |f| {
+            rl.load_texture(thread, f)
+        })?;
+        Ok(AssetHandle { owner })
+    }
+
+    /// Returns the font at `path`, loading it if no handle to it is alive.
+    pub fn load_font(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: impl AsRef<Path>,
+    ) -> Result<FontHandle, LoadFontError> {
+        let owner = get_or_load(&mut self.fonts, path.as_ref(), |f| rl.load_font(thread, f))?;
+        Ok(AssetHandle { owner })
+    }
+
+    /// Returns the model at `path`, loading it if no handle to it is alive.
+    pub fn load_model(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: impl AsRef<Path>,
+    ) -> Result<ModelHandle, LoadModelError> {
+        let owner = get_or_load(&mut self.models, path.as_ref(), |f| {
+            rl.load_model(thread, f)
+        })?;
+        Ok(AssetHandle { owner })
+    }
+
+    /// Returns the sound at `path`, loading it if no handle to it is alive.
+    pub fn load_sound(
+        &mut self,
+        audio: &'aud RaylibAudio,
+        path: impl AsRef<Path>,
+    ) -> Result<SoundHandle<'aud>, LoadSoundError> {
+        get_or_load(&mut self.sounds, path.as_ref(), |f| audio.new_sound(f))
+    }
+
+    /// Number of assets with at least one live handle
+    #[must_use]
+    pub fn len(&self) -> usize {
+        fn live<T>(map: &HashMap<PathBuf, Weak<T>>) -> usize {
+            map.values().filter(|w| w.strong_count() > 0).count()
+        }
+        live(&self.textures) + live(&self.fonts) + live(&self.models) + live(&self.sounds)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets entries whose assets have already been unloaded.
+    pub fn purge(&mut self) {
+        self.textures.retain(|_, w| w.strong_count() > 0);
+        self.fonts.retain(|_, w| w.strong_count() > 0);
+        self.models.retain(|_, w| w.strong_count() > 0);
+        self.sounds.retain(|_, w| w.strong_count() > 0);
+    }
+}
+
+/// Looks `path` up by its canonical form, calling `load` on a miss. Paths that can't be canonicalized (usually
+/// because the file doesn't exist) are used as given, so `load` reports the error.
+fn get_or_load<T, E>(
+    map: &mut HashMap<PathBuf, Weak<T>>,
+    path: &Path,
+    load: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<Rc<T>, E> {
+    let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Some(asset) = map.get(&key).and_then(Weak::upgrade) {
+        return Ok(asset);
+    }
+    let asset = Rc::new(load(&path.to_string_lossy())?);
+    map.insert(key, Rc::downgrade(&asset));
+    Ok(asset)
+}
+
+#[cfg(test)]
+mod assets_test {
+    use super::*;
+
+    #[test]
+    fn test_get_or_load_dedupes() {
+        let mut map = HashMap::new();
+        let mut loads = 0;
+        let mut load = |name: &str| -> Result<String, ()> {
+            loads += 1;
+            Ok(name.to_owned())
+        };
+
+        let a = get_or_load(&mut map, Path::new("Cargo.toml"), &mut load).unwrap();
+        let b = get_or_load(&mut map, Path::new("./src/../Cargo.toml"), &mut load).unwrap();
+        assert!(Rc::ptr_eq(&a, &b));
+
+        drop((a, b));
+        get_or_load(&mut map, Path::new("Cargo.toml"), &mut load).unwrap();
+        assert_eq!(loads, 2);
+    }
+
+    #[test]
+    fn test_get_or_load_error_is_not_cached() {
+        let mut map: HashMap<PathBuf, Weak<()>> = HashMap::new();
+        assert!(get_or_load(&mut map, Path::new("missing.png"), |_| Err(())).is_err());
+        assert!(map.is_empty());
+    }
+}
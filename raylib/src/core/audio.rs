@@ -498,12 +498,24 @@ impl<'aud, 'bind> SoundAlias<'aud, 'bind> {
     }
 }
 
+impl AsRef<ffi::AudioStream> for SoundAlias<'_, '_> {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        &self.0.stream
+    }
+}
+
 impl Drop for SoundAlias<'_, '_> {
     fn drop(&mut self) {
         unsafe { ffi::UnloadSoundAlias(self.0) }
     }
 }
 
+impl<'aud> AsRef<ffi::AudioStream> for Music<'aud> {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        &self.0.stream
+    }
+}
+
 impl<'aud> Music<'aud> {
     /// Starts music playing.
     #[inline]
@@ -588,6 +600,12 @@ impl<'aud> Music<'aud> {
     }
 }
 
+impl<'aud> AsRef<ffi::AudioStream> for AudioStream<'aud> {
+    fn as_ref(&self) -> &ffi::AudioStream {
+        &self.0
+    }
+}
+
 impl<'aud> AudioStream<'aud> {
     /// Checks if an audio stream is valid (buffers initialized)
     #[inline]
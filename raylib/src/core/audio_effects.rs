@@ -0,0 +1,779 @@
+//! DSP effects for sounds, music and audio streams.
+//!
+//! An [`AudioEffectChain`] runs [`AudioEffect`] nodes in order over interleaved `f32` samples. Attached to a
+//! stream it runs on the audio thread; [`AudioEffectChain::process_wave`] runs it offline over a [`Wave`].
+//! Node parameters are [`Param`]s, atomics that the game thread can change while the audio thread reads them.
+//! ```ignore
+//! let lowpass = Biquad::low_pass(800.0, 0.707);
+//! let cutoff = lowpass.frequency.clone();
+//! let mut chain = AudioEffectChain::new();
+//! chain.push(lowpass).push(Delay::new(0.25, 0.4, 0.3));
+//! let _effects = chain.attach(&music)?;
+//! // underwater until the player surfaces
+//! cutoff.set(20000.0);
+//! ```
+use std::f32::consts::PI;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::core::audio::Wave;
+use crate::core::callbacks::stream_processor_with_user_data_wrapper::{
+    AudioCallbackWithUserData, attach_audio_stream_processor_with_user_data,
+    detach_audio_stream_processor_with_user_data,
+};
+use crate::core::error::AudioCallbackError;
+use crate::ffi;
+
+/// Channels of raylib's mixing format (`AUDIO_DEVICE_CHANNELS`). raylib converts every stream to it before
+/// processors run, so attached effects see this many channels whatever the stream's own `channels()` are.
+pub const MIXER_CHANNELS: u32 = 2;
+
+/// Sample rate attached chains run at unless told otherwise. Processors see samples at the playback device's
+/// rate, which raylib doesn't report; 48 kHz is what miniaudio and most devices use.
+pub const DEFAULT_MIXER_SAMPLE_RATE: u32 = 48000;
+
+/// An `f32` shared between the game and the audio thread without locking
+#[derive(Debug, Clone)]
+pub struct Param(Arc<AtomicU32>);
+
+impl Param {
+    pub fn new(value: f32) -> Self {
+        Self(Arc::new(AtomicU32::new(value.to_bits())))
+    }
+
+    #[inline]
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub fn set(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed)
+    }
+}
+
+/// A node of an [`AudioEffectChain`]
+pub trait AudioEffect: Send {
+    /// Sets up for a sample format, before the first [`process`](AudioEffect::process) and whenever the format
+    /// changes. Allocations belong here rather than in `process`, which runs on the audio thread.
+    fn prepare(&mut self, sample_rate: u32, channels: u32);
+
+    /// Processes interleaved frames in place
+    fn process(&mut self, samples: &mut [f32]);
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Multiplies samples by a linear gain
+#[derive(Debug, Clone)]
+pub struct Gain {
+    pub gain: Param,
+}
+
+impl Gain {
+    pub fn new(gain: f32) -> Self {
+        Self {
+            gain: Param::new(gain),
+        }
+    }
+}
+
+impl AudioEffect for Gain {
+    fn prepare(&mut self, _: u32, _: u32) {}
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let gain = self.gain.get();
+        samples.iter_mut().for_each(|s| *s *= gain);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiquadKind {
+    LowPass,
+    HighPass,
+}
+
+/// Second order low or high pass filter (RBJ cookbook coefficients)
+#[derive(Debug, Clone)]
+pub struct Biquad {
+    pub kind: BiquadKind,
+    /// Cutoff in Hz
+    pub frequency: Param,
+    /// Resonance, 0.707 for a flat pass band
+    pub q: Param,
+    sample_rate: f32,
+    channels: usize,
+    /// `(frequency, q)` the coefficients were computed for
+    designed: (f32, f32),
+    /// b0, b1, b2, a1, a2, normalized by a0
+    coefficients: [f32; 5],
+    /// Transposed direct form II state per channel
+    state: Vec<[f32; 2]>,
+}
+
+impl Biquad {
+    pub fn new(kind: BiquadKind, frequency: f32, q: f32) -> Self {
+        Self {
+            kind,
+            frequency: Param::new(frequency),
+            q: Param::new(q),
+            sample_rate: DEFAULT_MIXER_SAMPLE_RATE as f32,
+            channels: 0,
+            designed: (f32::NAN, f32::NAN),
+            coefficients: [1.0, 0.0, 0.0, 0.0, 0.0],
+            state: Vec::new(),
+        }
+    }
+
+    pub fn low_pass(frequency: f32, q: f32) -> Self {
+        Self::new(BiquadKind::LowPass, frequency, q)
+    }
+
+    pub fn high_pass(frequency: f32, q: f32) -> Self {
+        Self::new(BiquadKind::HighPass, frequency, q)
+    }
+
+    fn design(&mut self, frequency: f32, q: f32) {
+        self.designed = (frequency, q);
+        let frequency = frequency.clamp(10.0, self.sample_rate * 0.49);
+        let w0 = 2.0 * PI * frequency / self.sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q.max(0.01));
+        let (b0, b1, b2) = match self.kind {
+            BiquadKind::LowPass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0),
+            BiquadKind::HighPass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0),
+        };
+        let a0 = 1.0 + alpha;
+        self.coefficients = [
+            b0 / a0,
+            b1 / a0,
+            b2 / a0,
+            -2.0 * cos / a0,
+            (1.0 - alpha) / a0,
+        ];
+    }
+}
+
+impl AudioEffect for Biquad {
+    fn prepare(&mut self, sample_rate: u32, channels: u32) {
+        self.sample_rate = sample_rate as f32;
+        self.channels = channels as usize;
+        self.state = vec![[0.0; 2]; self.channels];
+        self.designed = (f32::NAN, f32::NAN);
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let (frequency, q) = (self.frequency.get(), self.q.get());
+        if (frequency, q) != self.designed {
+            self.design(frequency, q);
+        }
+        let [b0, b1, b2, a1, a2] = self.coefficients;
+        for frame in samples.chunks_exact_mut(self.channels.max(1)) {
+            for (sample, [z1, z2]) in frame.iter_mut().zip(&mut self.state) {
+                let x = *sample;
+                let y = b0 * x + *z1;
+                *z1 = b1 * x - a1 * y + *z2;
+                *z2 = b2 * x - a2 * y;
+                *sample = y;
+            }
+        }
+    }
+}
+
+/// Echo: a feedback delay line per channel
+#[derive(Debug, Clone)]
+pub struct Delay {
+    /// Delay in seconds, up to the maximum given at creation
+    pub time: Param,
+    /// Share of the echo fed back into the line, below 1
+    pub feedback: Param,
+    /// 0 for the dry signal only, 1 for the echoes only
+    pub mix: Param,
+    max_time: f32,
+    sample_rate: f32,
+    channels: usize,
+    lines: Vec<Vec<f32>>,
+    write: usize,
+}
+
+impl Delay {
+    /// A delay of at most two seconds
+    pub fn new(time: f32, feedback: f32, mix: f32) -> Self {
+        Self::with_max_time(time, feedback, mix, 2.0)
+    }
+
+    pub fn with_max_time(time: f32, feedback: f32, mix: f32, max_time: f32) -> Self {
+        Self {
+            time: Param::new(time),
+            feedback: Param::new(feedback),
+            mix: Param::new(mix),
+            max_time: max_time.max(0.0),
+            sample_rate: DEFAULT_MIXER_SAMPLE_RATE as f32,
+            channels: 0,
+            lines: Vec::new(),
+            write: 0,
+        }
+    }
+}
+
+impl AudioEffect for Delay {
+    fn prepare(&mut self, sample_rate: u32, channels: u32) {
+        self.sample_rate = sample_rate as f32;
+        self.channels = channels as usize;
+        // at least one sample of delay, so `process` always has a valid range to clamp to
+        let length = ((self.max_time * self.sample_rate).ceil() as usize).max(1) + 1;
+        self.lines = vec![vec![0.0; length]; self.channels];
+        self.write = 0;
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let Some(length) = self.lines.first().map(Vec::len) else {
+            return;
+        };
+        let delay = ((self.time.get() * self.sample_rate) as usize).clamp(1, length - 1);
+        let feedback = self.feedback.get().clamp(0.0, 0.99);
+        let mix = self.mix.get().clamp(0.0, 1.0);
+        for frame in samples.chunks_exact_mut(self.channels) {
+            let read = (self.write + length - delay) % length;
+            for (sample, line) in frame.iter_mut().zip(&mut self.lines) {
+                let echo = line[read];
+                line[self.write] = *sample + echo * feedback;
+                *sample = *sample * (1.0 - mix) + echo * mix;
+            }
+            self.write = (self.write + 1) % length;
+        }
+    }
+}
+
+/// Freeverb comb and allpass lengths at 44.1 kHz
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+/// Extra length for odd channels, which decorrelates left and right
+const STEREO_SPREAD: usize = 23;
+
+#[derive(Debug, Clone)]
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    filtered: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+/// Room reverb after Jezar's Freeverb: eight damped combs and four allpasses per channel
+#[derive(Debug, Clone)]
+pub struct Reverb {
+    /// 0 to 1, longer tails as it grows
+    pub room_size: Param,
+    /// 0 to 1, darker tails as it grows
+    pub damping: Param,
+    /// 0 for the dry signal only, 1 for the reverb only
+    pub mix: Param,
+    channels: usize,
+    combs: Vec<Vec<Comb>>,
+    allpasses: Vec<Vec<Allpass>>,
+}
+
+impl Reverb {
+    pub fn new(room_size: f32, damping: f32, mix: f32) -> Self {
+        Self {
+            room_size: Param::new(room_size),
+            damping: Param::new(damping),
+            mix: Param::new(mix),
+            channels: 0,
+            combs: Vec::new(),
+            allpasses: Vec::new(),
+        }
+    }
+}
+
+impl AudioEffect for Reverb {
+    fn prepare(&mut self, sample_rate: u32, channels: u32) {
+        let scale = sample_rate as f32 / 44100.0;
+        let length = |tuning: usize, channel: usize| {
+            (((tuning + STEREO_SPREAD * (channel % 2)) as f32 * scale) as usize).max(1)
+        };
+        self.channels = channels as usize;
+        self.combs = (0..self.channels)
+            .map(|channel| {
+                COMB_TUNING
+                    .iter()
+                    .map(|&tuning| Comb {
+                        buffer: vec![0.0; length(tuning, channel)],
+                        index: 0,
+                        filtered: 0.0,
+                    })
+                    .collect()
+            })
+            .collect();
+        self.allpasses = (0..self.channels)
+            .map(|channel| {
+                ALLPASS_TUNING
+                    .iter()
+                    .map(|&tuning| Allpass {
+                        buffer: vec![0.0; length(tuning, channel)],
+                        index: 0,
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        if self.channels == 0 {
+            return;
+        }
+        let feedback = self.room_size.get().clamp(0.0, 1.0) * 0.28 + 0.7;
+        let damping = self.damping.get().clamp(0.0, 1.0) * 0.4;
+        let mix = self.mix.get().clamp(0.0, 1.0);
+        for frame in samples.chunks_exact_mut(self.channels) {
+            for ((sample, combs), allpasses) in frame
+                .iter_mut()
+                .zip(&mut self.combs)
+                .zip(&mut self.allpasses)
+            {
+                let input = *sample * 0.015;
+                let mut wet = 0.0;
+                for comb in combs.iter_mut() {
+                    let out = comb.buffer[comb.index];
+                    comb.filtered = out * (1.0 - damping) + comb.filtered * damping;
+                    comb.buffer[comb.index] = input + comb.filtered * feedback;
+                    comb.index = (comb.index + 1) % comb.buffer.len();
+                    wet += out;
+                }
+                for allpass in allpasses.iter_mut() {
+                    let out = allpass.buffer[allpass.index];
+                    allpass.buffer[allpass.index] = wet + out * 0.5;
+                    allpass.index = (allpass.index + 1) % allpass.buffer.len();
+                    wet = out - wet;
+                }
+                *sample = *sample * (1.0 - mix) + wet * 3.0 * mix;
+            }
+        }
+    }
+}
+
+/// Downward compressor with a peak envelope shared by all channels, so the stereo image doesn't shift
+#[derive(Debug, Clone)]
+pub struct Compressor {
+    /// Level in dBFS above which the signal is compressed
+    pub threshold: Param,
+    /// Input dB above the threshold per output dB, 1 for no compression
+    pub ratio: Param,
+    /// Seconds
+    pub attack: Param,
+    /// Seconds
+    pub release: Param,
+    /// Gain in dB applied after compression
+    pub makeup: Param,
+    sample_rate: f32,
+    channels: usize,
+    envelope: f32,
+}
+
+impl Compressor {
+    pub fn new(threshold: f32, ratio: f32) -> Self {
+        Self {
+            threshold: Param::new(threshold),
+            ratio: Param::new(ratio),
+            attack: Param::new(0.005),
+            release: Param::new(0.1),
+            makeup: Param::new(0.0),
+            sample_rate: DEFAULT_MIXER_SAMPLE_RATE as f32,
+            channels: 0,
+            envelope: 0.0,
+        }
+    }
+}
+
+impl AudioEffect for Compressor {
+    fn prepare(&mut self, sample_rate: u32, channels: u32) {
+        self.sample_rate = sample_rate as f32;
+        self.channels = channels as usize;
+        self.envelope = 0.0;
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        if self.channels == 0 {
+            return;
+        }
+        let smoothing = |seconds: f32| (-1.0 / (seconds.max(1e-5) * self.sample_rate)).exp();
+        let (attack, release) = (smoothing(self.attack.get()), smoothing(self.release.get()));
+        let threshold = self.threshold.get();
+        let slope = 1.0 - 1.0 / self.ratio.get().max(1.0);
+        let makeup = self.makeup.get();
+        for frame in samples.chunks_exact_mut(self.channels) {
+            let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            let coefficient = if peak > self.envelope {
+                attack
+            } else {
+                release
+            };
+            self.envelope = peak + (self.envelope - peak) * coefficient;
+            let over = 20.0 * self.envelope.max(1e-9).log10() - threshold;
+            let gain = db_to_gain(makeup - over.max(0.0) * slope);
+            frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+/// Lo-fi effect that lowers the bit depth and holds samples to lower the rate
+#[derive(Debug, Clone)]
+pub struct Bitcrusher {
+    /// 1 to 24, fractional values allowed
+    pub bits: Param,
+    /// Frames each sample is held for, 1 keeps the rate
+    pub downsample: Param,
+    channels: usize,
+    held: Vec<f32>,
+    phase: f32,
+}
+
+impl Bitcrusher {
+    pub fn new(bits: f32, downsample: f32) -> Self {
+        Self {
+            bits: Param::new(bits),
+            downsample: Param::new(downsample),
+            channels: 0,
+            held: Vec::new(),
+            phase: 0.0,
+        }
+    }
+}
+
+impl AudioEffect for Bitcrusher {
+    fn prepare(&mut self, _: u32, channels: u32) {
+        self.channels = channels as usize;
+        self.held = vec![0.0; self.channels];
+        self.phase = 0.0;
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        if self.channels == 0 {
+            return;
+        }
+        let steps = 2f32.powf(self.bits.get().clamp(1.0, 24.0) - 1.0);
+        let downsample = self.downsample.get().max(1.0);
+        for frame in samples.chunks_exact_mut(self.channels) {
+            if self.phase <= 0.0 {
+                self.phase += downsample;
+                for (held, &sample) in self.held.iter_mut().zip(frame.iter()) {
+                    *held = (sample * steps).round() / steps;
+                }
+            }
+            self.phase -= 1.0;
+            frame.copy_from_slice(&self.held);
+        }
+    }
+}
+
+/// [`AudioEffect`]s run one after the other. A chain is an effect itself, so chains nest.
+pub struct AudioEffectChain {
+    effects: Vec<Box<dyn AudioEffect>>,
+    mixer_sample_rate: u32,
+    /// `(sample_rate, channels)` the effects are prepared for
+    format: Option<(u32, u32)>,
+}
+
+impl Default for AudioEffectChain {
+    fn default() -> Self {
+        Self {
+            effects: Vec::new(),
+            mixer_sample_rate: DEFAULT_MIXER_SAMPLE_RATE,
+            format: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for AudioEffectChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioEffectChain")
+            .field("effects", &self.effects.len())
+            .field("mixer_sample_rate", &self.mixer_sample_rate)
+            .finish()
+    }
+}
+
+impl AudioEffectChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an effect, which then runs after the ones already in the chain
+    pub fn push(&mut self, effect: impl AudioEffect + 'static) -> &mut Self {
+        let mut effect = Box::new(effect);
+        if let Some((sample_rate, channels)) = self.format {
+            effect.prepare(sample_rate, channels);
+        }
+        self.effects.push(effect);
+        self
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Sample rate [`attach`](AudioEffectChain::attach) prepares for, when the device doesn't run at
+    /// [`DEFAULT_MIXER_SAMPLE_RATE`]
+    pub fn set_mixer_sample_rate(&mut self, sample_rate: u32) -> &mut Self {
+        self.mixer_sample_rate = sample_rate;
+        self
+    }
+
+    /// Runs the chain over interleaved samples of the given format, preparing the effects first if the format
+    /// changed
+    pub fn process_with_format(&mut self, samples: &mut [f32], sample_rate: u32, channels: u32) {
+        if self.format != Some((sample_rate, channels)) {
+            self.prepare(sample_rate, channels);
+        }
+        self.process(samples);
+    }
+
+    /// Runs the chain offline over a wave, converting it to 32-bit float samples first
+    pub fn process_wave(&mut self, wave: &mut Wave) {
+        let (sample_rate, channels) = (wave.sample_rate(), wave.channels());
+        if wave.sample_size() != 32 {
+            wave.format(sample_rate as i32, 32, channels as i32);
+        }
+        if wave.data.is_null() {
+            return;
+        }
+        let samples = unsafe {
+            std::slice::from_raw_parts_mut(
+                wave.data as *mut f32,
+                wave.frame_count() as usize * channels as usize,
+            )
+        };
+        self.process_with_format(samples, sample_rate, channels);
+    }
+
+    /// Processes a [`Sound`](crate::core::audio::Sound), [`Music`](crate::core::audio::Music) or
+    /// [`AudioStream`](crate::core::audio::AudioStream) on the audio thread until the returned handle is dropped.
+    /// Effects are prepared for raylib's mixing format: [`MIXER_CHANNELS`] at the mixer sample rate.
+    ///
+    /// Attached chains share the [`AUDIO_CALLBACK_SLOTS`](crate::core::callbacks::AUDIO_CALLBACK_SLOTS) slots of
+    /// [`attach_audio_stream_processor_to_music`](crate::core::callbacks::attach_audio_stream_processor_to_music)
+    /// and fail with [`AudioCallbackError::NoFreeSlot`] when none is left.
+    pub fn attach<S: AsRef<ffi::AudioStream>>(
+        mut self,
+        stream: &S,
+    ) -> Result<AttachedEffectChain<'_, S>, AudioCallbackError> {
+        self.prepare(self.mixer_sample_rate, MIXER_CHANNELS);
+        let stream = *stream.as_ref();
+        let chain = Box::into_raw(Box::new(self));
+        let callback_index = attach_audio_stream_processor_with_user_data(
+            stream,
+            AudioCallbackWithUserData::new(
+                chain as *mut std::ffi::c_void,
+                AttachedEffectChain::<S>::c_callback,
+            ),
+        )
+        .inspect_err(|_| drop(unsafe { Box::from_raw(chain) }))?;
+        Ok(AttachedEffectChain {
+            stream,
+            chain,
+            callback_index,
+            _stream: PhantomData,
+        })
+    }
+}
+
+impl AudioEffect for AudioEffectChain {
+    fn prepare(&mut self, sample_rate: u32, channels: u32) {
+        self.format = Some((sample_rate, channels));
+        for effect in &mut self.effects {
+            effect.prepare(sample_rate, channels);
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        for effect in &mut self.effects {
+            effect.process(samples);
+        }
+    }
+}
+
+/// An [`AudioEffectChain`] processing a stream. Dropping it, or [`detach`](AttachedEffectChain::detach)ing it,
+/// removes the chain from the stream.
+pub struct AttachedEffectChain<'s, S> {
+    stream: ffi::AudioStream,
+    /// Owned, turned back into a box once raylib no longer calls the processor
+    chain: *mut AudioEffectChain,
+    callback_index: usize,
+    _stream: PhantomData<&'s S>,
+}
+
+impl<S> std::fmt::Debug for AttachedEffectChain<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AttachedEffectChain")
+            .field("callback_index", &self.callback_index)
+            .finish()
+    }
+}
+
+impl<S> AttachedEffectChain<'_, S> {
+    extern "C" fn c_callback(
+        user_data: *mut std::ffi::c_void,
+        data_ptr: *mut std::ffi::c_void,
+        frames: u32,
+    ) {
+        // Safety: raylib only calls the processor between attach and detach, one call at a time
+        let chain = unsafe { &mut *(user_data as *mut AudioEffectChain) };
+        let samples = unsafe {
+            std::slice::from_raw_parts_mut(data_ptr as *mut f32, (frames * MIXER_CHANNELS) as usize)
+        };
+        chain.process(samples);
+    }
+
+    /// Removes the chain from the stream and hands it back
+    pub fn detach(mut self) -> AudioEffectChain {
+        *self.release()
+    }
+
+    fn release(&mut self) -> Box<AudioEffectChain> {
+        detach_audio_stream_processor_with_user_data(self.stream, self.callback_index);
+        let chain = unsafe { Box::from_raw(self.chain) };
+        self.chain = std::ptr::null_mut();
+        chain
+    }
+}
+
+impl<S> Drop for AttachedEffectChain<'_, S> {
+    fn drop(&mut self) {
+        if !self.chain.is_null() {
+            self.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod audio_effects_test {
+    use super::*;
+
+    const RATE: u32 = 48000;
+
+    fn sine(frequency: f32, frames: usize, channels: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s = (2.0 * PI * frequency * i as f32 / RATE as f32).sin();
+                std::iter::repeat_n(s, channels)
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn run(effect: impl AudioEffect + 'static, samples: &mut [f32], channels: u32) {
+        let mut chain = AudioEffectChain::new();
+        chain.push(effect);
+        chain.process_with_format(samples, RATE, channels);
+    }
+
+    #[test]
+    fn test_gain_param() {
+        let gain = Gain::new(0.5);
+        let volume = gain.gain.clone();
+        let mut chain = AudioEffectChain::new();
+        chain.push(gain);
+        let mut samples = vec![1.0; 4];
+        chain.process_with_format(&mut samples, RATE, 2);
+        assert_eq!(samples, [0.5; 4]);
+        volume.set(2.0);
+        chain.process_with_format(&mut samples, RATE, 2);
+        assert_eq!(samples, [1.0; 4]);
+    }
+
+    #[test]
+    fn test_biquad() {
+        let mut low = sine(100.0, 4800, 2);
+        let mut high = sine(8000.0, 4800, 2);
+        run(Biquad::low_pass(1000.0, 0.707), &mut low, 2);
+        run(Biquad::low_pass(1000.0, 0.707), &mut high, 2);
+        assert!(rms(&low[2400..]) > 0.65);
+        assert!(rms(&high[2400..]) < 0.02);
+
+        let mut low = sine(100.0, 4800, 1);
+        let mut high = sine(8000.0, 4800, 1);
+        run(Biquad::high_pass(1000.0, 0.707), &mut low, 1);
+        run(Biquad::high_pass(1000.0, 0.707), &mut high, 1);
+        assert!(rms(&low[2400..]) < 0.02);
+        assert!(rms(&high[2400..]) > 0.65);
+    }
+
+    #[test]
+    fn test_delay() {
+        let mut samples = vec![0.0; 2 * 100];
+        samples[0] = 1.0;
+        samples[1] = -1.0;
+        let delay = Delay::new(10.0 / RATE as f32, 0.5, 0.5);
+        run(delay, &mut samples, 2);
+        assert_eq!(&samples[..2], [0.5, -0.5]);
+        assert_eq!(&samples[20..22], [0.5, -0.5]);
+        assert_eq!(&samples[40..42], [0.25, -0.25]);
+        assert_eq!(samples.iter().filter(|s| **s != 0.0).count(), 2 * 10);
+    }
+
+    #[test]
+    fn test_reverb_tail() {
+        let mut samples = vec![0.0; 2 * RATE as usize / 2];
+        samples[0] = 1.0;
+        samples[1] = 1.0;
+        run(Reverb::new(0.8, 0.5, 1.0), &mut samples, 2);
+        let tail = &samples[2 * 4800..];
+        assert!(rms(tail) > 1e-4);
+        // the odd channel has longer lines, so the channels differ
+        assert!(tail.chunks(2).any(|f| f[0] != f[1]));
+    }
+
+    #[test]
+    fn test_compressor() {
+        let mut loud = sine(440.0, 9600, 2);
+        let mut quiet: Vec<f32> = sine(440.0, 9600, 2).iter().map(|s| s * 0.05).collect();
+        let quiet_rms = rms(&quiet);
+        run(Compressor::new(-20.0, 4.0), &mut loud, 2);
+        run(Compressor::new(-20.0, 4.0), &mut quiet, 2);
+        // 0 dBFS peaks are 20 dB over, which a 4:1 ratio turns into 5
+        assert!((rms(&loud[4800..]) / 0.707 - db_to_gain(-15.0)).abs() < 0.03);
+        assert!((rms(&quiet) - quiet_rms).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_delay_without_max_time() {
+        let mut samples = vec![1.0, 0.0, 0.0];
+        run(Delay::with_max_time(0.5, 0.5, 0.5, 0.0), &mut samples, 1);
+        assert_eq!(samples, [0.5, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_bitcrusher() {
+        let mut samples: Vec<f32> = (0..8).map(|i| i as f32 / 10.0).collect();
+        run(Bitcrusher::new(2.0, 2.0), &mut samples, 1);
+        assert_eq!(samples, [0.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_nested_chain() {
+        let mut inner = AudioEffectChain::new();
+        inner.push(Gain::new(2.0)).push(Gain::new(3.0));
+        let mut outer = AudioEffectChain::new();
+        outer.push(inner).push(Bitcrusher::new(24.0, 1.0));
+        let mut samples = vec![0.1; 6];
+        outer.process_with_format(&mut samples, RATE, 3);
+        assert!(samples.iter().all(|s| (s - 0.6).abs() < 1e-6));
+    }
+}
@@ -5,6 +5,10 @@ use std::{
 };
 
 use crate::{ffi, RaylibHandle};
+use crate::error::AllocationError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
 pub struct AutomationEventIter<'a> {
@@ -138,6 +142,246 @@ impl AutomationEvent {
     }
 }
 
+impl AutomationEvent {
+    /// Create an event from its typed representation
+    #[inline]
+    #[must_use]
+    pub fn new(frame: u32, kind: AutomationEventKind) -> Self {
+        let (type_, params) = kind.to_raw();
+        AutomationEvent(ffi::AutomationEvent {
+            frame,
+            type_,
+            params,
+        })
+    }
+    /// Typed event data. Unknown event types are returned as [`AutomationEventKind::Unknown`]
+    #[inline]
+    #[must_use]
+    pub const fn kind(&self) -> AutomationEventKind {
+        AutomationEventKind::from_raw(self.0.type_, self.0.params)
+    }
+}
+
+impl Clone for AutomationEvent {
+    fn clone(&self) -> Self {
+        AutomationEvent(self.0)
+    }
+}
+
+impl PartialEq for AutomationEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.frame == other.0.frame
+            && self.0.type_ == other.0.type_
+            && self.0.params == other.0.params
+    }
+}
+
+// Serialized as `{ frame, kind }` so replays stay readable and editable.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "AutomationEvent")]
+struct AutomationEventRepr {
+    frame: u32,
+    kind: AutomationEventKind,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for AutomationEvent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AutomationEventRepr {
+            frame: self.frame(),
+            kind: self.kind(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for AutomationEvent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = AutomationEventRepr::deserialize(deserializer)?;
+        Ok(AutomationEvent::new(repr.frame, repr.kind))
+    }
+}
+
+/// Typed version of raylib's `AutomationEventType` and its parameters.
+///
+/// Keys, buttons, axes and gestures are stored as their raylib values (e.g. `KeyboardKey::KEY_W as i32`)
+/// so events from any raylib version round-trip unchanged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AutomationEventKind {
+    None,
+    KeyUp {
+        key: i32,
+    },
+    KeyDown {
+        key: i32,
+    },
+    KeyPressed {
+        key: i32,
+    },
+    KeyReleased {
+        key: i32,
+    },
+    MouseButtonUp {
+        button: i32,
+    },
+    MouseButtonDown {
+        button: i32,
+    },
+    MouseMove {
+        x: i32,
+        y: i32,
+    },
+    MouseWheel {
+        x: i32,
+        y: i32,
+    },
+    GamepadConnect {
+        gamepad: i32,
+    },
+    GamepadDisconnect {
+        gamepad: i32,
+    },
+    GamepadButtonUp {
+        gamepad: i32,
+        button: i32,
+    },
+    GamepadButtonDown {
+        gamepad: i32,
+        button: i32,
+    },
+    /// `value` is the axis position scaled by 32768
+    GamepadAxis {
+        gamepad: i32,
+        axis: i32,
+        value: i32,
+    },
+    TouchUp {
+        id: i32,
+    },
+    TouchDown {
+        id: i32,
+    },
+    TouchMove {
+        id: i32,
+        x: i32,
+        y: i32,
+    },
+    Gesture {
+        gesture: i32,
+    },
+    WindowClose,
+    WindowMaximize,
+    WindowMinimize,
+    WindowResize {
+        width: i32,
+        height: i32,
+    },
+    TakeScreenshot,
+    SetTargetFps {
+        fps: i32,
+    },
+    /// An event type this version does not know about
+    Unknown {
+        event_type: u32,
+        params: [i32; 4],
+    },
+}
+
+impl AutomationEventKind {
+    /// Decode a raw event type and its parameters
+    #[must_use]
+    pub const fn from_raw(event_type: u32, p: [i32; 4]) -> Self {
+        use AutomationEventKind::*;
+        match event_type {
+            0 => None,
+            1 => KeyUp { key: p[0] },
+            2 => KeyDown { key: p[0] },
+            3 => KeyPressed { key: p[0] },
+            4 => KeyReleased { key: p[0] },
+            5 => MouseButtonUp { button: p[0] },
+            6 => MouseButtonDown { button: p[0] },
+            7 => MouseMove { x: p[0], y: p[1] },
+            8 => MouseWheel { x: p[0], y: p[1] },
+            9 => GamepadConnect { gamepad: p[0] },
+            10 => GamepadDisconnect { gamepad: p[0] },
+            11 => GamepadButtonUp {
+                gamepad: p[0],
+                button: p[1],
+            },
+            12 => GamepadButtonDown {
+                gamepad: p[0],
+                button: p[1],
+            },
+            13 => GamepadAxis {
+                gamepad: p[0],
+                axis: p[1],
+                value: p[2],
+            },
+            14 => TouchUp { id: p[0] },
+            15 => TouchDown { id: p[0] },
+            16 => TouchMove {
+                id: p[0],
+                x: p[1],
+                y: p[2],
+            },
+            17 => Gesture { gesture: p[0] },
+            18 => WindowClose,
+            19 => WindowMaximize,
+            20 => WindowMinimize,
+            21 => WindowResize {
+                width: p[0],
+                height: p[1],
+            },
+            22 => TakeScreenshot,
+            23 => SetTargetFps { fps: p[0] },
+            _ => Unknown {
+                event_type,
+                params: p,
+            },
+        }
+    }
+
+    /// Encode into raylib's event type and parameters
+    #[must_use]
+    pub const fn to_raw(&self) -> (u32, [i32; 4]) {
+        use AutomationEventKind::*;
+        match *self {
+            None => (0, [0; 4]),
+            KeyUp { key } => (1, [key, 0, 0, 0]),
+            KeyDown { key } => (2, [key, 0, 0, 0]),
+            KeyPressed { key } => (3, [key, 0, 0, 0]),
+            KeyReleased { key } => (4, [key, 0, 0, 0]),
+            MouseButtonUp { button } => (5, [button, 0, 0, 0]),
+            MouseButtonDown { button } => (6, [button, 0, 0, 0]),
+            MouseMove { x, y } => (7, [x, y, 0, 0]),
+            MouseWheel { x, y } => (8, [x, y, 0, 0]),
+            GamepadConnect { gamepad } => (9, [gamepad, 0, 0, 0]),
+            GamepadDisconnect { gamepad } => (10, [gamepad, 0, 0, 0]),
+            GamepadButtonUp { gamepad, button } => (11, [gamepad, button, 0, 0]),
+            GamepadButtonDown { gamepad, button } => (12, [gamepad, button, 0, 0]),
+            GamepadAxis {
+                gamepad,
+                axis,
+                value,
+            } => (13, [gamepad, axis, value, 0]),
+            TouchUp { id } => (14, [id, 0, 0, 0]),
+            TouchDown { id } => (15, [id, 0, 0, 0]),
+            TouchMove { id, x, y } => (16, [id, x, y, 0]),
+            Gesture { gesture } => (17, [gesture, 0, 0, 0]),
+            WindowClose => (18, [0; 4]),
+            WindowMaximize => (19, [0; 4]),
+            WindowMinimize => (20, [0; 4]),
+            WindowResize { width, height } => (21, [width, height, 0, 0]),
+            TakeScreenshot => (22, [0; 4]),
+            SetTargetFps { fps } => (23, [fps, 0, 0, 0]),
+            Unknown { event_type, params } => (event_type, params),
+        }
+    }
+}
+
 impl AutomationEvent {
     /// Play a recorded automation event
     #[inline]
@@ -162,6 +406,28 @@ impl RaylibHandle {
             None => AutomationEventList(unsafe { ffi::LoadAutomationEventList(null()) }),
         }
     }
+    /// Build an automation event list from events created on the Rust side, e.g. a deserialized replay.
+    /// The list is allocated by raylib with a capacity of MAX_AUTOMATION_EVENTS.
+    ///
+    /// ```ignore
+    /// let events: Vec<AutomationEvent> = serde_json::from_str(&std::fs::read_to_string("replay.json")?)?;
+    /// let mut list = rl.load_automation_event_list_from_events(&events)?;
+    /// ```
+    pub fn load_automation_event_list_from_events(
+        &self,
+        events: &[AutomationEvent],
+    ) -> Result<AutomationEventList, AllocationError> {
+        let mut list = AutomationEventList(unsafe { ffi::LoadAutomationEventList(null()) });
+        if events.len() > list.capacity() as usize {
+            return Err(AllocationError::ExceedsCapacity);
+        }
+        let dst = unsafe { std::slice::from_raw_parts_mut(list.0.events, events.len()) };
+        for (d, e) in dst.iter_mut().zip(events) {
+            *d = e.0;
+        }
+        list.0.count = events.len() as u32;
+        Ok(list)
+    }
     /// Set automation event list to record to
     #[inline]
     pub fn set_automation_event_list(&self, l: &mut AutomationEventList) {
@@ -185,3 +451,64 @@ impl RaylibHandle {
         unsafe { ffi::StopAutomationEventRecording() };
     }
 }
+
+#[cfg(test)]
+mod automation_test {
+    use super::{AutomationEvent, AutomationEventKind};
+
+    #[test]
+    fn test_kind_roundtrip() {
+        let kinds = [
+            AutomationEventKind::KeyDown { key: 87 },
+            AutomationEventKind::MouseMove { x: 10, y: -4 },
+            AutomationEventKind::GamepadAxis {
+                gamepad: 1,
+                axis: 2,
+                value: -16384,
+            },
+            AutomationEventKind::WindowResize {
+                width: 800,
+                height: 450,
+            },
+            AutomationEventKind::Unknown {
+                event_type: 99,
+                params: [1, 2, 3, 4],
+            },
+        ];
+        for kind in kinds {
+            let event = AutomationEvent::new(42, kind);
+            assert_eq!(event.frame(), 42);
+            assert_eq!(event.kind(), kind);
+        }
+        let (event_type, params) = AutomationEventKind::TouchMove { id: 0, x: 5, y: 6 }.to_raw();
+        assert_eq!(event_type, 16);
+        assert_eq!(params, [0, 5, 6, 0]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let events = vec![
+            AutomationEvent::new(3, AutomationEventKind::KeyDown { key: 87 }),
+            AutomationEvent::new(
+                5,
+                AutomationEventKind::GamepadAxis {
+                    gamepad: 0,
+                    axis: 1,
+                    value: -200,
+                },
+            ),
+            AutomationEvent::new(
+                8,
+                AutomationEventKind::Unknown {
+                    event_type: 99,
+                    params: [1, 2, 3, 4],
+                },
+            ),
+        ];
+        let json = serde_json::to_string(&events).unwrap();
+        assert!(json.starts_with(r#"[{"frame":3,"kind":{"KeyDown":{"key":87}}}"#));
+        let back: Vec<AutomationEvent> = serde_json::from_str(&json).unwrap();
+        assert!(back == events);
+    }
+}
@@ -5,15 +5,21 @@ pub use raylib_sys::TraceLogLevel;
 use std::{
     borrow::Cow,
     convert::TryInto,
-    ffi::{CStr, CString, c_char, c_int, c_void},
+    ffi::{CStr, c_char, c_int, c_void},
     mem::{size_of, transmute},
     pin::Pin,
     ptr::null_mut,
     slice::from_raw_parts_mut,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
-mod stream_processor_with_user_data_wrapper;
-use super::audio::Music;
+pub(crate) mod stream_processor_with_user_data_wrapper;
+use super::audio::{Music, RaylibAudio};
+use super::audio_effects::MIXER_CHANNELS;
+use crate::error::AudioCallbackError;
+pub use stream_processor_with_user_data_wrapper::AUDIO_CALLBACK_SLOTS;
 use stream_processor_with_user_data_wrapper::*;
 
 type TraceLogCallback = unsafe extern "C" fn(*mut i8, *const i8, ...);
@@ -21,53 +27,126 @@ unsafe extern "C" {
     fn SetTraceLogCallback(cb: Option<TraceLogCallback>);
 }
 
-type RustTraceLogCallback = fn(TraceLogLevel, &str);
-type RustSaveFileDataCallback = fn(&str, &[u8]) -> bool;
-type RustLoadFileDataCallback = fn(&str) -> Vec<u8>;
-type RustSaveFileTextCallback = fn(&str, &str) -> bool;
-type RustLoadFileTextCallback = fn(&str) -> String;
+pub type RustTraceLogCallback = dyn Fn(TraceLogLevel, &str) + Send + Sync;
+pub type RustSaveFileDataCallback = dyn Fn(&str, &[u8]) -> bool + Send + Sync;
+pub type RustLoadFileDataCallback = dyn Fn(&str) -> Vec<u8> + Send + Sync;
+pub type RustSaveFileTextCallback = dyn Fn(&str, &str) -> bool + Send + Sync;
+pub type RustLoadFileTextCallback = dyn Fn(&str) -> String + Send + Sync;
 type RustAudioStreamCallback = fn(&[u8]);
 
-static TRACE_LOG_CALLBACK: AtomicUsize = AtomicUsize::new(0);
-static SAVE_FILE_DATA_CALLBACK: AtomicUsize = AtomicUsize::new(0);
-static LOAD_FILE_DATA_CALLBACK: AtomicUsize = AtomicUsize::new(0);
-static SAVE_FILE_TEXT_CALLBACK: AtomicUsize = AtomicUsize::new(0);
-static LOAD_FILE_TEXT_CALLBACK: AtomicUsize = AtomicUsize::new(0);
-static AUDIO_STREAM_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+/// The Rust callback of one raylib callback, and how to point raylib at its trampoline
+struct CallbackSlot<F: ?Sized + 'static> {
+    callback: RwLock<Option<Arc<F>>>,
+    /// Registers the trampoline with raylib, or restores raylib's default behavior
+    register: fn(bool),
+}
+
+impl<F: ?Sized + 'static> CallbackSlot<F> {
+    const fn new(register: fn(bool)) -> Self {
+        Self {
+            callback: RwLock::new(None),
+            register,
+        }
+    }
+
+    /// Clones the callback out of the lock, so callbacks may replace callbacks or log while running
+    fn get(&self) -> Option<Arc<F>> {
+        self.callback.read().unwrap().clone()
+    }
+
+    fn swap(&self, callback: Option<Arc<F>>) -> Option<Arc<F>> {
+        if callback.is_some() {
+            let previous = std::mem::replace(&mut *self.callback.write().unwrap(), callback);
+            (self.register)(true);
+            previous
+        } else {
+            (self.register)(false);
+            self.callback.write().unwrap().take()
+        }
+    }
 
-fn trace_log_callback() -> Option<RustTraceLogCallback> {
-    debug_assert!(size_of::<RustTraceLogCallback>() == size_of::<usize>());
-    unsafe { transmute(TRACE_LOG_CALLBACK.load(Ordering::Relaxed)) }
+    fn replace(&'static self, callback: Option<Arc<F>>) -> CallbackGuard<F> {
+        CallbackGuard {
+            slot: self,
+            previous: Some(self.swap(callback)),
+        }
+    }
 }
 
-fn save_file_data_callback() -> Option<RustSaveFileDataCallback> {
-    debug_assert!(size_of::<RustSaveFileDataCallback>() == size_of::<usize>());
-    unsafe { transmute(SAVE_FILE_DATA_CALLBACK.load(Ordering::Relaxed)) }
+/// Returned when setting or unsetting a callback. Dropping it puts back the callback that was set before, or
+/// raylib's default behavior if there was none; [`CallbackGuard::keep`] keeps the new callback for good.
+///
+/// Guards of the same callback should be dropped in reverse order of creation, like any stack of overrides.
+#[must_use = "the previous callback is restored as soon as the guard is dropped"]
+pub struct CallbackGuard<F: ?Sized + 'static> {
+    slot: &'static CallbackSlot<F>,
+    previous: Option<Option<Arc<F>>>,
 }
 
-fn load_file_data_callback() -> Option<RustLoadFileDataCallback> {
-    debug_assert!(size_of::<RustLoadFileDataCallback>() == size_of::<usize>());
-    unsafe { transmute(LOAD_FILE_DATA_CALLBACK.load(Ordering::Relaxed)) }
+impl<F: ?Sized + 'static> CallbackGuard<F> {
+    /// Keeps the current callback installed after the guard is gone
+    pub fn keep(mut self) {
+        self.previous = None;
+    }
 }
 
-fn save_file_text_callback() -> Option<RustSaveFileTextCallback> {
-    debug_assert!(size_of::<RustSaveFileTextCallback>() == size_of::<usize>());
-    unsafe { transmute(SAVE_FILE_TEXT_CALLBACK.load(Ordering::Relaxed)) }
+impl<F: ?Sized + 'static> std::fmt::Debug for CallbackGuard<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackGuard")
+            .field(
+                "restores",
+                &match self.previous {
+                    Some(Some(_)) => "previous callback",
+                    Some(None) => "default",
+                    None => "nothing",
+                },
+            )
+            .finish()
+    }
 }
 
-fn load_file_text_callback() -> Option<RustLoadFileTextCallback> {
-    debug_assert!(size_of::<RustLoadFileTextCallback>() == size_of::<usize>());
-    unsafe { transmute(LOAD_FILE_TEXT_CALLBACK.load(Ordering::Relaxed)) }
+impl<F: ?Sized + 'static> Drop for CallbackGuard<F> {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            self.slot.swap(previous);
+        }
+    }
 }
 
+static TRACE_LOG_CALLBACK: CallbackSlot<RustTraceLogCallback> = CallbackSlot::new(|set| unsafe {
+    if set {
+        ffi::setLogCallbackWrapper()
+    } else {
+        SetTraceLogCallback(None)
+    }
+});
+static SAVE_FILE_DATA_CALLBACK: CallbackSlot<RustSaveFileDataCallback> =
+    CallbackSlot::new(|set| unsafe {
+        ffi::SetSaveFileDataCallback(set.then_some(custom_save_file_data_callback))
+    });
+static LOAD_FILE_DATA_CALLBACK: CallbackSlot<RustLoadFileDataCallback> =
+    CallbackSlot::new(|set| unsafe {
+        ffi::SetLoadFileDataCallback(set.then_some(custom_load_file_data_callback))
+    });
+static SAVE_FILE_TEXT_CALLBACK: CallbackSlot<RustSaveFileTextCallback> =
+    CallbackSlot::new(|set| unsafe {
+        ffi::SetSaveFileTextCallback(set.then_some(custom_save_file_text_callback))
+    });
+static LOAD_FILE_TEXT_CALLBACK: CallbackSlot<RustLoadFileTextCallback> =
+    CallbackSlot::new(|set| unsafe {
+        ffi::SetLoadFileTextCallback(set.then_some(custom_load_file_text_callback))
+    });
+static AUDIO_STREAM_CALLBACK: AtomicUsize = AtomicUsize::new(0);
+
 fn audio_stream_callback() -> Option<RustAudioStreamCallback> {
     debug_assert!(size_of::<RustAudioStreamCallback>() == size_of::<usize>());
     unsafe { transmute(AUDIO_STREAM_CALLBACK.load(Ordering::Relaxed)) }
 }
 
+/// Called by the C wrapper in `raylib-sys` with the message already formatted
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn custom_trace_log_callback(level: TraceLogLevel, text: *const c_char) {
-    if let Some(trace_log) = trace_log_callback() {
+    if let Some(trace_log) = TRACE_LOG_CALLBACK.get() {
         let text = if text.is_null() {
             Cow::Borrowed("(MESSAGE WAS NULL)")
         } else {
@@ -83,47 +162,69 @@ extern "C" fn custom_save_file_data_callback(
     buffer: *mut c_void,
     size: c_int,
 ) -> bool {
-    let save_file_data = save_file_data_callback().expect("no callback");
+    let Some(save_file_data) = SAVE_FILE_DATA_CALLBACK.get() else {
+        return false;
+    };
     let path = unsafe { CStr::from_ptr(path) };
     let buffer = unsafe { from_raw_parts_mut(buffer as *mut u8, size as usize) };
 
     save_file_data(path.to_str().expect("path is non utf-8"), buffer)
 }
 
+/// Copies `bytes` into memory raylib frees itself, with `nul` extra zero bytes at the end
+fn to_raylib_buffer(bytes: &[u8], nul: usize) -> *mut u8 {
+    unsafe {
+        let buffer = ffi::MemAlloc(
+            (bytes.len() + nul)
+                .try_into()
+                .expect("non representable buffer size"),
+        ) as *mut u8;
+        if !buffer.is_null() {
+            // MemAlloc zeroes the memory, which also terminates text
+            buffer.copy_from_nonoverlapping(bytes.as_ptr(), bytes.len());
+        }
+        buffer
+    }
+}
+
 extern "C" fn custom_load_file_data_callback(path: *const c_char, size: *mut c_int) -> *mut u8 {
-    let load_file_data = load_file_data_callback().expect("no callback");
+    let Some(load_file_data) = LOAD_FILE_DATA_CALLBACK.get() else {
+        return null_mut();
+    };
 
     if let Some(size) = unsafe { size.as_mut() } {
         let path = unsafe { CStr::from_ptr(path) };
         let buffer = load_file_data(path.to_str().expect("path is non utf-8"));
         *size = buffer.len().try_into().expect("out of range buffer size");
+        if buffer.is_empty() {
+            return null_mut();
+        }
 
         // Copy everything to the raylib world
-        unsafe {
-            let buffer_ffi =
-                ffi::MemAlloc((*size).try_into().expect("non representable buffer size"))
-                    as *mut u8;
-            buffer_ffi.copy_from_nonoverlapping(buffer.as_ptr(), buffer.len());
-
-            buffer_ffi
-        }
+        to_raylib_buffer(&buffer, 0)
     } else {
         null_mut()
     }
 }
 
 extern "C" fn custom_save_file_text_callback(a: *const c_char, b: *mut c_char) -> bool {
-    let save_file_text = save_file_text_callback().unwrap();
+    let Some(save_file_text) = SAVE_FILE_TEXT_CALLBACK.get() else {
+        return false;
+    };
     let a = unsafe { CStr::from_ptr(a) };
     let b = unsafe { CStr::from_ptr(b) };
-    return save_file_text(a.to_str().unwrap(), b.to_str().unwrap());
+    save_file_text(a.to_str().unwrap(), b.to_str().unwrap())
 }
 extern "C" fn custom_load_file_text_callback(a: *const c_char) -> *mut c_char {
-    let load_file_text = load_file_text_callback().unwrap();
+    let Some(load_file_text) = LOAD_FILE_TEXT_CALLBACK.get() else {
+        return null_mut();
+    };
     let a = unsafe { CStr::from_ptr(a) };
     let st = load_file_text(a.to_str().unwrap());
-    let oh = Box::leak(Box::new(CString::new(st).unwrap()));
-    oh.as_ptr() as *mut c_char
+    if st.is_empty() {
+        return null_mut();
+    }
+    to_raylib_buffer(st.as_bytes(), 1) as *mut c_char
 }
 
 extern "C" fn custom_audio_stream_callback(a: *mut c_void, b: u32) {
@@ -142,67 +243,71 @@ impl<'a> std::fmt::Display for SetLogError<'a> {
 
 impl<'a> std::error::Error for SetLogError<'a> {}
 
-macro_rules! safe_callback_set_func {
-    ($cb:expr, $target_cb:expr, $rawsetter:expr, $ogfunc:expr, $ty:literal) => {
-        if $target_cb.load(Ordering::Acquire) == 0 {
-            $target_cb.store($cb as usize, Ordering::Release);
-            unsafe { $rawsetter(Some($ogfunc)) };
-            Ok(())
-        } else {
-            Err(SetLogError($ty))
-        }
-    };
+/// Set custom trace log, replacing the current one. Messages arrive fully formatted, whatever their length.
+/// ```ignore
+/// let _guard = set_trace_log_callback(move |level, text| {
+///     tracing::event!(tracing::Level::INFO, ?level, frame = frame.load(Ordering::Relaxed), "{text}")
+/// });
+/// ```
+pub fn set_trace_log_callback<F>(cb: F) -> CallbackGuard<RustTraceLogCallback>
+where
+    F: Fn(TraceLogLevel, &str) + Send + Sync + 'static,
+{
+    TRACE_LOG_CALLBACK.replace(Some(Arc::new(cb)))
 }
-
-/// Set custom trace log
-pub fn set_trace_log_callback<'a>(cb: fn(TraceLogLevel, &str)) -> Result<(), SetLogError<'a>> {
-    TRACE_LOG_CALLBACK.store(cb as usize, Ordering::Relaxed);
-    unsafe { ffi::setLogCallbackWrapper() };
-    Ok(())
+/// Go back to raylib's own trace log output
+pub fn unset_trace_log_callback() -> CallbackGuard<RustTraceLogCallback> {
+    TRACE_LOG_CALLBACK.replace(None)
 }
-/// Set custom file binary data saver
-pub fn set_save_file_data_callback<'a>(cb: fn(&str, &[u8]) -> bool) -> Result<(), SetLogError<'a>> {
-    safe_callback_set_func!(
-        cb,
-        SAVE_FILE_DATA_CALLBACK,
-        ffi::SetSaveFileDataCallback,
-        custom_save_file_data_callback,
-        "save file data"
-    )
+/// Set custom file binary data saver, replacing the current one
+pub fn set_save_file_data_callback<F>(cb: F) -> CallbackGuard<RustSaveFileDataCallback>
+where
+    F: Fn(&str, &[u8]) -> bool + Send + Sync + 'static,
+{
+    SAVE_FILE_DATA_CALLBACK.replace(Some(Arc::new(cb)))
 }
-/// Set custom file binary data loader
+/// Go back to raylib's own file binary data saver
+pub fn unset_save_file_data_callback() -> CallbackGuard<RustSaveFileDataCallback> {
+    SAVE_FILE_DATA_CALLBACK.replace(None)
+}
+/// Set custom file binary data loader, replacing the current one
 ///
-/// Whatever you return from your callback will be intentionally leaked as Raylib is relied on to free it.
-pub fn set_load_file_data_callback<'b>(cb: fn(&str) -> Vec<u8>) -> Result<(), SetLogError<'b>> {
-    safe_callback_set_func!(
-        cb,
-        LOAD_FILE_DATA_CALLBACK,
-        ffi::SetLoadFileDataCallback,
-        custom_load_file_data_callback,
-        "load file data"
-    )
-}
-/// Set custom file text data saver
-pub fn set_save_file_text_callback<'a>(cb: fn(&str, &str) -> bool) -> Result<(), SetLogError<'a>> {
-    safe_callback_set_func!(
-        cb,
-        SAVE_FILE_TEXT_CALLBACK,
-        ffi::SetSaveFileTextCallback,
-        custom_save_file_text_callback,
-        "load file data"
-    )
-}
-/// Set custom file text data loader
+/// Whatever you return from your callback is copied into memory that raylib frees. Return an empty `Vec` for
+/// a file that doesn't exist and raylib fails the load like it does for a missing file.
+pub fn set_load_file_data_callback<F>(cb: F) -> CallbackGuard<RustLoadFileDataCallback>
+where
+    F: Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+{
+    LOAD_FILE_DATA_CALLBACK.replace(Some(Arc::new(cb)))
+}
+/// Go back to raylib's own file binary data loader
+pub fn unset_load_file_data_callback() -> CallbackGuard<RustLoadFileDataCallback> {
+    LOAD_FILE_DATA_CALLBACK.replace(None)
+}
+/// Set custom file text data saver, replacing the current one
+pub fn set_save_file_text_callback<F>(cb: F) -> CallbackGuard<RustSaveFileTextCallback>
+where
+    F: Fn(&str, &str) -> bool + Send + Sync + 'static,
+{
+    SAVE_FILE_TEXT_CALLBACK.replace(Some(Arc::new(cb)))
+}
+/// Go back to raylib's own file text data saver
+pub fn unset_save_file_text_callback() -> CallbackGuard<RustSaveFileTextCallback> {
+    SAVE_FILE_TEXT_CALLBACK.replace(None)
+}
+/// Set custom file text data loader, replacing the current one
 ///
-/// Whatever you return from your callback will be intentionally leaked as Raylib is relied on to free it.
-pub fn set_load_file_text_callback<'a>(cb: fn(&str) -> String) -> Result<(), SetLogError<'a>> {
-    safe_callback_set_func!(
-        cb,
-        LOAD_FILE_TEXT_CALLBACK,
-        ffi::SetLoadFileTextCallback,
-        custom_load_file_text_callback,
-        "load file text"
-    )
+/// Whatever you return from your callback is copied into memory that raylib frees. Return an empty `String`
+/// for a file that doesn't exist and raylib fails the load like it does for a missing file.
+pub fn set_load_file_text_callback<F>(cb: F) -> CallbackGuard<RustLoadFileTextCallback>
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    LOAD_FILE_TEXT_CALLBACK.replace(Some(Arc::new(cb)))
+}
+/// Go back to raylib's own file text data loader
+pub fn unset_load_file_text_callback() -> CallbackGuard<RustLoadFileTextCallback> {
+    LOAD_FILE_TEXT_CALLBACK.replace(None)
 }
 
 // region: -- AudioStreamProcessorCallback --
@@ -217,6 +322,7 @@ where
 {
     rust_callback: &'a mut F,
     nb_channels: u32,
+    stream: ffi::AudioStream,
     callback_index: Option<usize>,
 }
 
@@ -224,10 +330,11 @@ impl<'a, F> AudioStreamProcessorCallback<'a, F>
 where
     F: FnMut(&mut [f32], u32) -> (),
 {
-    fn new(closure: &'a mut F, nb_channels_from_music: u32) -> Self {
+    fn new(closure: &'a mut F, nb_channels_from_music: u32, stream: ffi::AudioStream) -> Self {
         Self {
             rust_callback: closure,
             nb_channels: nb_channels_from_music,
+            stream,
             callback_index: None,
         }
     }
@@ -271,33 +378,141 @@ where
 {
     fn drop(&mut self) {
         if let Some(index) = self.callback_index {
-            detach_audio_stream_processor_with_user_data(index);
+            detach_audio_stream_processor_with_user_data(self.stream, index);
         }
     }
 }
 
 // endregion: -- AudioStreamProcessorCallback --
 
+/// Runs `processor` over `music`'s samples on the audio thread until the returned handle is dropped. Processors
+/// share [`AUDIO_CALLBACK_SLOTS`] slots with procedural streams and effect chains, and this returns
+/// [`AudioCallbackError::NoFreeSlot`] when they're all taken.
 pub fn attach_audio_stream_processor_to_music<'a, F>(
     music: &'a Music<'a>,
     processor: &'a mut F,
-) -> Pin<Box<AudioStreamProcessorCallback<'a, F>>>
+) -> Result<Pin<Box<AudioStreamProcessorCallback<'a, F>>>, AudioCallbackError>
 where
     F: FnMut(&mut [f32], u32) -> () + Send + 'static, // static because the function is executed in another thread
 {
-    let mut stream_processor_callback =
-        Box::new(AudioStreamProcessorCallback::<'a, F>::new(processor, 2));
+    let mut stream_processor_callback = Box::new(AudioStreamProcessorCallback::<'a, F>::new(
+        processor,
+        MIXER_CHANNELS,
+        music.stream,
+    ));
     stream_processor_callback.callback_index = Some(attach_audio_stream_processor_with_user_data(
         music.stream,
         AudioCallbackWithUserData::new(
             stream_processor_callback.get_as_user_data(), // pass the address of the stream_processor_callback as void*
             stream_processor_callback.get_c_callback(),
         ),
-    ));
-    assert!(stream_processor_callback.callback_index.is_some());
-    Box::into_pin(stream_processor_callback)
+    )?);
+    Ok(Box::into_pin(stream_processor_callback))
+}
+
+// region: -- ProceduralStream --
+
+/// The closure of a [`ProceduralStream`], handed to the audio thread as user data
+struct ProceduralSource {
+    generate: Box<dyn FnMut(&mut [f32]) + Send>,
+    channels: u32,
+}
+
+impl ProceduralSource {
+    extern "C" fn c_callback(user_data: *mut c_void, data_ptr: *mut c_void, frames: u32) {
+        // Safety: the slot is cleared before the source is dropped, and the slot lock keeps calls from overlapping
+        let source = unsafe { &mut *(user_data as *mut ProceduralSource) };
+        let samples = unsafe {
+            std::slice::from_raw_parts_mut(
+                data_ptr as *mut f32,
+                frames as usize * source.channels as usize,
+            )
+        };
+        samples.fill(0.0);
+        (source.generate)(samples);
+    }
+}
+
+/// An [`AudioStream`] that pulls its samples from a Rust closure on the audio thread, so nothing has to call
+/// `update` from the game loop. Derefs to the stream for playback control. Dropping it detaches the closure
+/// before the stream is unloaded.
+pub struct ProceduralStream<'aud> {
+    stream: AudioStream<'aud>,
+    /// Owned, turned back into a box once the audio thread can't reach it anymore
+    source: *mut ProceduralSource,
+    callback_index: usize,
+}
+
+impl std::fmt::Debug for ProceduralStream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProceduralStream")
+            .field("sample_rate", &self.stream.sample_rate())
+            .field("channels", &self.stream.channels())
+            .finish()
+    }
+}
+
+impl<'aud> std::ops::Deref for ProceduralStream<'aud> {
+    type Target = AudioStream<'aud>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
 }
 
+impl Drop for ProceduralStream<'_> {
+    fn drop(&mut self) {
+        unset_audio_stream_callback_with_user_data(self.stream.0, self.callback_index);
+        drop(unsafe { Box::from_raw(self.source) });
+    }
+}
+
+impl RaylibAudio {
+    /// Creates a 32-bit float stream whose samples come from `generate`. It's called on the audio thread with a
+    /// zeroed buffer of interleaved frames (`channels` samples each) to fill, so it must never block.
+    ///
+    /// Procedural streams share the [`AUDIO_CALLBACK_SLOTS`] slots of
+    /// [`attach_audio_stream_processor_to_music`]. When they're all taken this returns
+    /// [`AudioCallbackError::NoFreeSlot`].
+    /// ```ignore
+    /// let mut phase = 0.0f32;
+    /// let tone = audio.new_procedural_stream(44100, 1, move |samples| {
+    ///     for sample in samples {
+    ///         *sample = (phase * std::f32::consts::TAU).sin() * 0.2;
+    ///         phase = (phase + 440.0 / 44100.0).fract();
+    ///     }
+    /// })?;
+    /// tone.play();
+    /// ```
+    pub fn new_procedural_stream<'aud, F>(
+        &'aud self,
+        sample_rate: u32,
+        channels: u32,
+        generate: F,
+    ) -> Result<ProceduralStream<'aud>, AudioCallbackError>
+    where
+        F: FnMut(&mut [f32]) + Send + 'static,
+    {
+        let stream = self.new_audio_stream(sample_rate, 32, channels);
+        let source = Box::into_raw(Box::new(ProceduralSource {
+            generate: Box::new(generate),
+            channels,
+        }));
+        let callback_index = set_audio_stream_callback_with_user_data(
+            stream.0,
+            AudioCallbackWithUserData::new(source as *mut c_void, ProceduralSource::c_callback),
+        )
+        .inspect_err(|_| drop(unsafe { Box::from_raw(source) }))?;
+        Ok(ProceduralStream {
+            stream,
+            source,
+            callback_index,
+        })
+    }
+}
+
+// endregion: -- ProceduralStream --
+
 /// Audio thread callback to request new data
 pub fn set_audio_stream_callback(stream: AudioStream, cb: fn(&[u8])) -> Result<(), SetLogError> {
     if AUDIO_STREAM_CALLBACK.load(Ordering::Acquire) == 0 {
@@ -316,7 +531,8 @@ impl RaylibHandle {
         &mut self,
         cb: fn(TraceLogLevel, &str),
     ) -> Result<(), SetLogError> {
-        set_trace_log_callback(cb)
+        set_trace_log_callback(cb).keep();
+        Ok(())
     }
     /// Set custom file binary data saver
     #[deprecated = "Decoupled from RaylibHandle. Use [set_save_file_data_callback](core::callbacks::set_save_file_data_callback) instead."]
@@ -324,17 +540,19 @@ impl RaylibHandle {
         &mut self,
         cb: fn(&str, &[u8]) -> bool,
     ) -> Result<(), SetLogError> {
-        set_save_file_data_callback(cb)
+        set_save_file_data_callback(cb).keep();
+        Ok(())
     }
     /// Set custom file binary data loader
     ///
-    /// Whatever you return from your callback will be intentionally leaked as Raylib is relied on to free it.
+    /// Whatever you return from your callback is copied into memory that raylib frees.
     #[deprecated = "Decoupled from RaylibHandle. Use [set_load_file_data_callback](core::callbacks::set_load_file_data_callback) instead."]
     pub fn set_load_file_data_callback<'b>(
         &mut self,
         cb: fn(&str) -> Vec<u8>,
     ) -> Result<(), SetLogError> {
-        set_load_file_data_callback(cb)
+        set_load_file_data_callback(cb).keep();
+        Ok(())
     }
     /// Set custom file text data saver
     #[deprecated = "Decoupled from RaylibHandle. Use [set_save_file_text_callback](core::callbacks::set_save_file_text_callback) instead."]
@@ -342,17 +560,19 @@ impl RaylibHandle {
         &mut self,
         cb: fn(&str, &str) -> bool,
     ) -> Result<(), SetLogError> {
-        set_save_file_text_callback(cb)
+        set_save_file_text_callback(cb).keep();
+        Ok(())
     }
     /// Set custom file text data loader
     ///
-    /// Whatever you return from your callback will be intentionally leaked as Raylib is relied on to free it.
+    /// Whatever you return from your callback is copied into memory that raylib frees.
     #[deprecated = "Decoupled from RaylibHandle. Use [set_load_file_text_callback](core::callbacks::set_load_file_text_callback) instead."]
     pub fn set_load_file_text_callback(
         &mut self,
         cb: fn(&str) -> String,
     ) -> Result<(), SetLogError> {
-        set_load_file_text_callback(cb)
+        set_load_file_text_callback(cb).keep();
+        Ok(())
     }
 
     /// Audio thread callback to request new data
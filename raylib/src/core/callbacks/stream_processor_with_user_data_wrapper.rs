@@ -1,5 +1,8 @@
+use crate::error::AudioCallbackError;
 use paste::paste;
-use raylib_sys::{AttachAudioStreamProcessor, AudioStream};
+use raylib_sys::{
+    AttachAudioStreamProcessor, AudioStream, DetachAudioStreamProcessor, SetAudioStreamCallback,
+};
 use seq_macro::seq;
 use std::sync::{LazyLock, Mutex};
 
@@ -67,19 +70,20 @@ macro_rules! generate_functions {
         )*
 
           /// Function to set our context
-          /// and returns the slot used to store the context.
+          /// and returns the slot used to store the context,
+          /// or `None` if every slot is taken.
           #[allow(unpredictable_function_pointer_comparisons)]
-          fn set_context(audio_callback: AudioCallbackWithUserData) -> usize {
+          fn set_context(audio_callback: AudioCallbackWithUserData) -> Option<usize> {
               $(
                   {
                       let mut guard = [< CLOSURE_ $n >].lock().unwrap();
                       if (*guard).callback == None {
                         *guard = audio_callback;
-                        return $n;
+                        return Some($n);
                       }
                   }
               )*
-              panic!("index out of bounds");
+              None
           }
 
           /// Function to clear our context given the slot of the context.
@@ -131,6 +135,9 @@ macro_rules! generate_functions {
   }
 }
 
+/// Number of callback slots, keep in sync with the `seq!` range below
+pub const AUDIO_CALLBACK_SLOTS: usize = 30;
+
 // here, you can control how many callbacks are supported
 seq!(I in 1..30 {
     generate_functions!( 0#(,I)* );
@@ -142,14 +149,41 @@ seq!(I in 1..30 {
 pub fn attach_audio_stream_processor_with_user_data(
     stream: AudioStream,
     callback: AudioCallbackWithUserData,
-) -> usize {
-    let idx = set_context(callback);
+) -> Result<usize, AudioCallbackError> {
+    let idx = set_context(callback).ok_or(AudioCallbackError::NoFreeSlot(AUDIO_CALLBACK_SLOTS))?;
     unsafe {
         AttachAudioStreamProcessor(stream, Some(get_callback(idx)));
     }
-    idx
+    Ok(idx)
+}
+
+/// raylib detaches processors under the audio lock, so the slot is freed
+/// only once the processor can't be running anymore.
+pub fn detach_audio_stream_processor_with_user_data(stream: AudioStream, index: usize) {
+    unsafe {
+        DetachAudioStreamProcessor(stream, Some(get_callback(index)));
+    }
+    clear_context(index);
+}
+
+/// Makes the slot callback the stream's data source, so the stream pulls
+/// its samples from `callback` instead of `UpdateAudioStream`.
+pub fn set_audio_stream_callback_with_user_data(
+    stream: AudioStream,
+    callback: AudioCallbackWithUserData,
+) -> Result<usize, AudioCallbackError> {
+    let idx = set_context(callback).ok_or(AudioCallbackError::NoFreeSlot(AUDIO_CALLBACK_SLOTS))?;
+    unsafe {
+        SetAudioStreamCallback(stream, Some(get_callback(idx)));
+    }
+    Ok(idx)
 }
 
-pub fn detach_audio_stream_processor_with_user_data(index: usize) {
+/// raylib swaps stream callbacks under the audio lock, so once the
+/// callback is unset the slot can't be running anymore and is freed.
+pub fn unset_audio_stream_callback_with_user_data(stream: AudioStream, index: usize) {
+    unsafe {
+        SetAudioStreamCallback(stream, None);
+    }
     clear_context(index);
 }
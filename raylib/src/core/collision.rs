@@ -1,5 +1,5 @@
 //! Common collision handling code
-use crate::core::math::Vector2;
+use crate::core::math::{Vector2, Vector3};
 
 use crate::ffi;
 use crate::math::{Matrix, RayCollision};
@@ -139,6 +139,29 @@ pub fn get_ray_collision_model(
     unsafe { ffi::GetRayCollisionMesh(ray.into(), model.0, transform.into()).into() }
 }
 
+/// Gets collision info between ray and a mesh, using `vertices` in place of the mesh's own vertex positions,
+/// e.g. the [`skinned_vertices`](crate::core::models::RaylibModel::skinned_vertices) of an animated model.
+///
+/// Panics if `vertices` doesn't have one position per mesh vertex.
+#[inline]
+#[must_use]
+pub fn get_ray_collision_mesh_vertices(
+    ray: impl Into<ffi::Ray>,
+    mesh: impl AsRef<ffi::Mesh>,
+    vertices: &[Vector3],
+    transform: &Matrix,
+) -> RayCollision {
+    let mut mesh = *mesh.as_ref();
+    assert_eq!(
+        vertices.len(),
+        mesh.vertexCount as usize,
+        "expected one position per mesh vertex"
+    );
+    // GetRayCollisionMesh only reads the positions, the copy never outlives `vertices`
+    mesh.vertices = vertices.as_ptr() as *mut f32;
+    unsafe { ffi::GetRayCollisionMesh(ray.into(), mesh, transform.into()).into() }
+}
+
 /// Gets collision info between ray and triangle.
 #[inline]
 #[must_use]
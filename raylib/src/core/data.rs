@@ -130,6 +130,27 @@ impl<T: Copy> DataBuf<T> {
         } else { Err(AllocationError::SubMinSize) }
     }
 
+    /// Allocate memory managed by Raylib and copy `data` into it, for handing Rust-built data to Raylib structs.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`DataBuf::alloc`].
+    pub(crate) fn from_slice(data: &[T]) -> Result<Self, AllocationError> {
+        let count = i32::try_from(data.len()).map_err(|_| AllocationError::ExceedsUIntMax)?;
+        let mut buf = Self::alloc(count)?;
+        buf.copy_from_slice(data);
+        Ok(buf)
+    }
+
+    /// Copy `data` into memory managed by Raylib and give up ownership of it, for filling Raylib structs that
+    /// Raylib frees itself (e.g. the attribute arrays of a [`ffi::Mesh`]). Empty slices give a null pointer.
+    pub(crate) fn leak_slice<U>(data: &[T]) -> Result<*mut U, AllocationError> {
+        if data.is_empty() {
+            return Ok(std::ptr::null_mut());
+        }
+        Ok(Self::from_slice(data)?.leak().0.as_ptr().cast())
+    }
+
     /// Reallocate memory already managed by Raylib
     ///
     /// # Errors
@@ -2,6 +2,10 @@
 
 use raylib_sys::Rectangle;
 
+use crate::core::sdf_font::{SdfFont, SdfTextStyle};
+#[cfg(feature = "shaping")]
+use crate::core::shaping::ShapedText;
+use crate::core::text_layout::TextLayout;
 use crate::core::texture::Texture2D;
 use crate::core::vr::VrStereoConfig;
 use crate::core::{RaylibHandle, RaylibThread};
@@ -1313,6 +1317,118 @@ pub trait RaylibDraw {
         }
     }
 
+    /// Draws text with an [`SdfFont`] and its shader, with the outline, softness and drop shadow of `style`.
+    /// Sharp at any size and camera zoom.
+    ///
+    /// This begins and ends its own shader mode, and raylib's shader modes don't nest: called inside
+    /// [`RaylibShaderModeExt::begin_shader_mode`] it ends the caller's shader, and everything drawn after it in
+    /// that mode uses the default shader. Draw SDF text outside of other shader modes.
+    fn draw_text_sdf(
+        &mut self,
+        font: &SdfFont,
+        text: &str,
+        position: impl Into<MintVec2>,
+        font_size: f32,
+        spacing: f32,
+        tint: impl Into<ffi::Color>,
+        style: &SdfTextStyle,
+    ) {
+        let c_text = CString::new(text).unwrap();
+        let position: Vector2 = position.into().into();
+        let tint = tint.into();
+        unsafe {
+            ffi::BeginShaderMode(*font.shader().as_ref());
+            if let Some(shadow) = style.shadow {
+                font.set_style(
+                    style.outline_width,
+                    shadow.color,
+                    style.softness + shadow.softness,
+                );
+                ffi::DrawTextEx(
+                    *font.font().as_ref(),
+                    c_text.as_ptr(),
+                    (position + shadow.offset).into(),
+                    font_size,
+                    spacing,
+                    shadow.color,
+                );
+                // uniforms aren't part of the batch, so draw the shadow before changing them
+                ffi::rlDrawRenderBatchActive();
+            }
+            let outline_color = if style.outline_width > 0.0 {
+                style.outline_color
+            } else {
+                tint
+            };
+            font.set_style(style.outline_width, outline_color, style.softness);
+            ffi::DrawTextEx(
+                *font.font().as_ref(),
+                c_text.as_ptr(),
+                position.into(),
+                font_size,
+                spacing,
+                tint,
+            );
+            ffi::EndShaderMode();
+        }
+    }
+
+    /// Draw text shaped by [`ShapingFont::shape`](crate::core::shaping::ShapingFont::shape) with the font that
+    /// shaped it, its top left corner at `position`
+    #[cfg(feature = "shaping")]
+    fn draw_shaped_text(
+        &mut self,
+        font: impl AsRef<ffi::Font>,
+        text: &ShapedText,
+        position: impl Into<MintVec2>,
+        font_size: f32,
+        tint: impl Into<ffi::Color>,
+    ) {
+        let font = *font.as_ref();
+        let position: Vector2 = position.into().into();
+        let scale = font_size / font.baseSize as f32;
+        let tint = tint.into();
+        for glyph in text.glyphs() {
+            unsafe {
+                ffi::DrawTextCodepoint(
+                    font,
+                    glyph.codepoint as i32,
+                    (position + glyph.position * scale).into(),
+                    font_size,
+                    tint,
+                );
+            }
+        }
+    }
+
+    /// Draw a [`TextLayout`] with its origin at `position`
+    fn draw_text_layout(&mut self, layout: &TextLayout, position: impl Into<MintVec2>) {
+        let position: Vector2 = position.into().into();
+        for glyph in layout.glyphs().iter().filter(|g| g.visible) {
+            let span = &layout.spans()[glyph.span];
+            let mut dest = glyph.dest;
+            dest.x += position.x;
+            dest.y += position.y;
+            unsafe {
+                ffi::DrawTexturePro(
+                    span.font.texture,
+                    glyph.source,
+                    dest,
+                    Vector2::ZERO.into(),
+                    0.0,
+                    span.color,
+                );
+            }
+        }
+        for &(mut rec, color) in layout.underlines() {
+            rec.x += position.x;
+            rec.y += position.y;
+            unsafe {
+                ffi::DrawRectangleRec(rec, color);
+            }
+        }
+    }
+
     /// Enable waiting for events when the handle is dropped, no automatic event polling
     #[inline]
     fn enable_event_waiting(&self) {
@@ -0,0 +1,496 @@
+//! Fonts that rasterize glyphs the first time they are used.
+//!
+//! [`DynamicFont`] keeps the TTF/OTF file data around instead of baking a fixed codepoint list into the atlas, so
+//! any character a player can type is available without loading every CJK glyph up front. Rasterize the
+//! characters of a string with [`DynamicFont::cache_text`] before drawing or measuring it; the font is a
+//! [`RaylibFont`] and works everywhere a [`Font`] does.
+//! ```ignore
+//! let mut font = rl.load_dynamic_font(&thread, "resources/noto_sans_jp.ttf", 32)?;
+//! let name = "プレイヤー1";
+//! let size = font.cache_text(&thread, name).measure_text(name, 32.0, 1.0);
+//! d.draw_text_ex(font.cache_text(&thread, name), name, Vector2::new(10.0, 10.0), 32.0, 1.0, Color::WHITE);
+//! ```
+//!
+//! [`Font`]: crate::core::text::Font
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use crate::consts::{PixelFormat, TraceLogLevel};
+use crate::core::logging::trace_log;
+use crate::core::text::RaylibFont;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::error::LoadFontError;
+use crate::ffi;
+use crate::ffi::Rectangle;
+
+/// Same as raylib's `FONT_TTF_DEFAULT_CHARS_PADDING`
+pub(crate) const GLYPH_PADDING: i32 = 4;
+pub(crate) const MAX_ATLAS_SIZE: i32 = 4096;
+
+/// A single atlas page, packed in shelves, that doubles in size when it runs out of space
+#[derive(Debug)]
+pub(crate) struct GlyphAtlas {
+    width: i32,
+    height: i32,
+    max_size: i32,
+    /// `PIXELFORMAT_UNCOMPRESSED_GRAY_ALPHA` pixels: white, with the glyph coverage as alpha
+    pixels: Vec<u8>,
+    shelves: Vec<Shelf>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Shelf {
+    y: i32,
+    height: i32,
+    /// Left edge of the free space
+    x: i32,
+}
+
+impl GlyphAtlas {
+    pub(crate) fn new(size: i32, max_size: i32) -> Self {
+        Self {
+            width: size,
+            height: size,
+            max_size,
+            pixels: [255, 0].repeat((size * size) as usize),
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Top left corner of a free `width` by `height` area, growing the page if needed. `None` if it doesn't
+    /// fit at the maximum size.
+    pub(crate) fn allocate(&mut self, width: i32, height: i32) -> Option<(i32, i32)> {
+        loop {
+            if let Some(position) = self.try_allocate(width, height) {
+                return Some(position);
+            }
+            if !self.grow() {
+                return None;
+            }
+        }
+    }
+
+    fn try_allocate(&mut self, width: i32, height: i32) -> Option<(i32, i32)> {
+        let page_width = self.width;
+        // the lowest shelf with room wastes the least space
+        if let Some(shelf) = self
+            .shelves
+            .iter_mut()
+            .filter(|s| height <= s.height && s.x + width <= page_width)
+            .min_by_key(|s| s.height)
+        {
+            let position = (shelf.x, shelf.y);
+            shelf.x += width;
+            return Some(position);
+        }
+        let y = self.shelves.last().map_or(0, |s| s.y + s.height);
+        if width > self.width || y + height > self.height {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width,
+        });
+        Some((0, y))
+    }
+
+    /// Doubles the shorter side, keeping existing glyphs where they are
+    fn grow(&mut self) -> bool {
+        let taller = self.height < self.width || self.width >= self.max_size;
+        if taller && self.height < self.max_size {
+            self.pixels
+                .extend([255, 0].repeat((self.width * self.height) as usize));
+            self.height *= 2;
+        } else if self.width < self.max_size {
+            let row = (self.width * 2) as usize;
+            let mut pixels = [255, 0].repeat((self.width * 2 * self.height) as usize);
+            for (old, new) in self
+                .pixels
+                .chunks_exact(row)
+                .zip(pixels.chunks_exact_mut(row * 2))
+            {
+                new[..row].copy_from_slice(old);
+            }
+            self.width *= 2;
+            self.pixels = pixels;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Copies 8-bit coverage values into the page
+    pub(crate) fn blit(&mut self, x: i32, y: i32, width: i32, coverage: &[u8]) {
+        if width == 0 {
+            return;
+        }
+        for (row, values) in coverage.chunks_exact(width as usize).enumerate() {
+            let start = (((y + row as i32) * self.width + x) * 2) as usize;
+            for (pixel, &value) in self.pixels[start..start + values.len() * 2]
+                .chunks_exact_mut(2)
+                .zip(values)
+            {
+                pixel[1] = value;
+            }
+        }
+    }
+
+    /// Brings `texture` up to date with the page: recreates it if the page grew, otherwise updates the `dirty`
+    /// rectangles
+    pub(crate) fn upload(&mut self, texture: &mut ffi::Texture2D, dirty: &[Rectangle]) {
+        if texture.id != 0 && texture.width == self.width && texture.height == self.height {
+            for rec in dirty {
+                let pixels = self.region(
+                    rec.x as i32,
+                    rec.y as i32,
+                    rec.width as i32,
+                    rec.height as i32,
+                );
+                unsafe { ffi::UpdateTextureRec(*texture, *rec, pixels.as_ptr() as *const _) };
+            }
+            return;
+        }
+        let image = ffi::Image {
+            data: self.pixels.as_mut_ptr() as *mut _,
+            width: self.width,
+            height: self.height,
+            mipmaps: 1,
+            format: PixelFormat::PIXELFORMAT_UNCOMPRESSED_GRAY_ALPHA as i32,
+        };
+        unsafe {
+            if texture.id != 0 {
+                ffi::UnloadTexture(*texture);
+            }
+            *texture = ffi::LoadTextureFromImage(image);
+        }
+    }
+
+    /// Pixels of a rectangle of the page, row by row
+    fn region(&self, x: i32, y: i32, width: i32, height: i32) -> Vec<u8> {
+        (y..y + height)
+            .flat_map(|row| {
+                let start = ((row * self.width + x) * 2) as usize;
+                &self.pixels[start..start + (width * 2) as usize]
+            })
+            .copied()
+            .collect()
+    }
+}
+
+/// Font data, glyphs and atlas shared by the fonts that rasterize glyphs on demand. Owns the atlas texture;
+/// glyphs and recs belong to the Vecs.
+pub(crate) struct AtlasFont {
+    pub(crate) font: ffi::Font,
+    pub(crate) file_data: Vec<u8>,
+    pub(crate) glyphs: Vec<ffi::GlyphInfo>,
+    pub(crate) recs: Vec<Rectangle>,
+    pub(crate) atlas: GlyphAtlas,
+}
+
+impl AtlasFont {
+    /// Reads a TTF/OTF file through raylib, so custom file loaders apply
+    pub(crate) fn read_file(filename: &str) -> Result<Vec<u8>, LoadFontError> {
+        let c_filename = CString::new(filename).unwrap();
+        let mut size = 0;
+        let data = unsafe { ffi::LoadFileData(c_filename.as_ptr(), &mut size) };
+        if data.is_null() {
+            return Err(LoadFontError::LoadFromFileFailed {
+                path: filename.into(),
+            });
+        }
+        let file_data = unsafe { std::slice::from_raw_parts(data, size as usize).to_vec() };
+        unsafe { ffi::UnloadFileData(data) };
+        Ok(file_data)
+    }
+
+    /// Font without glyphs whose atlas starts out with room for `glyph_count` glyphs at `font_size`, like
+    /// raylib's own atlas sizing
+    pub(crate) fn new(
+        file_data: Vec<u8>,
+        font_size: i32,
+        glyph_count: u64,
+    ) -> Result<Self, LoadFontError> {
+        let invalid = || LoadFontError::InvalidSize { font_size };
+        let glyph_size = u64::try_from(font_size)
+            .ok()
+            .filter(|&size| size > 0)
+            .and_then(|size| size.checked_add(2 * GLYPH_PADDING as u64))
+            .ok_or_else(invalid)?;
+        let area = glyph_size
+            .checked_mul(glyph_size)
+            .and_then(|area| area.checked_mul(glyph_count))
+            .ok_or_else(invalid)?;
+        // a glyph that doesn't fit in the largest page could never be drawn
+        if glyph_size > MAX_ATLAS_SIZE as u64 {
+            return Err(invalid());
+        }
+        let size = ((area as f64).sqrt() as u64)
+            .next_power_of_two()
+            .min(MAX_ATLAS_SIZE as u64);
+
+        let mut font: ffi::Font = unsafe { std::mem::zeroed() };
+        font.baseSize = font_size;
+        font.glyphPadding = GLYPH_PADDING;
+        Ok(Self {
+            font,
+            file_data,
+            glyphs: Vec::new(),
+            recs: Vec::new(),
+            atlas: GlyphAtlas::new(size as i32, MAX_ATLAS_SIZE),
+        })
+    }
+
+    /// Points the font at the glyphs and brings the atlas texture up to date
+    pub(crate) fn upload(&mut self, dirty: &[Rectangle]) {
+        self.font.glyphs = self.glyphs.as_mut_ptr();
+        self.font.recs = self.recs.as_mut_ptr();
+        self.font.glyphCount = self.glyphs.len() as i32;
+        self.atlas.upload(&mut self.font.texture, dirty);
+    }
+}
+
+impl Drop for AtlasFont {
+    fn drop(&mut self) {
+        if self.font.texture.id != 0 {
+            unsafe { ffi::UnloadTexture(self.font.texture) }
+        }
+    }
+}
+
+/// Font that rasterizes missing glyphs from its TTF/OTF data on demand and packs them into a growing atlas.
+///
+/// Printable ASCII is rasterized on load. Everything else has to go through [`DynamicFont::cache_text`] or
+/// [`DynamicFont::cache_codepoints`] before drawing, otherwise raylib draws the `'?'` fallback glyph. The atlas
+/// texture is recreated when the page grows, so don't hold on to a copy of [`RaylibFont::texture`].
+pub struct DynamicFont {
+    inner: AtlasFont,
+    cached: HashMap<char, usize>,
+}
+
+impl std::fmt::Debug for DynamicFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicFont")
+            .field("font", &self.inner.font)
+            .field("glyph_count", &self.inner.glyphs.len())
+            .field("atlas_size", &self.atlas_size())
+            .finish_non_exhaustive()
+    }
+}
+
+impl_atlas_font!(DynamicFont, inner);
+
+impl RaylibFont for DynamicFont {}
+
+impl std::ops::Deref for DynamicFont {
+    type Target = ffi::Font;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner.font
+    }
+}
+
+impl RaylibHandle {
+    /// Loads a TTF/OTF font that rasterizes glyphs at `font_size` pixels the first time they are needed.
+    /// The file is read through raylib, so custom file loaders apply.
+    pub fn load_dynamic_font(
+        &mut self,
+        thread: &RaylibThread,
+        filename: &str,
+        font_size: i32,
+    ) -> Result<DynamicFont, LoadFontError> {
+        let font = AtlasFont::new(AtlasFont::read_file(filename)?, font_size, 95)?;
+        DynamicFont::new(thread, font).ok_or_else(|| LoadFontError::LoadFromFileFailed {
+            path: filename.into(),
+        })
+    }
+
+    /// Like [`RaylibHandle::load_dynamic_font`] with the contents of a TTF/OTF file
+    pub fn load_dynamic_font_from_memory(
+        &mut self,
+        thread: &RaylibThread,
+        file_data: impl Into<Vec<u8>>,
+        font_size: i32,
+    ) -> Result<DynamicFont, LoadFontError> {
+        let font = AtlasFont::new(file_data.into(), font_size, 95)?;
+        DynamicFont::new(thread, font).ok_or(LoadFontError::LoadFromMemoryFailed)
+    }
+}
+
+impl DynamicFont {
+    fn new(thread: &RaylibThread, inner: AtlasFont) -> Option<Self> {
+        let mut font = DynamicFont {
+            inner,
+            cached: HashMap::new(),
+        };
+        font.cache_codepoints(thread, ' '..='~');
+        (font.inner.font.texture.id != 0 && font.cached.contains_key(&'?')).then_some(font)
+    }
+
+    /// TTF/OTF data the glyphs are rasterized from
+    #[inline]
+    pub fn file_data(&self) -> &[u8] {
+        &self.inner.file_data
+    }
+
+    /// Width and height of the atlas page
+    #[inline]
+    pub fn atlas_size(&self) -> (i32, i32) {
+        (self.inner.atlas.width, self.inner.atlas.height)
+    }
+
+    /// Whether `codepoint` has been rasterized
+    #[inline]
+    pub fn is_cached(&self, codepoint: char) -> bool {
+        self.cached.contains_key(&codepoint)
+    }
+
+    /// Rasterizes the characters of `text` that aren't in the atlas yet. Returns the font so it can go straight
+    /// into `draw_text_ex`, `measure_text` or a `TextSpan`.
+    pub fn cache_text(&mut self, thread: &RaylibThread, text: &str) -> &Self {
+        self.cache_codepoints(thread, text.chars())
+    }
+
+    /// Rasterizes the given codepoints that aren't in the atlas yet
+    pub fn cache_codepoints(
+        &mut self,
+        _: &RaylibThread,
+        codepoints: impl IntoIterator<Item = char>,
+    ) -> &Self {
+        let mut missing: Vec<i32> = codepoints
+            .into_iter()
+            .filter(|c| !c.is_control() && !self.cached.contains_key(c))
+            .map(|c| c as i32)
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        if !missing.is_empty() {
+            self.rasterize(&mut missing);
+        }
+        self
+    }
+
+    fn rasterize(&mut self, codepoints: &mut [i32]) {
+        let count = codepoints.len() as i32;
+        let chars = unsafe {
+            ffi::LoadFontData(
+                self.inner.file_data.as_ptr(),
+                self.inner.file_data.len() as i32,
+                self.inner.font.baseSize,
+                codepoints.as_mut_ptr(),
+                count,
+                ffi::FontType::FONT_DEFAULT as i32,
+            )
+        };
+        if chars.is_null() {
+            return;
+        }
+
+        let mut dirty = Vec::new();
+        for (glyph, &codepoint) in unsafe { std::slice::from_raw_parts(chars, count as usize) }
+            .iter()
+            .zip(codepoints.iter())
+        {
+            let image = glyph.image;
+            let Some((x, y)) = self.inner.atlas.allocate(
+                image.width + 2 * GLYPH_PADDING,
+                image.height + 2 * GLYPH_PADDING,
+            ) else {
+                trace_log(
+                    TraceLogLevel::LOG_WARNING,
+                    &format!("FONT: Dynamic font atlas is full, glyph {codepoint} was not added"),
+                );
+                continue;
+            };
+            let (x, y) = (x + GLYPH_PADDING, y + GLYPH_PADDING);
+            if !image.data.is_null()
+                && image.format == PixelFormat::PIXELFORMAT_UNCOMPRESSED_GRAYSCALE as i32
+            {
+                let coverage = unsafe {
+                    std::slice::from_raw_parts(
+                        image.data as *const u8,
+                        (image.width * image.height) as usize,
+                    )
+                };
+                self.inner.atlas.blit(x, y, image.width, coverage);
+            }
+            if let Some(c) = char::from_u32(codepoint as u32) {
+                self.cached.insert(c, self.inner.glyphs.len());
+            }
+            self.inner.glyphs.push(ffi::GlyphInfo {
+                value: codepoint,
+                image: unsafe { std::mem::zeroed() },
+                ..*glyph
+            });
+            self.inner.recs.push(Rectangle::new(
+                x as f32,
+                y as f32,
+                image.width as f32,
+                image.height as f32,
+            ));
+            dirty.push(Rectangle::new(
+                (x - GLYPH_PADDING) as f32,
+                (y - GLYPH_PADDING) as f32,
+                (image.width + 2 * GLYPH_PADDING) as f32,
+                (image.height + 2 * GLYPH_PADDING) as f32,
+            ));
+        }
+        unsafe { ffi::UnloadFontData(chars, count) };
+
+        self.inner.upload(&dirty);
+    }
+}
+
+#[cfg(test)]
+mod dynamic_font_test {
+    use super::*;
+
+    #[test]
+    fn test_atlas_packing() {
+        let mut atlas = GlyphAtlas::new(16, 64);
+        assert_eq!(atlas.allocate(10, 8), Some((0, 0)));
+        assert_eq!(atlas.allocate(6, 8), Some((10, 0)));
+        assert_eq!(atlas.allocate(4, 4), Some((0, 8)));
+        // a short glyph goes to the lowest shelf with room
+        assert_eq!(atlas.allocate(4, 3), Some((4, 8)));
+        atlas.blit(1, 1, 2, &[10, 20, 30, 40]);
+
+        // doesn't fit below the last shelf, so the page gets wider and the first shelf has room again
+        assert_eq!(atlas.allocate(8, 8), Some((16, 0)));
+        assert_eq!((atlas.width, atlas.height), (32, 16));
+        assert_eq!(atlas.pixels.len(), 32 * 16 * 2);
+        assert_eq!(
+            atlas.region(1, 1, 2, 2),
+            [255, 10, 255, 20, 255, 30, 255, 40]
+        );
+        assert_eq!(atlas.region(16, 0, 1, 1), [255, 0]);
+    }
+
+    #[test]
+    fn test_atlas_growth_limit() {
+        let mut atlas = GlyphAtlas::new(8, 16);
+        assert_eq!(atlas.allocate(17, 1), None);
+        assert_eq!(atlas.allocate(16, 16), Some((0, 0)));
+        assert_eq!((atlas.width, atlas.height), (16, 16));
+        assert_eq!(atlas.allocate(1, 1), None);
+    }
+
+    #[test]
+    fn test_atlas_font_size() {
+        let size = |font_size| {
+            AtlasFont::new(Vec::new(), font_size, 95).map(|f| (f.atlas.width, f.atlas.height))
+        };
+        assert_eq!(size(32).unwrap(), (512, 512));
+        assert_eq!(
+            size(MAX_ATLAS_SIZE - 2 * GLYPH_PADDING).unwrap(),
+            (4096, 4096)
+        );
+        assert!(matches!(
+            size(0),
+            Err(LoadFontError::InvalidSize { font_size: 0 })
+        ));
+        assert!(size(-1).is_err());
+        assert!(size(MAX_ATLAS_SIZE).is_err());
+        assert!(size(i32::MAX).is_err());
+    }
+}
@@ -10,6 +10,12 @@ pub enum AudioInitError {
     InitFailed,
 }
 
+#[derive(Error, Debug)]
+pub enum AudioCallbackError {
+    #[error("all {0} audio callback slots are in use")]
+    NoFreeSlot(usize),
+}
+
 #[derive(Error, Debug)]
 pub enum ExportWaveError {
     #[error("wave data must be 16 bit per sample for QOA format export (actual: {0})")]
@@ -60,6 +66,51 @@ pub enum LoadModelError {
     LoadFromMeshFailed,
 }
 
+#[derive(Error, Debug)]
+pub enum MeshBuilderError {
+    #[error("mesh has no vertices")]
+    Empty,
+    #[error("mesh has {vertices} vertices but {count} {attribute}")]
+    AttributeCount {
+        attribute: &'static str,
+        count: usize,
+        vertices: usize,
+    },
+    #[error("triangle list has {count} entries, not a multiple of 3")]
+    NotTriangles { count: usize },
+    #[error("index {index} is out of range for {vertices} vertices")]
+    IndexOutOfRange { index: u32, vertices: usize },
+    #[error("mesh has {vertices} vertices, indexed meshes can have at most 65536")]
+    TooManyVertices { vertices: usize },
+    #[error("allocation error")]
+    Allocation(#[from] AllocationError),
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateMeshError {
+    #[error("mesh was already uploaded with static buffers")]
+    AlreadyUploaded,
+    #[error("mesh has no {attribute} buffer")]
+    MissingAttribute { attribute: &'static str },
+    #[error("{len} {attribute} at offset {offset} exceed the mesh's {count}")]
+    OutOfBounds {
+        attribute: &'static str,
+        offset: usize,
+        len: usize,
+        count: usize,
+    },
+    #[error("index {index} is out of range for {vertices} vertices")]
+    IndexOutOfRange { index: u16, vertices: usize },
+}
+
+#[derive(Error, Debug)]
+pub enum MeshProcessingError {
+    #[error("index {index} is out of range for {vertices} vertices")]
+    IndexOutOfRange { index: u32, vertices: usize },
+    #[error("vertex {vertex} has no original vertex mapped to it")]
+    Unmapped { vertex: usize },
+}
+
 #[derive(Error, Debug)]
 pub enum LoadModelAnimError {
     #[error("no model animations loaded\npath: {path:?}")]
@@ -81,13 +132,20 @@ pub enum LoadMaterialError {
 }
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum LoadFontError {
-    #[error("error loading font; check if the file exists and if it's the right type\npath: {path:?}")]
+    #[error(
+        "error loading font; check if the file exists and if it's the right type\npath: {path:?}"
+    )]
     LoadFromFileFailed { path: String },
     #[error("error loading font from image")]
     LoadFromImageFailed,
     #[error("error loading font from memory; check if the file's type is correct")]
     LoadFromMemoryFailed,
+    #[error("error compiling the SDF font shader")]
+    SdfShaderFailed,
+    #[error("font size {font_size} is out of range; glyphs have to fit in a 4096x4096 atlas")]
+    InvalidSize { font_size: i32 },
 }
 
 #[derive(Error, Debug)]
@@ -136,10 +194,81 @@ pub enum LoadTextureError {
     InvalidData,
 }
 
+#[derive(Error, Debug)]
+pub enum ShaderUniformError {
+    #[error("shader has no active uniforms named {names:?}")]
+    MissingUniforms { names: Vec<String> },
+}
+
+#[derive(Error, Debug)]
+pub enum LoadShaderError {
+    #[error("could not read shader file\npath: {path:?}")]
+    ReadFailed { path: String },
+    #[error("failed to load or compile shader\nvertex: {vs:?}\nfragment: {fs:?}")]
+    LoadFailed {
+        vs: Option<String>,
+        fs: Option<String>,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum GltfError {
+    #[error("not a valid GLB file: {0}")]
+    Format(String),
+    #[error("not a valid glTF document: {0}")]
+    Json(String),
+    #[error("could not resolve uri {uri:?}")]
+    UnresolvedUri { uri: String },
+    #[error("{context}: {message}")]
+    Invalid { context: String, message: String },
+    #[error("could not read or write glTF file\npath: {path:?}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("allocation error")]
+    Allocation(#[from] AllocationError),
+}
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("could not read archive\npath: {path:?}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("not a valid tar archive: {0}")]
+    Tar(String),
+    #[error("not a valid zip archive: {0}")]
+    Zip(String),
+    #[error("unsupported archive entry {path:?}: {reason}")]
+    Unsupported { path: String, reason: String },
+}
+
+#[derive(Error, Debug)]
+pub enum VfsError {
+    #[error("file not found\npath: {path:?}")]
+    NotFound { path: String },
+    #[error("path is absolute or leaves the mounted directory\npath: {path:?}")]
+    InvalidPath { path: String },
+    #[error("could not read file\npath: {path:?}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not decompress archive entry\npath: {path:?}")]
+    Corrupt { path: String },
+}
+
 #[derive(Error, Debug)]
 pub enum RaylibError {
     #[error("audio initialization error")]
     AudioInit(#[from] AudioInitError),
+    #[error("audio callback error")]
+    AudioCallback(#[from] AudioCallbackError),
     #[error("wave export error")]
     ExportWave(#[from] ExportWaveError),
     #[error("sound loading error")]
@@ -150,6 +279,10 @@ pub enum RaylibError {
     Compression(#[from] CompressionError),
     #[error("model loading error")]
     LoadModel(#[from] LoadModelError),
+    #[error("mesh building error")]
+    MeshBuilder(#[from] MeshBuilderError),
+    #[error("mesh update error")]
+    UpdateMesh(#[from] UpdateMeshError),
     #[error("model animation loading error")]
     LoadModelAnim(#[from] LoadModelAnimError),
     #[error("material update error")]
@@ -164,4 +297,12 @@ pub enum RaylibError {
     UpdateTexture(#[from] UpdateTextureError),
     #[error("texture loading error")]
     LoadTexture(#[from] LoadTextureError),
+    #[error("shader loading error")]
+    LoadShader(#[from] LoadShaderError),
+    #[error("shader uniform error")]
+    ShaderUniform(#[from] ShaderUniformError),
+    #[error("glTF error")]
+    Gltf(#[from] GltfError),
+    #[error("archive error")]
+    Archive(#[from] ArchiveError),
 }
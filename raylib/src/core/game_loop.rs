@@ -0,0 +1,217 @@
+//! Fixed timestep game loop.
+//!
+//! Simulation runs at a fixed rate regardless of the frame rate: real time is collected in an accumulator and
+//! spent in whole steps, and the leftover fraction is handed to rendering as an interpolation alpha.
+//! ```ignore
+//! run_fixed(&mut rl, &thread, 60, |rl, dt| world.update(rl, dt), |d, alpha| world.draw(d, alpha));
+//! ```
+use crate::core::drawing::RaylibDrawHandle;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::ffi;
+
+/// Default for [FixedTimestep::set_max_frame_time]
+pub const DEFAULT_MAX_FRAME_TIME: f64 = 0.25;
+
+/// Source of time, in seconds, for [FixedTimestep].
+pub trait Clock {
+    fn now(&mut self) -> f64;
+}
+
+/// Any `FnMut() -> f64` returning seconds can be used as a clock.
+impl<F: FnMut() -> f64> Clock for F {
+    fn now(&mut self) -> f64 {
+        self()
+    }
+}
+
+/// Time since the window was initialized, as returned by [RaylibHandle::get_time].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RaylibClock;
+
+impl Clock for RaylibClock {
+    fn now(&mut self) -> f64 {
+        unsafe { ffi::GetTime() }
+    }
+}
+
+/// A clock that only moves when told to, for tests and offline simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ManualClock {
+    pub time: f64,
+}
+
+impl ManualClock {
+    pub fn advance(&mut self, seconds: f64) {
+        self.time += seconds;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&mut self) -> f64 {
+        self.time
+    }
+}
+
+/// What to do this frame, as returned by [FixedTimestep::advance].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedFrame {
+    /// Number of fixed updates to run
+    pub steps: u32,
+    /// How far between the last and the next update the frame is, in `[0, 1)`
+    pub alpha: f32,
+}
+
+/// Accumulator for a fixed timestep.
+#[derive(Debug, Clone)]
+pub struct FixedTimestep<C: Clock = RaylibClock> {
+    clock: C,
+    step: f64,
+    max_frame_time: f64,
+    accumulator: f64,
+    last: Option<f64>,
+}
+
+impl FixedTimestep<RaylibClock> {
+    /// Steps `hz` times per second of [RaylibClock] time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hz` is 0.
+    pub fn new(hz: u32) -> Self {
+        Self::with_clock(hz, RaylibClock)
+    }
+}
+
+impl<C: Clock> FixedTimestep<C> {
+    /// Steps `hz` times per second of `clock` time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hz` is 0.
+    pub fn with_clock(hz: u32, clock: C) -> Self {
+        assert!(hz > 0, "fixed timestep rate must be above 0");
+        Self {
+            clock,
+            step: 1.0 / hz as f64,
+            max_frame_time: DEFAULT_MAX_FRAME_TIME,
+            accumulator: 0.0,
+            last: None,
+        }
+    }
+
+    /// Caps how much time a single frame may add to the accumulator. After a stall (a breakpoint, a slow load,
+    /// a dragged window) the simulation then falls behind instead of running ever more updates to catch up.
+    pub fn set_max_frame_time(&mut self, seconds: f64) {
+        self.max_frame_time = seconds;
+    }
+
+    /// Length of one update in seconds
+    #[inline]
+    pub fn step(&self) -> f32 {
+        self.step as f32
+    }
+
+    #[inline]
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    #[inline]
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Samples the clock and returns how many updates to run this frame. The first call runs none.
+    pub fn advance(&mut self) -> FixedFrame {
+        let now = self.clock.now();
+        let frame_time = self.last.map_or(0.0, |last| (now - last).max(0.0));
+        self.last = Some(now);
+        self.accumulator += frame_time.min(self.max_frame_time);
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        FixedFrame {
+            steps,
+            alpha: (self.accumulator / self.step) as f32,
+        }
+    }
+}
+
+/// Runs `update` `hz` times per second and `render` once per frame until the window should close.
+///
+/// `update` receives the fixed step in seconds, `render` the interpolation alpha between the previous and the
+/// current simulation state.
+pub fn run_fixed(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    hz: u32,
+    update: impl FnMut(&mut RaylibHandle, f32),
+    render: impl FnMut(&mut RaylibDrawHandle, f32),
+) {
+    run_fixed_with_clock(rl, thread, FixedTimestep::new(hz), update, render)
+}
+
+/// [run_fixed] with a custom timestep, e.g. one with a different clock or max frame time.
+pub fn run_fixed_with_clock<C: Clock>(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    mut timestep: FixedTimestep<C>,
+    mut update: impl FnMut(&mut RaylibHandle, f32),
+    mut render: impl FnMut(&mut RaylibDrawHandle, f32),
+) {
+    let dt = timestep.step();
+    while !rl.window_should_close() {
+        let frame = timestep.advance();
+        for _ in 0..frame.steps {
+            update(rl, dt);
+        }
+        let mut d = rl.begin_drawing(thread);
+        render(&mut d, frame.alpha);
+    }
+}
+
+#[cfg(test)]
+mod game_loop_test {
+    use super::*;
+
+    #[test]
+    fn test_accumulates_steps() {
+        let mut timestep = FixedTimestep::with_clock(4, ManualClock::default());
+        timestep.set_max_frame_time(1.0);
+        assert_eq!(timestep.advance().steps, 0);
+
+        timestep.clock_mut().advance(0.625);
+        let frame = timestep.advance();
+        assert_eq!(frame.steps, 2);
+        assert!((frame.alpha - 0.5).abs() < 1e-4);
+
+        // the leftover half step carries over
+        timestep.clock_mut().advance(0.125);
+        let frame = timestep.advance();
+        assert_eq!(frame.steps, 1);
+        assert!(frame.alpha.abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_clamps_spiral_of_death() {
+        let mut timestep = FixedTimestep::with_clock(8, ManualClock::default());
+        timestep.set_max_frame_time(0.5);
+        timestep.advance();
+        timestep.clock_mut().advance(10.0);
+        assert_eq!(timestep.advance().steps, 4);
+    }
+
+    #[test]
+    fn test_closure_clock() {
+        let mut t = 0.0;
+        let mut timestep = FixedTimestep::with_clock(4, move || {
+            t += 0.25;
+            t
+        });
+        timestep.advance();
+        assert_eq!(timestep.advance().steps, 1);
+    }
+}
@@ -0,0 +1,1804 @@
+//! glTF 2.0 import and export in Rust, without going through raylib's file loaders.
+//!
+//! [`GltfDocument`] parses `.gltf` and `.glb` data from memory. [`RaylibHandle::load_model_from_gltf`] and
+//! [`RaylibHandle::load_model_animations_from_gltf`] turn it into a [`Model`] and [`ModelAnimation`]s, and errors
+//! name the node, mesh, primitive and accessor they come from.
+//! [`RaylibMesh::export_gltf`](crate::core::models::RaylibMesh::export_gltf) writes a mesh back out.
+//!
+//! Like raylib's own loader, node transforms are baked into the vertices of static meshes, only the first skin
+//! drives bones and animations are sampled at [`GLTF_ANIMATION_FPS`].
+//! ```ignore
+//! let doc = GltfDocument::from_file("assets/robot.glb")?;
+//! let mut model = rl.load_model_from_gltf(&thread, &doc)?;
+//! let anims = rl.load_model_animations_from_gltf(&thread, &doc)?;
+//! ```
+use std::collections::BTreeMap;
+use std::ffi::c_char;
+use std::path::Path;
+
+use glam::{Mat4, Quat, Vec3, Vec4};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::consts::MaterialMapIndex;
+use crate::core::data::DataBuf;
+use crate::core::math::Matrix;
+use crate::core::models::{Material, Mesh, Model, ModelAnimation, RaylibMaterial};
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::error::{AllocationError, GltfError};
+use crate::ffi;
+
+/// Rate at which glTF animations are sampled into [`ModelAnimation`] frames
+pub const GLTF_ANIMATION_FPS: f32 = 60.0;
+
+/// Longest animation that gets sampled, in seconds; longer ones are rejected as malformed
+pub const GLTF_MAX_ANIMATION_SECONDS: f32 = 600.0;
+
+/// Most values an accessor without a buffer view may expand to (its elements are all zero)
+const MAX_ZEROED_ACCESSOR_VALUES: usize = 1 << 24;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_JSON: u32 = 0x4E4F_534A;
+const GLB_BIN: u32 = 0x004E_4942;
+
+const BYTE: u32 = 5120;
+const UNSIGNED_BYTE: u32 = 5121;
+const SHORT: u32 = 5122;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+const TRIANGLES: u32 = 4;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Root {
+    scene: Option<usize>,
+    scenes: Vec<SceneDef>,
+    nodes: Vec<NodeDef>,
+    meshes: Vec<MeshDef>,
+    accessors: Vec<AccessorDef>,
+    buffer_views: Vec<BufferViewDef>,
+    buffers: Vec<BufferDef>,
+    materials: Vec<MaterialDef>,
+    textures: Vec<TextureDef>,
+    images: Vec<ImageDef>,
+    skins: Vec<SkinDef>,
+    animations: Vec<AnimationDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SceneDef {
+    nodes: Vec<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct NodeDef {
+    name: Option<String>,
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    skin: Option<usize>,
+    matrix: Option<[f32; 16]>,
+    translation: Option<[f32; 3]>,
+    rotation: Option<[f32; 4]>,
+    scale: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct MeshDef {
+    name: Option<String>,
+    primitives: Vec<PrimitiveDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PrimitiveDef {
+    attributes: BTreeMap<String, usize>,
+    indices: Option<usize>,
+    material: Option<usize>,
+    mode: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AccessorDef {
+    buffer_view: Option<usize>,
+    byte_offset: usize,
+    component_type: u32,
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct BufferViewDef {
+    buffer: usize,
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct BufferDef {
+    byte_length: usize,
+    uri: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct MaterialDef {
+    name: Option<String>,
+    pbr_metallic_roughness: Option<PbrDef>,
+    normal_texture: Option<TextureRef>,
+    occlusion_texture: Option<TextureRef>,
+    emissive_texture: Option<TextureRef>,
+    emissive_factor: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PbrDef {
+    base_color_factor: Option<[f32; 4]>,
+    base_color_texture: Option<TextureRef>,
+    metallic_factor: Option<f32>,
+    roughness_factor: Option<f32>,
+    metallic_roughness_texture: Option<TextureRef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TextureRef {
+    index: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TextureDef {
+    source: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ImageDef {
+    uri: Option<String>,
+    buffer_view: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SkinDef {
+    inverse_bind_matrices: Option<usize>,
+    joints: Vec<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AnimationDef {
+    name: Option<String>,
+    channels: Vec<ChannelDef>,
+    samplers: Vec<SamplerDef>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ChannelDef {
+    sampler: usize,
+    target: TargetDef,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TargetDef {
+    node: Option<usize>,
+    path: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SamplerDef {
+    input: usize,
+    output: usize,
+    interpolation: Option<String>,
+}
+
+fn invalid(context: impl Into<String>, message: impl Into<String>) -> GltfError {
+    GltfError::Invalid {
+        context: context.into(),
+        message: message.into(),
+    }
+}
+
+fn named(kind: &str, index: usize, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{kind} {index} {name:?}"),
+        None => format!("{kind} {index}"),
+    }
+}
+
+/// Vertex data of one mesh, in the layout raylib's [`ffi::Mesh`] uses. Empty attributes are absent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub texcoords: Vec<[f32; 2]>,
+    pub texcoords2: Vec<[f32; 2]>,
+    pub colors: Vec<[u8; 4]>,
+    pub tangents: Vec<[f32; 4]>,
+    /// Triangle list, or empty if every three vertices form a triangle
+    pub indices: Vec<u16>,
+    pub joints: Vec<[u8; 4]>,
+    pub weights: Vec<[f32; 4]>,
+}
+
+impl MeshData {
+    /// Number of triangles
+    pub fn triangle_count(&self) -> usize {
+        if self.indices.is_empty() {
+            self.positions.len() / 3
+        } else {
+            self.indices.len() / 3
+        }
+    }
+
+    /// Copies the CPU side vertex data out of a raylib mesh.
+    pub fn from_mesh(mesh: &ffi::Mesh) -> MeshData {
+        unsafe fn read<T: Copy, U: Copy>(ptr: *const U, len: usize) -> Vec<T> {
+            if ptr.is_null() || len == 0 {
+                return Vec::new();
+            }
+            unsafe { std::slice::from_raw_parts(ptr.cast::<T>(), len).to_vec() }
+        }
+        let vertices = mesh.vertexCount.max(0) as usize;
+        let index_count = mesh.triangleCount.max(0) as usize * 3;
+        unsafe {
+            MeshData {
+                positions: read(mesh.vertices, vertices),
+                normals: read(mesh.normals, vertices),
+                texcoords: read(mesh.texcoords, vertices),
+                texcoords2: read(mesh.texcoords2, vertices),
+                colors: read(mesh.colors, vertices),
+                tangents: read(mesh.tangents, vertices),
+                indices: read(mesh.indices, index_count),
+                joints: read(mesh.boneIds, vertices),
+                weights: read(mesh.boneWeights, vertices),
+            }
+        }
+    }
+}
+
+/// One mesh primitive of the default scene with the data needed to build a raylib mesh for it
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    /// Index of the glTF node the primitive was found under
+    pub node: usize,
+    pub mesh: MeshData,
+    /// Index into the document's materials, if the primitive has one
+    pub material: Option<usize>,
+    /// Whether the vertices are skinned, rather than baked into world space
+    pub skinned: bool,
+}
+
+/// The joints of the first skin, in bone order
+#[derive(Debug, Clone)]
+pub struct GltfSkeleton {
+    /// Node index of every joint
+    pub joints: Vec<usize>,
+    pub names: Vec<String>,
+    /// Bone index of every joint's closest ancestor joint, -1 for roots
+    pub parents: Vec<i32>,
+    /// Model space transform of every joint in the bind pose
+    pub bind_pose: Vec<Mat4>,
+}
+
+/// An animation sampled at [`GLTF_ANIMATION_FPS`]
+#[derive(Debug, Clone)]
+pub struct GltfAnimation {
+    pub name: String,
+    /// Model space transform of every joint, per frame
+    pub frames: Vec<Vec<Mat4>>,
+}
+
+/// A parsed glTF file with its buffers and images resolved
+#[derive(Debug)]
+pub struct GltfDocument {
+    root: Root,
+    buffers: Vec<Vec<u8>>,
+    images: Vec<Option<Vec<u8>>>,
+}
+
+impl GltfDocument {
+    /// Parses a `.gltf` or `.glb` file. Buffers and images must be embedded, use [`GltfDocument::from_slice_with`]
+    /// or [`GltfDocument::from_file`] for files that reference others.
+    pub fn from_slice(data: &[u8]) -> Result<GltfDocument, GltfError> {
+        Self::from_slice_with(data, |_| None)
+    }
+
+    /// Parses a `.gltf` or `.glb` file, calling `resolve` for every external buffer or image uri.
+    pub fn from_slice_with(
+        data: &[u8],
+        mut resolve: impl FnMut(&str) -> Option<Vec<u8>>,
+    ) -> Result<GltfDocument, GltfError> {
+        let (json, mut bin) = if data.starts_with(GLB_MAGIC) {
+            split_glb(data)?
+        } else {
+            (data, None)
+        };
+        let root: Root =
+            serde_json::from_slice(json).map_err(|e| GltfError::Json(e.to_string()))?;
+
+        let mut load = |uri: &str, context: &str| -> Result<Vec<u8>, GltfError> {
+            match uri.strip_prefix("data:") {
+                Some(data) => {
+                    let (_, payload) = data
+                        .split_once(";base64,")
+                        .ok_or_else(|| invalid(context, "data uri is not base64 encoded"))?;
+                    base64_decode(payload)
+                        .ok_or_else(|| invalid(context, "data uri is not valid base64"))
+                }
+                None => resolve(uri).ok_or_else(|| GltfError::UnresolvedUri {
+                    uri: uri.to_owned(),
+                }),
+            }
+        };
+
+        let mut buffers = Vec::with_capacity(root.buffers.len());
+        for (i, buffer) in root.buffers.iter().enumerate() {
+            let context = format!("buffer {i}");
+            let data = match &buffer.uri {
+                Some(uri) => load(uri, &context)?,
+                None if i == 0 => bin
+                    .take()
+                    .ok_or_else(|| {
+                        invalid(&context, "has no uri and there is no GLB binary chunk")
+                    })?
+                    .to_vec(),
+                None => return Err(invalid(&context, "has no uri")),
+            };
+            if data.len() < buffer.byte_length {
+                return Err(invalid(
+                    &context,
+                    format!("has {} bytes, expected {}", data.len(), buffer.byte_length),
+                ));
+            }
+            buffers.push(data);
+        }
+
+        let mut images = Vec::with_capacity(root.images.len());
+        for (i, image) in root.images.iter().enumerate() {
+            images.push(match &image.uri {
+                Some(uri) => Some(load(uri, &format!("image {i}"))?),
+                None => None,
+            });
+        }
+
+        Ok(GltfDocument {
+            root,
+            buffers,
+            images,
+        })
+    }
+
+    /// Reads and parses a `.gltf` or `.glb` file, resolving external uris relative to it.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<GltfDocument, GltfError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| GltfError::Io {
+            path: path.to_string_lossy().into_owned(),
+            source,
+        })?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Self::from_slice_with(&data, |uri| {
+            std::fs::read(dir.join(percent_decode(uri))).ok()
+        })
+    }
+
+    /// Number of materials, not counting raylib's default material
+    pub fn material_count(&self) -> usize {
+        self.root.materials.len()
+    }
+
+    /// Number of animations
+    pub fn animation_count(&self) -> usize {
+        self.root.animations.len()
+    }
+
+    /// Reads an accessor as floats, normalizing integer components if the accessor says so.
+    /// Returns the values and the number of components per element.
+    fn read_floats(&self, index: usize, context: &str) -> Result<(Vec<f32>, usize), GltfError> {
+        let context = format!("{context}, accessor {index}");
+        let accessor = self
+            .root
+            .accessors
+            .get(index)
+            .ok_or_else(|| invalid(&context, "does not exist"))?;
+        let components = match accessor.kind.as_str() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" => 4,
+            "MAT4" => 16,
+            kind => return Err(invalid(&context, format!("has unsupported type {kind:?}"))),
+        };
+        let size = match accessor.component_type {
+            BYTE | UNSIGNED_BYTE => 1,
+            SHORT | UNSIGNED_SHORT => 2,
+            UNSIGNED_INT | FLOAT => 4,
+            other => {
+                return Err(invalid(
+                    &context,
+                    format!("has unknown component type {other}"),
+                ));
+            }
+        };
+        if accessor.sparse.is_some() {
+            return Err(invalid(&context, "sparse accessors are not supported"));
+        }
+
+        let Some(view_index) = accessor.buffer_view else {
+            let len = accessor
+                .count
+                .checked_mul(components)
+                .filter(|len| *len <= MAX_ZEROED_ACCESSOR_VALUES)
+                .ok_or_else(|| {
+                    invalid(
+                        &context,
+                        format!(
+                            "count {} is too large without a buffer view",
+                            accessor.count
+                        ),
+                    )
+                })?;
+            return Ok((vec![0.0; len], components));
+        };
+        let view =
+            self.root.buffer_views.get(view_index).ok_or_else(|| {
+                invalid(&context, format!("buffer view {view_index} does not exist"))
+            })?;
+        let buffer = self
+            .buffers
+            .get(view.buffer)
+            .ok_or_else(|| invalid(&context, format!("buffer {} does not exist", view.buffer)))?;
+        let view_end = view
+            .byte_offset
+            .checked_add(view.byte_length)
+            .filter(|end| *end <= buffer.len())
+            .ok_or_else(|| {
+                invalid(
+                    &context,
+                    format!("buffer view {view_index} is out of bounds"),
+                )
+            })?;
+
+        let element = size * components;
+        let stride = view.byte_stride.unwrap_or(element);
+        let start = view.byte_offset.saturating_add(accessor.byte_offset);
+        let end = match accessor.count {
+            0 => Some(start),
+            count => stride
+                .checked_mul(count - 1)
+                .and_then(|last| last.checked_add(start))
+                .and_then(|last| last.checked_add(element)),
+        };
+        if stride < element || end.is_none_or(|end| end > view_end) {
+            return Err(invalid(
+                &context,
+                format!("reads past the end of buffer view {view_index}"),
+            ));
+        }
+
+        let normalized = accessor.normalized;
+        // can't overflow, the elements fit in the buffer
+        let mut out = Vec::with_capacity(accessor.count * components);
+        for i in 0..accessor.count {
+            let bytes = &buffer[start + i * stride..];
+            for c in 0..components {
+                let b = &bytes[c * size..(c + 1) * size];
+                out.push(match accessor.component_type {
+                    BYTE => {
+                        let v = b[0] as i8 as f32;
+                        if normalized { (v / 127.0).max(-1.0) } else { v }
+                    }
+                    UNSIGNED_BYTE => {
+                        let v = b[0] as f32;
+                        if normalized { v / 255.0 } else { v }
+                    }
+                    SHORT => {
+                        let v = i16::from_le_bytes([b[0], b[1]]) as f32;
+                        if normalized {
+                            (v / 32767.0).max(-1.0)
+                        } else {
+                            v
+                        }
+                    }
+                    UNSIGNED_SHORT => {
+                        let v = u16::from_le_bytes([b[0], b[1]]) as f32;
+                        if normalized { v / 65535.0 } else { v }
+                    }
+                    UNSIGNED_INT => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32,
+                    _ => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                });
+            }
+        }
+        Ok((out, components))
+    }
+
+    fn read_array<const N: usize>(
+        &self,
+        index: usize,
+        context: &str,
+    ) -> Result<Vec<[f32; N]>, GltfError> {
+        let (values, components) = self.read_floats(index, context)?;
+        if components != N {
+            return Err(invalid(
+                format!("{context}, accessor {index}"),
+                format!("has {components} components, expected {N}"),
+            ));
+        }
+        Ok(values
+            .chunks_exact(N)
+            .map(|c| c.try_into().unwrap())
+            .collect())
+    }
+
+    fn read_indices(&self, index: usize, context: &str) -> Result<Vec<u32>, GltfError> {
+        Ok(self
+            .read_array::<1>(index, context)?
+            .into_iter()
+            .map(|[v]| v as u32)
+            .collect())
+    }
+
+    fn node_context(&self, index: usize) -> String {
+        named(
+            "node",
+            index,
+            self.root.nodes.get(index).and_then(|n| n.name.as_deref()),
+        )
+    }
+
+    fn parents(&self) -> Vec<Option<usize>> {
+        let mut parents = vec![None; self.root.nodes.len()];
+        for (i, node) in self.root.nodes.iter().enumerate() {
+            for &child in &node.children {
+                if let Some(parent) = parents.get_mut(child) {
+                    *parent = Some(i);
+                }
+            }
+        }
+        parents
+    }
+
+    fn rest_pose(&self) -> Vec<(Vec3, Quat, Vec3)> {
+        self.root
+            .nodes
+            .iter()
+            .map(|node| match node.matrix {
+                Some(m) => {
+                    let (s, r, t) = Mat4::from_cols_array(&m).to_scale_rotation_translation();
+                    (t, r, s)
+                }
+                None => (
+                    Vec3::from(node.translation.unwrap_or_default()),
+                    Quat::from_array(node.rotation.unwrap_or([0.0, 0.0, 0.0, 1.0])).normalize(),
+                    Vec3::from(node.scale.unwrap_or([1.0; 3])),
+                ),
+            })
+            .collect()
+    }
+
+    fn global_transform(
+        &self,
+        node: usize,
+        parents: &[Option<usize>],
+        pose: &[(Vec3, Quat, Vec3)],
+    ) -> Mat4 {
+        let mut transform = Mat4::IDENTITY;
+        let mut current = Some(node);
+        // bounded in case a malformed file has a cycle
+        for _ in 0..parents.len() {
+            let Some(n) = current else { break };
+            let (t, r, s) = pose[n];
+            transform = Mat4::from_scale_rotation_translation(s, r, t) * transform;
+            current = parents[n];
+        }
+        transform
+    }
+
+    /// Every triangle primitive reachable from the default scene, in traversal order.
+    pub fn primitives(&self) -> Result<Vec<GltfPrimitive>, GltfError> {
+        let nodes = &self.root.nodes;
+        let parents = self.parents();
+        let roots: Vec<usize> = match self.root.scenes.get(self.root.scene.unwrap_or(0)) {
+            Some(scene) => scene.nodes.clone(),
+            None => (0..nodes.len()).filter(|n| parents[*n].is_none()).collect(),
+        };
+        let pose = self.rest_pose();
+
+        let mut out = Vec::new();
+        let mut visited = vec![false; nodes.len()];
+        let mut stack: Vec<usize> = roots.into_iter().rev().collect();
+        while let Some(index) = stack.pop() {
+            let node_context = self.node_context(index);
+            let node = nodes
+                .get(index)
+                .ok_or_else(|| invalid(&node_context, "does not exist"))?;
+            if std::mem::replace(&mut visited[index], true) {
+                return Err(invalid(
+                    &node_context,
+                    "is part of a cycle or has two parents",
+                ));
+            }
+            stack.extend(node.children.iter().rev());
+
+            let Some(mesh_index) = node.mesh else {
+                continue;
+            };
+            let mesh = self.root.meshes.get(mesh_index).ok_or_else(|| {
+                invalid(&node_context, format!("mesh {mesh_index} does not exist"))
+            })?;
+            let skinned = node.skin.is_some();
+            let world = (!skinned).then(|| self.global_transform(index, &parents, &pose));
+            for (p, primitive) in mesh.primitives.iter().enumerate() {
+                let context = format!(
+                    "{node_context}, {} primitive {p}",
+                    named("mesh", mesh_index, mesh.name.as_deref())
+                );
+                if let Some(material) = primitive.material
+                    && material >= self.root.materials.len()
+                {
+                    return Err(invalid(
+                        &context,
+                        format!("material {material} does not exist"),
+                    ));
+                }
+                out.push(GltfPrimitive {
+                    node: index,
+                    mesh: self.primitive(primitive, &context, world)?,
+                    material: primitive.material,
+                    skinned,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    fn primitive(
+        &self,
+        primitive: &PrimitiveDef,
+        context: &str,
+        world: Option<Mat4>,
+    ) -> Result<MeshData, GltfError> {
+        if let Some(mode) = primitive.mode.filter(|m| *m != TRIANGLES) {
+            return Err(invalid(
+                context,
+                format!("only triangle lists are supported, found mode {mode}"),
+            ));
+        }
+        let attribute_context = |name: &str| format!("{context}, {name}");
+        let position = *primitive
+            .attributes
+            .get("POSITION")
+            .ok_or_else(|| invalid(context, "has no POSITION attribute"))?;
+
+        let mut mesh = MeshData {
+            positions: self.read_array(position, &attribute_context("POSITION"))?,
+            ..Default::default()
+        };
+        let count = mesh.positions.len();
+        let attribute = |name: &str| -> Option<(usize, String)> {
+            primitive
+                .attributes
+                .get(name)
+                .map(|index| (*index, attribute_context(name)))
+        };
+        let check = |len: usize, context: &str| {
+            if len == count {
+                Ok(())
+            } else {
+                Err(invalid(
+                    context,
+                    format!("has {len} elements, POSITION has {count}"),
+                ))
+            }
+        };
+
+        if let Some((index, context)) = attribute("NORMAL") {
+            mesh.normals = self.read_array(index, &context)?;
+            check(mesh.normals.len(), &context)?;
+        }
+        if let Some((index, context)) = attribute("TEXCOORD_0") {
+            mesh.texcoords = self.read_array(index, &context)?;
+            check(mesh.texcoords.len(), &context)?;
+        }
+        if let Some((index, context)) = attribute("TEXCOORD_1") {
+            mesh.texcoords2 = self.read_array(index, &context)?;
+            check(mesh.texcoords2.len(), &context)?;
+        }
+        if let Some((index, context)) = attribute("TANGENT") {
+            mesh.tangents = self.read_array(index, &context)?;
+            check(mesh.tangents.len(), &context)?;
+        }
+        if let Some((index, context)) = attribute("COLOR_0") {
+            let (values, components) = self.read_floats(index, &context)?;
+            let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            mesh.colors = match components {
+                3 => values
+                    .chunks_exact(3)
+                    .map(|c| [to_u8(c[0]), to_u8(c[1]), to_u8(c[2]), 255])
+                    .collect(),
+                4 => values
+                    .chunks_exact(4)
+                    .map(|c| [to_u8(c[0]), to_u8(c[1]), to_u8(c[2]), to_u8(c[3])])
+                    .collect(),
+                n => {
+                    return Err(invalid(
+                        &context,
+                        format!("has {n} components, expected 3 or 4"),
+                    ));
+                }
+            };
+            check(mesh.colors.len(), &context)?;
+        }
+        if let Some((index, context)) = attribute("JOINTS_0") {
+            let joints = self.read_array::<4>(index, &context)?;
+            check(joints.len(), &context)?;
+            if let Some(joint) = joints.iter().flatten().find(|j| **j > 255.0) {
+                return Err(invalid(
+                    &context,
+                    format!("joint {joint} is above raylib's limit of 255"),
+                ));
+            }
+            mesh.joints = joints.iter().map(|j| j.map(|v| v as u8)).collect();
+        }
+        if let Some((index, context)) = attribute("WEIGHTS_0") {
+            mesh.weights = self.read_array(index, &context)?;
+            check(mesh.weights.len(), &context)?;
+        }
+
+        match primitive.indices {
+            Some(index) => {
+                let context = attribute_context("indices");
+                if count > u16::MAX as usize + 1 {
+                    return Err(invalid(
+                        &context,
+                        format!("indexed meshes are limited to 65536 vertices, found {count}"),
+                    ));
+                }
+                let indices = self.read_indices(index, &context)?;
+                if let Some(i) = indices.iter().find(|i| **i as usize >= count) {
+                    return Err(invalid(
+                        &context,
+                        format!("index {i} is out of range for {count} vertices"),
+                    ));
+                }
+                if !indices.len().is_multiple_of(3) {
+                    return Err(invalid(
+                        &context,
+                        format!("has {} indices, not a multiple of 3", indices.len()),
+                    ));
+                }
+                mesh.indices = indices.into_iter().map(|i| i as u16).collect();
+            }
+            None if !count.is_multiple_of(3) => {
+                return Err(invalid(
+                    context,
+                    format!("has {count} vertices without indices, not a multiple of 3"),
+                ));
+            }
+            None => {}
+        }
+
+        if let Some(world) = world {
+            let normal_matrix = world.inverse().transpose();
+            for p in &mut mesh.positions {
+                *p = world.transform_point3(Vec3::from(*p)).to_array();
+            }
+            for n in &mut mesh.normals {
+                *n = normal_matrix
+                    .transform_vector3(Vec3::from(*n))
+                    .normalize_or_zero()
+                    .to_array();
+            }
+            for t in &mut mesh.tangents {
+                let v = world
+                    .transform_vector3(Vec3::new(t[0], t[1], t[2]))
+                    .normalize_or_zero();
+                *t = [v.x, v.y, v.z, t[3]];
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// The first skin's joints, or `None` if the file has no skins.
+    pub fn skeleton(&self) -> Result<Option<GltfSkeleton>, GltfError> {
+        let Some(skin) = self.root.skins.first() else {
+            return Ok(None);
+        };
+        let context = "skin 0";
+        if skin.joints.len() > 256 {
+            return Err(invalid(
+                context,
+                format!("has {} joints, raylib supports 256", skin.joints.len()),
+            ));
+        }
+        if let Some(joint) = skin.joints.iter().find(|j| **j >= self.root.nodes.len()) {
+            return Err(invalid(
+                context,
+                format!("joint node {joint} does not exist"),
+            ));
+        }
+
+        let node_parents = self.parents();
+        let parents = skin
+            .joints
+            .iter()
+            .map(|&joint| {
+                let mut current = node_parents[joint];
+                for _ in 0..node_parents.len() {
+                    let Some(node) = current else { break };
+                    if let Some(bone) = skin.joints.iter().position(|j| *j == node) {
+                        return bone as i32;
+                    }
+                    current = node_parents[node];
+                }
+                -1
+            })
+            .collect();
+        let names = skin
+            .joints
+            .iter()
+            .enumerate()
+            .map(|(i, &joint)| {
+                self.root.nodes[joint]
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("joint {i}"))
+            })
+            .collect();
+        let bind_pose = match skin.inverse_bind_matrices {
+            Some(index) => {
+                let context = format!("{context}, inverseBindMatrices");
+                let matrices = self.read_array::<16>(index, &context)?;
+                if matrices.len() != skin.joints.len() {
+                    return Err(invalid(
+                        context,
+                        format!(
+                            "has {} matrices for {} joints",
+                            matrices.len(),
+                            skin.joints.len()
+                        ),
+                    ));
+                }
+                matrices
+                    .iter()
+                    .map(|m| Mat4::from_cols_array(m).inverse())
+                    .collect()
+            }
+            None => {
+                let pose = self.rest_pose();
+                skin.joints
+                    .iter()
+                    .map(|&joint| self.global_transform(joint, &node_parents, &pose))
+                    .collect()
+            }
+        };
+
+        Ok(Some(GltfSkeleton {
+            joints: skin.joints.clone(),
+            names,
+            parents,
+            bind_pose,
+        }))
+    }
+
+    /// Samples every animation for the joints of `skeleton`.
+    pub fn animations(&self, skeleton: &GltfSkeleton) -> Result<Vec<GltfAnimation>, GltfError> {
+        let parents = self.parents();
+        let rest = self.rest_pose();
+        let mut out = Vec::with_capacity(self.root.animations.len());
+        for (a, animation) in self.root.animations.iter().enumerate() {
+            let context = named("animation", a, animation.name.as_deref());
+            let mut tracks = Vec::new();
+            for (c, channel) in animation.channels.iter().enumerate() {
+                let context = format!("{context}, channel {c}");
+                if let Some(track) = self.track(animation, channel, &context)? {
+                    tracks.push(track);
+                }
+            }
+
+            let duration = tracks
+                .iter()
+                .map(|t| *t.times.last().unwrap())
+                .fold(0.0f32, f32::max);
+            if !duration.is_finite() || duration > GLTF_MAX_ANIMATION_SECONDS {
+                return Err(invalid(
+                    &context,
+                    format!(
+                        "lasts {duration} seconds, more than the {GLTF_MAX_ANIMATION_SECONDS} supported"
+                    ),
+                ));
+            }
+            let frame_count = (duration * GLTF_ANIMATION_FPS).ceil() as usize + 1;
+            let mut pose = rest.clone();
+            let frames = (0..frame_count)
+                .map(|frame| {
+                    let time = frame as f32 / GLTF_ANIMATION_FPS;
+                    for track in &tracks {
+                        let v = track.sample(time);
+                        let (t, r, s) = &mut pose[track.node];
+                        match track.path {
+                            Path3::Translation => *t = v.truncate(),
+                            Path3::Rotation => *r = Quat::from_vec4(v).normalize(),
+                            Path3::Scale => *s = v.truncate(),
+                        }
+                    }
+                    skeleton
+                        .joints
+                        .iter()
+                        .map(|&joint| self.global_transform(joint, &parents, &pose))
+                        .collect()
+                })
+                .collect();
+            out.push(GltfAnimation {
+                name: animation.name.clone().unwrap_or_default(),
+                frames,
+            });
+        }
+        Ok(out)
+    }
+
+    fn track(
+        &self,
+        animation: &AnimationDef,
+        channel: &ChannelDef,
+        context: &str,
+    ) -> Result<Option<Track>, GltfError> {
+        let Some(node) = channel.target.node else {
+            return Ok(None);
+        };
+        if node >= self.root.nodes.len() {
+            return Err(invalid(
+                context,
+                format!("target node {node} does not exist"),
+            ));
+        }
+        let (path, components) = match channel.target.path.as_str() {
+            "translation" => (Path3::Translation, 3),
+            "rotation" => (Path3::Rotation, 4),
+            "scale" => (Path3::Scale, 3),
+            // morph targets have no raylib equivalent
+            "weights" => return Ok(None),
+            other => return Err(invalid(context, format!("unknown target path {other:?}"))),
+        };
+        let sampler = animation.samplers.get(channel.sampler).ok_or_else(|| {
+            invalid(
+                context,
+                format!("sampler {} does not exist", channel.sampler),
+            )
+        })?;
+        let interpolation = match sampler.interpolation.as_deref().unwrap_or("LINEAR") {
+            "LINEAR" => Interpolation::Linear,
+            "STEP" => Interpolation::Step,
+            "CUBICSPLINE" => Interpolation::CubicSpline,
+            other => return Err(invalid(context, format!("unknown interpolation {other:?}"))),
+        };
+
+        let times: Vec<f32> = self
+            .read_array::<1>(sampler.input, &format!("{context}, input"))?
+            .into_iter()
+            .map(|[t]| t)
+            .collect();
+        if times.is_empty() {
+            return Err(invalid(context, "sampler has no keyframes"));
+        }
+        if times.iter().any(|t| !t.is_finite()) || times.windows(2).any(|w| w[1] < w[0]) {
+            return Err(invalid(
+                context,
+                "keyframe times are not finite and non-decreasing",
+            ));
+        }
+        let output_context = format!("{context}, output");
+        let (values, found) = self.read_floats(sampler.output, &output_context)?;
+        if found != components {
+            return Err(invalid(
+                output_context,
+                format!("has {found} components, expected {components}"),
+            ));
+        }
+        let per_key = if interpolation == Interpolation::CubicSpline {
+            3
+        } else {
+            1
+        };
+        let values: Vec<Vec4> = values
+            .chunks_exact(components)
+            .map(|c| match c {
+                [x, y, z] => Vec4::new(*x, *y, *z, 0.0),
+                _ => Vec4::from_slice(c),
+            })
+            .collect();
+        if values.len() != times.len() * per_key {
+            return Err(invalid(
+                output_context,
+                format!("has {} values for {} keyframes", values.len(), times.len()),
+            ));
+        }
+        Ok(Some(Track {
+            node,
+            path,
+            interpolation,
+            times,
+            values,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Path3 {
+    Translation,
+    Rotation,
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Interpolation {
+    Step,
+    Linear,
+    CubicSpline,
+}
+
+#[derive(Debug)]
+struct Track {
+    node: usize,
+    path: Path3,
+    interpolation: Interpolation,
+    times: Vec<f32>,
+    /// For cubic splines every keyframe is in-tangent, value, out-tangent
+    values: Vec<Vec4>,
+}
+
+impl Track {
+    fn value(&self, key: usize) -> Vec4 {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.values[key * 3 + 1],
+            _ => self.values[key],
+        }
+    }
+
+    fn sample(&self, time: f32) -> Vec4 {
+        let last = self.times.len() - 1;
+        if time <= self.times[0] {
+            return self.value(0);
+        }
+        if time >= self.times[last] {
+            return self.value(last);
+        }
+        let key = self.times.partition_point(|t| *t <= time).saturating_sub(1);
+        let (t0, t1) = (self.times[key], self.times[key + 1]);
+        let dt = t1 - t0;
+        let s = if dt > 0.0 { (time - t0) / dt } else { 0.0 };
+        let (a, b) = (self.value(key), self.value(key + 1));
+        match self.interpolation {
+            Interpolation::Step => a,
+            Interpolation::Linear if self.path == Path3::Rotation => {
+                Vec4::from(Quat::from_vec4(a).slerp(Quat::from_vec4(b), s))
+            }
+            Interpolation::Linear => a.lerp(b, s),
+            Interpolation::CubicSpline => {
+                let out_tangent = self.values[key * 3 + 2] * dt;
+                let in_tangent = self.values[(key + 1) * 3] * dt;
+                let (s2, s3) = (s * s, s * s * s);
+                a * (2.0 * s3 - 3.0 * s2 + 1.0)
+                    + out_tangent * (s3 - 2.0 * s2 + s)
+                    + b * (-2.0 * s3 + 3.0 * s2)
+                    + in_tangent * (s3 - s2)
+            }
+        }
+    }
+}
+
+fn split_glb(data: &[u8]) -> Result<(&[u8], Option<&[u8]>), GltfError> {
+    let read_u32 = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .ok_or_else(|| GltfError::Format("GLB file is truncated".into()))
+    };
+    let version = read_u32(4)?;
+    if version != 2 {
+        return Err(GltfError::Format(format!(
+            "unsupported GLB version {version}"
+        )));
+    }
+    let length = (read_u32(8)? as usize).min(data.len());
+
+    let (mut json, mut bin) = (None, None);
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(offset)? as usize;
+        let kind = read_u32(offset + 4)?;
+        let chunk = data
+            .get(offset + 8..offset + 8 + chunk_length)
+            .ok_or_else(|| {
+                GltfError::Format("GLB chunk extends past the end of the file".into())
+            })?;
+        match kind {
+            GLB_JSON if json.is_none() => json = Some(chunk),
+            GLB_BIN if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+        offset += 8 + chunk_length;
+    }
+    let json = json.ok_or_else(|| GltfError::Format("GLB file has no JSON chunk".into()))?;
+    Ok((json, bin))
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            break;
+        }
+        let v = BASE64.iter().position(|b| *b == c)? as u32;
+        bits = bits << 6 | v;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Encodes `mesh` as a single-node glTF scene and returns the JSON document and its binary buffer.
+fn encode_mesh(mesh: &MeshData) -> (Value, Vec<u8>) {
+    let mut bin = Vec::new();
+    let mut views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut push = |bytes: Vec<u8>, accessor: Value, target: u32| -> usize {
+        bin.resize(bin.len().next_multiple_of(4), 0);
+        views.push(json!({
+            "buffer": 0,
+            "byteOffset": bin.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        bin.extend_from_slice(&bytes);
+        let mut accessor = accessor;
+        accessor["bufferView"] = json!(views.len() - 1);
+        accessors.push(accessor);
+        accessors.len() - 1
+    };
+    fn floats<const N: usize>(values: &[[f32; N]]) -> Vec<u8> {
+        values
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect()
+    }
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+    let count = mesh.positions.len();
+    let mut attributes = serde_json::Map::new();
+    let (mut min, mut max) = ([f32::MAX; 3], [f32::MIN; 3]);
+    for p in &mesh.positions {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    let position = push(
+        floats(&mesh.positions),
+        json!({ "componentType": FLOAT, "count": count, "type": "VEC3", "min": min, "max": max }),
+        ARRAY_BUFFER,
+    );
+    attributes.insert("POSITION".into(), json!(position));
+
+    let float_attributes: [(&str, Vec<u8>, &str, bool); 5] = [
+        (
+            "NORMAL",
+            floats(&mesh.normals),
+            "VEC3",
+            mesh.normals.is_empty(),
+        ),
+        (
+            "TEXCOORD_0",
+            floats(&mesh.texcoords),
+            "VEC2",
+            mesh.texcoords.is_empty(),
+        ),
+        (
+            "TEXCOORD_1",
+            floats(&mesh.texcoords2),
+            "VEC2",
+            mesh.texcoords2.is_empty(),
+        ),
+        (
+            "TANGENT",
+            floats(&mesh.tangents),
+            "VEC4",
+            mesh.tangents.is_empty(),
+        ),
+        (
+            "WEIGHTS_0",
+            floats(&mesh.weights),
+            "VEC4",
+            mesh.weights.is_empty(),
+        ),
+    ];
+    for (name, bytes, kind, empty) in float_attributes {
+        if !empty {
+            let index = push(
+                bytes,
+                json!({ "componentType": FLOAT, "count": count, "type": kind }),
+                ARRAY_BUFFER,
+            );
+            attributes.insert(name.into(), json!(index));
+        }
+    }
+    if !mesh.colors.is_empty() {
+        let index = push(
+            mesh.colors.concat(),
+            json!({ "componentType": UNSIGNED_BYTE, "normalized": true, "count": count, "type": "VEC4" }),
+            ARRAY_BUFFER,
+        );
+        attributes.insert("COLOR_0".into(), json!(index));
+    }
+    if !mesh.joints.is_empty() {
+        let index = push(
+            mesh.joints.concat(),
+            json!({ "componentType": UNSIGNED_BYTE, "count": count, "type": "VEC4" }),
+            ARRAY_BUFFER,
+        );
+        attributes.insert("JOINTS_0".into(), json!(index));
+    }
+
+    let mut primitive = json!({ "attributes": attributes, "mode": TRIANGLES });
+    if !mesh.indices.is_empty() {
+        let bytes = mesh.indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let index = push(
+            bytes,
+            json!({ "componentType": UNSIGNED_SHORT, "count": mesh.indices.len(), "type": "SCALAR" }),
+            ELEMENT_ARRAY_BUFFER,
+        );
+        primitive["indices"] = json!(index);
+    }
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let document = json!({
+        "asset": { "version": "2.0", "generator": "raylib-rs" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "meshes": [{ "primitives": [primitive] }],
+        "accessors": accessors,
+        "bufferViews": views,
+        "buffers": [{ "byteLength": bin.len() }],
+    });
+    (document, bin)
+}
+
+/// Encodes `mesh` as a `.glb` file.
+pub fn mesh_to_glb(mesh: &MeshData) -> Vec<u8> {
+    let (document, bin) = encode_mesh(mesh);
+    let mut json = serde_json::to_vec(&document).unwrap();
+    json.resize(json.len().next_multiple_of(4), b' ');
+
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(length);
+    out.extend_from_slice(GLB_MAGIC);
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(length as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(&GLB_JSON.to_le_bytes());
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(&GLB_BIN.to_le_bytes());
+    out.extend_from_slice(&bin);
+    out
+}
+
+/// Encodes `mesh` as a `.gltf` file with the vertex data embedded as a base64 data uri.
+pub fn mesh_to_gltf(mesh: &MeshData) -> Vec<u8> {
+    let (mut document, bin) = encode_mesh(mesh);
+    document["buffers"][0]["uri"] = json!(format!(
+        "data:application/octet-stream;base64,{}",
+        base64_encode(&bin)
+    ));
+    serde_json::to_vec_pretty(&document).unwrap()
+}
+
+/// Writes `mesh` to `filename`, as `.glb` if the extension says so and as `.gltf` otherwise.
+pub(crate) fn export_mesh(mesh: &ffi::Mesh, filename: &str) -> Result<(), GltfError> {
+    let data = MeshData::from_mesh(mesh);
+    let binary = Path::new(filename)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("glb"));
+    let bytes = if binary {
+        mesh_to_glb(&data)
+    } else {
+        mesh_to_gltf(&data)
+    };
+    std::fs::write(filename, bytes).map_err(|source| GltfError::Io {
+        path: filename.into(),
+        source,
+    })
+}
+
+fn set_name(dst: &mut [c_char; 32], name: &str) {
+    let len = name.len().min(dst.len() - 1);
+    for (d, s) in dst.iter_mut().zip(&name.as_bytes()[..len]) {
+        *d = *s as c_char;
+    }
+    dst[len] = 0;
+}
+
+fn to_transform(m: &Mat4) -> ffi::Transform {
+    let (s, r, t) = m.to_scale_rotation_translation();
+    ffi::Transform {
+        translation: ffi::Vector3 {
+            x: t.x,
+            y: t.y,
+            z: t.z,
+        },
+        rotation: ffi::Quaternion {
+            x: r.x,
+            y: r.y,
+            z: r.z,
+            w: r.w,
+        },
+        scale: ffi::Vector3 {
+            x: s.x,
+            y: s.y,
+            z: s.z,
+        },
+    }
+}
+
+fn bone_infos(skeleton: &GltfSkeleton) -> Vec<ffi::BoneInfo> {
+    skeleton
+        .names
+        .iter()
+        .zip(&skeleton.parents)
+        .map(|(name, parent)| {
+            let mut bone = ffi::BoneInfo {
+                name: [0; 32],
+                parent: *parent,
+            };
+            set_name(&mut bone.name, name);
+            bone
+        })
+        .collect()
+}
+
+/// Builds an unuploaded raylib mesh from `data`.
+fn raylib_mesh(data: &MeshData) -> Result<Mesh, AllocationError> {
+    let mut mesh = Mesh(unsafe { std::mem::zeroed() });
+    mesh.0.vertexCount = data.positions.len() as i32;
+    mesh.0.triangleCount = data.triangle_count() as i32;
+    mesh.0.vertices = DataBuf::leak_slice(&data.positions)?;
+    mesh.0.normals = DataBuf::leak_slice(&data.normals)?;
+    mesh.0.texcoords = DataBuf::leak_slice(&data.texcoords)?;
+    mesh.0.texcoords2 = DataBuf::leak_slice(&data.texcoords2)?;
+    mesh.0.tangents = DataBuf::leak_slice(&data.tangents)?;
+    mesh.0.colors = DataBuf::leak_slice(&data.colors)?;
+    mesh.0.indices = DataBuf::leak_slice(&data.indices)?;
+    Ok(mesh)
+}
+
+/// Gives `mesh` the buffers raylib's CPU skinning writes to.
+fn attach_skin(mesh: &mut Mesh, data: &MeshData, bone_count: usize) -> Result<(), AllocationError> {
+    if data.joints.is_empty() || data.weights.is_empty() {
+        return Ok(());
+    }
+    mesh.0.boneIds = DataBuf::leak_slice(&data.joints)?;
+    mesh.0.boneWeights = DataBuf::leak_slice(&data.weights)?;
+    mesh.0.animVertices = DataBuf::leak_slice(&data.positions)?;
+    mesh.0.animNormals = DataBuf::leak_slice(&data.normals)?;
+    let identity: ffi::Matrix = Matrix::identity().into();
+    mesh.0.boneMatrices = DataBuf::leak_slice(&vec![identity; bone_count])?;
+    mesh.0.boneCount = bone_count as i32;
+    Ok(())
+}
+
+impl GltfDocument {
+    fn image_bytes(&self, index: usize) -> Option<&[u8]> {
+        if let Some(Some(data)) = self.images.get(index) {
+            return Some(data);
+        }
+        let view = self
+            .root
+            .buffer_views
+            .get(self.root.images.get(index)?.buffer_view?)?;
+        self.buffers
+            .get(view.buffer)?
+            .get(view.byte_offset..view.byte_offset.checked_add(view.byte_length)?)
+    }
+
+    fn load_texture(
+        &self,
+        texture: &TextureRef,
+        context: &str,
+    ) -> Result<ffi::Texture2D, GltfError> {
+        let context = format!("{context}, texture {}", texture.index);
+        let source = self
+            .root
+            .textures
+            .get(texture.index)
+            .ok_or_else(|| invalid(&context, "does not exist"))?
+            .source
+            .ok_or_else(|| invalid(&context, "has no image source"))?;
+        let bytes = self
+            .image_bytes(source)
+            .ok_or_else(|| invalid(&context, format!("image {source} has no data")))?;
+        let file_type = if bytes.starts_with(b"\x89PNG") {
+            c".png"
+        } else if bytes.starts_with(&[0xFF, 0xD8]) {
+            c".jpg"
+        } else {
+            return Err(invalid(
+                &context,
+                format!("image {source} is not a PNG or JPEG"),
+            ));
+        };
+
+        let image = unsafe {
+            ffi::LoadImageFromMemory(file_type.as_ptr(), bytes.as_ptr(), bytes.len() as i32)
+        };
+        if image.data.is_null() {
+            return Err(invalid(
+                &context,
+                format!("image {source} could not be decoded"),
+            ));
+        }
+        let result = unsafe { ffi::LoadTextureFromImage(image) };
+        unsafe { ffi::UnloadImage(image) };
+        if result.id == 0 {
+            return Err(invalid(
+                &context,
+                format!("image {source} could not be uploaded"),
+            ));
+        }
+        Ok(result)
+    }
+
+    fn material(&self, index: usize) -> Result<Material, GltfError> {
+        let def = &self.root.materials[index];
+        let context = named("material", index, def.name.as_deref());
+        // dropping a partially built material unloads the textures loaded so far
+        let mut material = Material(unsafe { ffi::LoadMaterialDefault() });
+        let maps = material.maps_mut();
+        let map = |index: MaterialMapIndex| index as usize;
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        if let Some(pbr) = &def.pbr_metallic_roughness {
+            let [r, g, b, a] = pbr.base_color_factor.unwrap_or([1.0; 4]);
+            let albedo = &mut maps[map(MaterialMapIndex::MATERIAL_MAP_ALBEDO)].0;
+            albedo.color = ffi::Color::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a));
+            if let Some(texture) = &pbr.base_color_texture {
+                albedo.texture =
+                    self.load_texture(texture, &format!("{context}, baseColorTexture"))?;
+            }
+            maps[map(MaterialMapIndex::MATERIAL_MAP_METALNESS)].0.value =
+                pbr.metallic_factor.unwrap_or(1.0);
+            let roughness = &mut maps[map(MaterialMapIndex::MATERIAL_MAP_ROUGHNESS)].0;
+            roughness.value = pbr.roughness_factor.unwrap_or(1.0);
+            if let Some(texture) = &pbr.metallic_roughness_texture {
+                roughness.texture =
+                    self.load_texture(texture, &format!("{context}, metallicRoughnessTexture"))?;
+            }
+        }
+        if let Some(texture) = &def.normal_texture {
+            maps[map(MaterialMapIndex::MATERIAL_MAP_NORMAL)].0.texture =
+                self.load_texture(texture, &format!("{context}, normalTexture"))?;
+        }
+        if let Some(texture) = &def.occlusion_texture {
+            maps[map(MaterialMapIndex::MATERIAL_MAP_OCCLUSION)]
+                .0
+                .texture = self.load_texture(texture, &format!("{context}, occlusionTexture"))?;
+        }
+        let emission = &mut maps[map(MaterialMapIndex::MATERIAL_MAP_EMISSION)].0;
+        if let Some([r, g, b]) = def.emissive_factor {
+            emission.color = ffi::Color::new(to_u8(r), to_u8(g), to_u8(b), 255);
+        }
+        if let Some(texture) = &def.emissive_texture {
+            emission.texture =
+                self.load_texture(texture, &format!("{context}, emissiveTexture"))?;
+        }
+        Ok(material)
+    }
+}
+
+impl RaylibHandle {
+    /// Builds a model from every mesh primitive in the default scene of `doc`.
+    ///
+    /// The model's first material is raylib's default, glTF material `i` becomes material `i + 1`.
+    pub fn load_model_from_gltf(
+        &mut self,
+        _: &RaylibThread,
+        doc: &GltfDocument,
+    ) -> Result<Model, GltfError> {
+        let primitives = doc.primitives()?;
+        if primitives.is_empty() {
+            return Err(invalid("scene", "has no meshes"));
+        }
+        let skeleton = doc.skeleton()?;
+
+        // filled in as we go, so an error part way unloads everything loaded so far
+        let mut model = Model(unsafe { std::mem::zeroed() });
+        model.0.transform = Matrix::identity().into();
+        let material_count = doc.material_count() + 1;
+        model.0.materials = DataBuf::<ffi::Material>::alloc(material_count as i32)?
+            .leak()
+            .0
+            .as_ptr();
+        unsafe { *model.0.materials = ffi::LoadMaterialDefault() };
+        model.0.materialCount = 1;
+        for i in 0..doc.material_count() {
+            let material = doc.material(i)?;
+            unsafe { *model.0.materials.add(i + 1) = material.to_raw() };
+            model.0.materialCount += 1;
+        }
+
+        let mesh_count = primitives.len() as i32;
+        model.0.meshes = DataBuf::<ffi::Mesh>::alloc(mesh_count)?.leak().0.as_ptr();
+        model.0.meshMaterial = DataBuf::<i32>::alloc(mesh_count)?.leak().0.as_ptr();
+        for (i, primitive) in primitives.iter().enumerate() {
+            let mut mesh = raylib_mesh(&primitive.mesh)?;
+            if let (true, Some(skeleton)) = (primitive.skinned, &skeleton) {
+                attach_skin(&mut mesh, &primitive.mesh, skeleton.joints.len())?;
+            }
+            unsafe {
+                ffi::UploadMesh(mesh.as_mut(), false);
+                *model.0.meshes.add(i) = mesh.to_raw();
+                *model.0.meshMaterial.add(i) = primitive.material.map_or(0, |m| m as i32 + 1);
+            }
+            model.0.meshCount += 1;
+        }
+
+        if let Some(skeleton) = &skeleton {
+            let bind_pose: Vec<ffi::Transform> =
+                skeleton.bind_pose.iter().map(to_transform).collect();
+            model.0.bones = DataBuf::leak_slice(&bone_infos(skeleton))?;
+            model.0.bindPose = DataBuf::leak_slice(&bind_pose)?;
+            model.0.boneCount = skeleton.joints.len() as i32;
+        }
+        Ok(model)
+    }
+
+    /// Samples every animation in `doc` for the bones of its first skin.
+    pub fn load_model_animations_from_gltf(
+        &mut self,
+        _: &RaylibThread,
+        doc: &GltfDocument,
+    ) -> Result<Vec<ModelAnimation>, GltfError> {
+        let skeleton = doc
+            .skeleton()?
+            .ok_or_else(|| invalid("skins", "there is no skin to animate"))?;
+        let bones = bone_infos(&skeleton);
+        doc.animations(&skeleton)?
+            .iter()
+            .map(|animation| {
+                let mut anim = ModelAnimation(unsafe { std::mem::zeroed() });
+                set_name(&mut anim.0.name, &animation.name);
+                anim.0.boneCount = bones.len() as i32;
+                anim.0.bones = DataBuf::leak_slice(&bones)?;
+                anim.0.framePoses =
+                    DataBuf::<*mut ffi::Transform>::alloc(animation.frames.len() as i32)?
+                        .leak()
+                        .0
+                        .as_ptr();
+                for (f, frame) in animation.frames.iter().enumerate() {
+                    let poses: Vec<ffi::Transform> = frame.iter().map(to_transform).collect();
+                    unsafe { *anim.0.framePoses.add(f) = DataBuf::leak_slice(&poses)? };
+                    anim.0.frameCount += 1;
+                }
+                Ok(anim)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod gltf_test {
+    use super::*;
+
+    fn quad() -> MeshData {
+        MeshData {
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            texcoords: vec![[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+            colors: vec![
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [0, 0, 255, 255],
+                [255; 4],
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_glb_round_trip() {
+        let mesh = quad();
+        let doc = GltfDocument::from_slice(&mesh_to_glb(&mesh)).unwrap();
+        let primitives = doc.primitives().unwrap();
+        assert_eq!(primitives.len(), 1);
+        assert_eq!(primitives[0].mesh, mesh);
+        assert_eq!(primitives[0].mesh.triangle_count(), 2);
+    }
+
+    #[test]
+    fn test_gltf_round_trip() {
+        let mesh = MeshData {
+            tangents: vec![[1.0, 0.0, 0.0, -1.0]; 4],
+            ..quad()
+        };
+        let doc = GltfDocument::from_slice(&mesh_to_gltf(&mesh)).unwrap();
+        assert_eq!(doc.primitives().unwrap()[0].mesh, mesh);
+        assert_eq!(base64_decode(&base64_encode(b"raylib")).unwrap(), b"raylib");
+    }
+
+    #[test]
+    fn test_errors_name_their_node() {
+        let mut mesh = quad();
+        mesh.indices[5] = 9;
+        let (mut document, bin) = encode_mesh(&mesh);
+        document["nodes"][0]["name"] = json!("Arm");
+        document["buffers"][0]["uri"] = json!(format!("data:;base64,{}", base64_encode(&bin)));
+        let doc = GltfDocument::from_slice(document.to_string().as_bytes()).unwrap();
+
+        let error = doc.primitives().unwrap_err().to_string();
+        assert!(
+            error.starts_with("node 0 \"Arm\", mesh 0 primitive 0, indices"),
+            "{error}"
+        );
+        assert!(
+            error.ends_with("index 9 is out of range for 4 vertices"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_node_transform_is_baked() {
+        let (mut document, bin) = encode_mesh(&quad());
+        document["nodes"][0]["translation"] = json!([0.0, 0.0, 5.0]);
+        document["nodes"][0]["scale"] = json!([2.0, 2.0, 2.0]);
+        document["buffers"][0]["uri"] = json!(format!("data:;base64,{}", base64_encode(&bin)));
+        let doc = GltfDocument::from_slice(document.to_string().as_bytes()).unwrap();
+
+        let mesh = &doc.primitives().unwrap()[0].mesh;
+        assert_eq!(mesh.positions[2], [2.0, 2.0, 5.0]);
+        assert_eq!(mesh.normals[2], [0.0, 0.0, 1.0]);
+    }
+
+    /// Two joints, the root sliding 6 units along x between `times`
+    fn slide_document(times: [f32; 2]) -> GltfDocument {
+        let values = [0.0f32, 0.0, 0.0, 6.0, 0.0, 0.0];
+        let bin: Vec<u8> = times
+            .iter()
+            .chain(&values)
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let document = json!({
+            "asset": { "version": "2.0" },
+            "nodes": [{ "name": "root", "children": [1] }, { "name": "bone", "translation": [0.0, 1.0, 0.0] }],
+            "skins": [{ "joints": [0, 1] }],
+            "animations": [{
+                "name": "slide",
+                "channels": [{ "sampler": 0, "target": { "node": 0, "path": "translation" } }],
+                "samplers": [{ "input": 0, "output": 1 }],
+            }],
+            "accessors": [
+                { "bufferView": 0, "componentType": FLOAT, "count": 2, "type": "SCALAR" },
+                { "bufferView": 0, "byteOffset": 8, "componentType": FLOAT, "count": 2, "type": "VEC3" },
+            ],
+            "bufferViews": [{ "buffer": 0, "byteLength": bin.len() }],
+            "buffers": [{ "byteLength": bin.len(), "uri": format!("data:;base64,{}", base64_encode(&bin)) }],
+        });
+        GltfDocument::from_slice(document.to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_animation_sampling() {
+        let doc = slide_document([0.0, 1.0]);
+        let skeleton = doc.skeleton().unwrap().unwrap();
+        assert_eq!(skeleton.parents, [-1, 0]);
+        assert_eq!(skeleton.names, ["root", "bone"]);
+
+        let animations = doc.animations(&skeleton).unwrap();
+        assert_eq!(animations[0].name, "slide");
+        assert_eq!(animations[0].frames.len(), GLTF_ANIMATION_FPS as usize + 1);
+        let halfway = &animations[0].frames[GLTF_ANIMATION_FPS as usize / 2];
+        assert!(
+            halfway[1]
+                .w_axis
+                .abs_diff_eq(Vec4::new(3.0, 1.0, 0.0, 1.0), 1e-5)
+        );
+    }
+
+    #[test]
+    fn test_oversized_input_is_rejected() {
+        let doc = slide_document([0.0, 1e9]);
+        let skeleton = doc.skeleton().unwrap().unwrap();
+        let error = doc.animations(&skeleton).unwrap_err().to_string();
+        assert!(error.contains("seconds"), "{error}");
+        let doc = slide_document([0.0, f32::INFINITY]);
+        assert!(doc.animations(&skeleton).is_err());
+        for times in [[f32::NAN, 1.0], [1.0, 0.5]] {
+            let doc = slide_document(times);
+            let error = doc.animations(&skeleton).unwrap_err().to_string();
+            assert!(error.contains("keyframe times"), "{error}");
+        }
+
+        let document = json!({
+            "asset": { "version": "2.0" },
+            "accessors": [
+                { "componentType": FLOAT, "count": usize::MAX / 2, "type": "VEC3" },
+                { "componentType": FLOAT, "count": 1usize << 30, "type": "SCALAR" },
+                { "componentType": FLOAT, "count": 4, "type": "VEC2" },
+            ],
+        });
+        let doc = GltfDocument::from_slice(document.to_string().as_bytes()).unwrap();
+        assert!(doc.read_floats(0, "test").is_err());
+        assert!(doc.read_floats(1, "test").is_err());
+        assert_eq!(doc.read_floats(2, "test").unwrap(), (vec![0.0; 8], 2));
+    }
+}
@@ -0,0 +1,217 @@
+//! Reloading textures and shaders when their files change on disk.
+//!
+//! [`HotTexture`] and [`HotShader`] own their GPU resource and swap it in place when `poll` sees a newer
+//! modification time, so call `poll` once per frame. If a reload fails the previous resource is kept.
+//! ```ignore
+//! let mut shader = HotShader::load(&mut rl, &thread, None, Some("shaders/bloom.fs"))?;
+//! while !rl.window_should_close() {
+//!     if let Err(e) = shader.poll(&mut rl, &thread) {
+//!         eprintln!("{e}");
+//!     }
+//!     let intensity = shader.location("intensity");
+//!     shader.set_shader_value(intensity, 0.8f32);
+//!     ...
+//! }
+//! ```
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::consts::ShaderLocationIndex;
+use crate::core::shaders::{RaylibShader, Shader};
+use crate::core::texture::Texture2D;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::error::{LoadShaderError, LoadTextureError};
+use crate::ffi;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// A texture that reloads when its file changes. Texture parameters such as the filter are reset by a reload.
+#[derive(Debug)]
+pub struct HotTexture {
+    texture: Texture2D,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl HotTexture {
+    /// Loads the texture at `path` and starts watching it.
+    pub fn load(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: impl AsRef<Path>,
+    ) -> Result<HotTexture, LoadTextureError> {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified(&path);
+        let texture = rl.load_texture(thread, &path.to_string_lossy())?;
+        Ok(HotTexture {
+            texture,
+            path,
+            modified,
+        })
+    }
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reloads the texture if its file changed since the last load. Returns whether it was reloaded.
+    /// On error the previous texture is kept and the same change isn't retried.
+    pub fn poll(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Result<bool, LoadTextureError> {
+        let modified = modified(&self.path);
+        // a missing file is usually an editor halfway through saving
+        if modified.is_none() || modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+        self.texture = rl.load_texture(thread, &self.path.to_string_lossy())?;
+        Ok(true)
+    }
+}
+
+impl std::ops::Deref for HotTexture {
+    type Target = Texture2D;
+
+    fn deref(&self) -> &Texture2D {
+        &self.texture
+    }
+}
+
+impl AsRef<ffi::Texture2D> for HotTexture {
+    fn as_ref(&self) -> &ffi::Texture2D {
+        &self.texture.0
+    }
+}
+
+/// A shader that recompiles when its vertex or fragment file changes.
+///
+/// Uniform locations change on recompile. Look them up through [HotShader::location] (cached, refreshed after
+/// every reload) rather than [RaylibShader::get_shader_location], and set custom entries of
+/// [RaylibShader::locs_mut] through [HotShader::bind_location] so they are restored.
+#[derive(Debug)]
+pub struct HotShader {
+    shader: Shader,
+    vs: Option<PathBuf>,
+    fs: Option<PathBuf>,
+    modified: [Option<SystemTime>; 2],
+    locations: HashMap<String, i32>,
+    bound: Vec<(ShaderLocationIndex, String)>,
+}
+
+impl HotShader {
+    /// Loads and compiles a shader and starts watching its files. `None` uses raylib's default for that stage.
+    pub fn load(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        vs_filename: Option<&str>,
+        fs_filename: Option<&str>,
+    ) -> Result<HotShader, LoadShaderError> {
+        let vs = vs_filename.map(PathBuf::from);
+        let fs = fs_filename.map(PathBuf::from);
+        let modified = [
+            vs.as_deref().and_then(modified),
+            fs.as_deref().and_then(modified),
+        ];
+        let shader = load_shader_checked(rl, thread, vs.as_deref(), fs.as_deref())?;
+        Ok(HotShader {
+            shader,
+            vs,
+            fs,
+            modified,
+            locations: HashMap::new(),
+            bound: Vec::new(),
+        })
+    }
+
+    /// Location of the uniform `name` in the current program, -1 if it doesn't exist.
+    pub fn location(&mut self, name: &str) -> i32 {
+        if let Some(loc) = self.locations.get(name) {
+            return *loc;
+        }
+        let loc = self.shader.get_shader_location(name);
+        self.locations.insert(name.to_owned(), loc);
+        loc
+    }
+
+    /// Points `locs[index]` at the uniform `name`, now and after every reload.
+    pub fn bind_location(&mut self, index: ShaderLocationIndex, name: &str) {
+        let loc = self.location(name);
+        self.shader.locs_mut()[index as usize] = loc;
+        self.bound.retain(|(i, _)| *i != index);
+        self.bound.push((index, name.to_owned()));
+    }
+
+    /// Recompiles the shader if one of its files changed since the last load. Returns whether it was reloaded.
+    /// If compilation fails the previous program is kept and the same change isn't retried.
+    pub fn poll(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+    ) -> Result<bool, LoadShaderError> {
+        let modified = [
+            self.vs.as_deref().and_then(modified),
+            self.fs.as_deref().and_then(modified),
+        ];
+        let missing = (self.vs.is_some() && modified[0].is_none())
+            || (self.fs.is_some() && modified[1].is_none());
+        if missing || modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+        self.shader = load_shader_checked(rl, thread, self.vs.as_deref(), self.fs.as_deref())?;
+
+        for (name, loc) in self.locations.iter_mut() {
+            *loc = self.shader.get_shader_location(name);
+        }
+        for (index, name) in &self.bound {
+            self.shader.locs_mut()[*index as usize] = self.locations[name];
+        }
+        Ok(true)
+    }
+}
+
+impl std::ops::Deref for HotShader {
+    type Target = Shader;
+
+    fn deref(&self) -> &Shader {
+        &self.shader
+    }
+}
+
+impl std::ops::DerefMut for HotShader {
+    fn deref_mut(&mut self) -> &mut Shader {
+        &mut self.shader
+    }
+}
+
+impl AsRef<ffi::Shader> for HotShader {
+    fn as_ref(&self) -> &ffi::Shader {
+        &self.shader.0
+    }
+}
+
+impl AsMut<ffi::Shader> for HotShader {
+    fn as_mut(&mut self) -> &mut ffi::Shader {
+        &mut self.shader.0
+    }
+}
+
+impl RaylibShader for HotShader {}
+
+fn load_shader_checked(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    vs: Option<&Path>,
+    fs: Option<&Path>,
+) -> Result<Shader, LoadShaderError> {
+    let vs = vs.map(|p| p.to_string_lossy().into_owned());
+    let fs = fs.map(|p| p.to_string_lossy().into_owned());
+    rl.load_shader(thread, vs.as_deref(), fs.as_deref())
+}
@@ -167,3 +167,26 @@ macro_rules! impl_rslice {
         }
     };
 }
+
+/// `AsRef` and `AsMut` for a font wrapping an `AtlasFont` in `$field`
+macro_rules! impl_atlas_font {
+    ($name:ident, $field:ident) => {
+        impl std::convert::AsRef<crate::ffi::Font> for $name {
+            fn as_ref(&self) -> &crate::ffi::Font {
+                &self.$field.font
+            }
+        }
+
+        impl std::convert::AsMut<crate::ffi::Font> for $name {
+            fn as_mut(&mut self) -> &mut crate::ffi::Font {
+                &mut self.$field.font
+            }
+        }
+
+        impl std::convert::AsRef<crate::ffi::Texture2D> for $name {
+            fn as_ref(&self) -> &crate::ffi::Texture2D {
+                &self.$field.font.texture
+            }
+        }
+    };
+}
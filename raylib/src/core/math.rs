@@ -490,6 +490,39 @@ impl From<&Matrix> for ffi::Matrix {
     }
 }
 
+impl From<glam::Mat4> for Matrix {
+    fn from(m: glam::Mat4) -> Matrix {
+        let c = m.to_cols_array();
+        Matrix {
+            m0: c[0],
+            m4: c[4],
+            m8: c[8],
+            m12: c[12],
+            m1: c[1],
+            m5: c[5],
+            m9: c[9],
+            m13: c[13],
+            m2: c[2],
+            m6: c[6],
+            m10: c[10],
+            m14: c[14],
+            m3: c[3],
+            m7: c[7],
+            m11: c[11],
+            m15: c[15],
+        }
+    }
+}
+
+impl From<Matrix> for glam::Mat4 {
+    fn from(m: Matrix) -> glam::Mat4 {
+        glam::Mat4::from_cols_array(&[
+            m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13,
+            m.m14, m.m15,
+        ])
+    }
+}
+
 impl Matrix {
     /// Returns the identity matrix.
     #[inline]
@@ -1197,6 +1230,17 @@ optional_serde_struct! {
     }
 }
 
+impl Transform {
+    /// Returns a matrix that scales, rotates and then translates.
+    #[inline]
+    #[must_use]
+    pub fn to_matrix(&self) -> Matrix {
+        let r: ffi::Quaternion = self.rotation.into();
+        let rotation = glam::Quat::from_xyzw(r.x, r.y, r.z, r.w).normalize();
+        glam::Mat4::from_scale_rotation_translation(self.scale, rotation, self.translation).into()
+    }
+}
+
 impl From<ffi::Transform> for Transform {
     fn from(r: ffi::Transform) -> Transform {
         unsafe { std::mem::transmute(r) }
@@ -0,0 +1,232 @@
+//! Building meshes from Rust data.
+//!
+//! [`MeshBuilder`] collects vertex attributes in `Vec`s, checks them and copies them into raylib-allocated memory,
+//! so the resulting [`Mesh`] owns and frees them like any mesh raylib generated itself.
+//! ```ignore
+//! let mut builder = MeshBuilder::new();
+//! let a = builder.vertex(rvec3(0, 0, 0));
+//! let b = builder.vertex(rvec3(1, 0, 0));
+//! let c = builder.vertex(rvec3(0, 1, 0));
+//! builder.triangle(a, b, c);
+//! let mesh = builder.build(&thread)?;
+//! ```
+use crate::core::RaylibThread;
+use crate::core::data::DataBuf;
+use crate::core::math::{Vector2, Vector3, Vector4};
+use crate::core::models::{DynamicMesh, Mesh};
+use crate::error::MeshBuilderError;
+use crate::ffi::{self, Color};
+
+/// Vertex attributes and indices for a new [`Mesh`].
+///
+/// Only `vertices` is required. Every other attribute is either empty or has one element per vertex. Without
+/// indices every three vertices form a triangle.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshBuilder {
+    pub vertices: Vec<Vector3>,
+    pub normals: Vec<Vector3>,
+    pub texcoords: Vec<Vector2>,
+    pub texcoords2: Vec<Vector2>,
+    pub colors: Vec<Color>,
+    pub tangents: Vec<Vector4>,
+    /// Triangle list. raylib meshes use 16 bit indices, so indexed meshes can have at most 65536 vertices.
+    pub indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves room for `vertices` vertex positions and `indices` indices.
+    pub fn with_capacity(vertices: usize, indices: usize) -> Self {
+        Self {
+            vertices: Vec::with_capacity(vertices),
+            indices: Vec::with_capacity(indices),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a vertex position and returns its index.
+    pub fn vertex(&mut self, position: impl Into<Vector3>) -> u32 {
+        self.vertices.push(position.into());
+        self.vertices.len() as u32 - 1
+    }
+
+    /// Adds a triangle, counter-clockwise when seen from the front.
+    pub fn triangle(&mut self, a: u32, b: u32, c: u32) -> &mut Self {
+        self.indices.extend([a, b, c]);
+        self
+    }
+
+    /// Adds a quad as two triangles, counter-clockwise when seen from the front.
+    pub fn quad(&mut self, a: u32, b: u32, c: u32, d: u32) -> &mut Self {
+        self.indices.extend([a, b, c, a, c, d]);
+        self
+    }
+
+    #[inline]
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    #[inline]
+    pub fn triangle_count(&self) -> usize {
+        if self.indices.is_empty() {
+            self.vertices.len() / 3
+        } else {
+            self.indices.len() / 3
+        }
+    }
+
+    /// Checks attribute counts and indices without building anything.
+    pub fn validate(&self) -> Result<(), MeshBuilderError> {
+        let vertices = self.vertices.len();
+        if vertices == 0 {
+            return Err(MeshBuilderError::Empty);
+        }
+        let attributes = [
+            ("normals", self.normals.len()),
+            ("texcoords", self.texcoords.len()),
+            ("texcoords2", self.texcoords2.len()),
+            ("colors", self.colors.len()),
+            ("tangents", self.tangents.len()),
+        ];
+        for (attribute, count) in attributes {
+            if count != 0 && count != vertices {
+                return Err(MeshBuilderError::AttributeCount {
+                    attribute,
+                    count,
+                    vertices,
+                });
+            }
+        }
+
+        if self.indices.is_empty() {
+            if !vertices.is_multiple_of(3) {
+                return Err(MeshBuilderError::NotTriangles { count: vertices });
+            }
+            if vertices > i32::MAX as usize {
+                return Err(MeshBuilderError::TooManyVertices { vertices });
+            }
+            return Ok(());
+        }
+        if vertices > u16::MAX as usize + 1 {
+            return Err(MeshBuilderError::TooManyVertices { vertices });
+        }
+        if !self.indices.len().is_multiple_of(3) {
+            return Err(MeshBuilderError::NotTriangles {
+                count: self.indices.len(),
+            });
+        }
+        if let Some(&index) = self.indices.iter().find(|i| **i as usize >= vertices) {
+            return Err(MeshBuilderError::IndexOutOfRange { index, vertices });
+        }
+        Ok(())
+    }
+
+    /// Builds the mesh and uploads it to the GPU.
+    pub fn build(&self, _: &RaylibThread) -> Result<Mesh, MeshBuilderError> {
+        let mut mesh = self.build_cpu()?;
+        unsafe { ffi::UploadMesh(&mut mesh.0, false) };
+        Ok(mesh)
+    }
+
+    /// Builds the mesh and uploads it with dynamic buffers, for updating it every frame.
+    pub fn build_dynamic(&self, thread: &RaylibThread) -> Result<DynamicMesh, MeshBuilderError> {
+        // a freshly built mesh was never uploaded, which is the only way creating a DynamicMesh fails
+        Ok(DynamicMesh::new(thread, self.build_cpu()?).unwrap())
+    }
+
+    /// Builds the mesh without uploading it, for editing it further or uploading it yourself with
+    /// [`RaylibMesh::upload`](crate::core::models::RaylibMesh::upload).
+    pub fn build_cpu(&self) -> Result<Mesh, MeshBuilderError> {
+        self.validate()?;
+        let tangents: Vec<[f32; 4]> = self.tangents.iter().map(|t| t.to_array()).collect();
+        let indices: Vec<u16> = self.indices.iter().map(|i| *i as u16).collect();
+
+        // any buffer already set is freed by UnloadMesh if a later allocation fails
+        let mut mesh = Mesh(unsafe { std::mem::zeroed() });
+        mesh.0.vertexCount = self.vertices.len() as i32;
+        mesh.0.triangleCount = self.triangle_count() as i32;
+        mesh.0.vertices = DataBuf::leak_slice(&self.vertices)?;
+        mesh.0.normals = DataBuf::leak_slice(&self.normals)?;
+        mesh.0.texcoords = DataBuf::leak_slice(&self.texcoords)?;
+        mesh.0.texcoords2 = DataBuf::leak_slice(&self.texcoords2)?;
+        mesh.0.colors = DataBuf::leak_slice(&self.colors)?;
+        mesh.0.tangents = DataBuf::leak_slice(&tangents)?;
+        mesh.0.indices = DataBuf::leak_slice(&indices)?;
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+mod mesh_builder_test {
+    use super::*;
+
+    fn triangle() -> MeshBuilder {
+        let mut builder = MeshBuilder::new();
+        let a = builder.vertex(Vector3::new(0.0, 0.0, 0.0));
+        let b = builder.vertex(Vector3::new(1.0, 0.0, 0.0));
+        let c = builder.vertex(Vector3::new(0.0, 1.0, 0.0));
+        builder.triangle(a, b, c);
+        builder
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut builder = triangle();
+        assert!(builder.validate().is_ok());
+        assert_eq!(builder.triangle_count(), 1);
+
+        builder.normals.push(Vector3::Z);
+        assert!(matches!(
+            builder.validate(),
+            Err(MeshBuilderError::AttributeCount {
+                attribute: "normals",
+                count: 1,
+                vertices: 3
+            })
+        ));
+        builder.normals.clear();
+
+        builder.triangle(0, 1, 3);
+        assert!(matches!(
+            builder.validate(),
+            Err(MeshBuilderError::IndexOutOfRange {
+                index: 3,
+                vertices: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_counts() {
+        assert!(matches!(
+            MeshBuilder::new().validate(),
+            Err(MeshBuilderError::Empty)
+        ));
+
+        let mut builder = triangle();
+        builder.indices.pop();
+        assert!(matches!(
+            builder.validate(),
+            Err(MeshBuilderError::NotTriangles { count: 2 })
+        ));
+
+        builder.indices.clear();
+        builder.vertex(Vector3::ONE);
+        assert!(matches!(
+            builder.validate(),
+            Err(MeshBuilderError::NotTriangles { count: 4 })
+        ));
+
+        let mut big = MeshBuilder::new();
+        big.vertices.resize(70_000, Vector3::ZERO);
+        big.quad(0, 1, 2, 3);
+        assert!(matches!(
+            big.validate(),
+            Err(MeshBuilderError::TooManyVertices { vertices: 70_000 })
+        ));
+    }
+}
@@ -0,0 +1,756 @@
+//! CPU-side mesh repair and optimization.
+//!
+//! Everything here works on plain slices, e.g. [`RaylibMesh::vertices`] and [`RaylibMesh::indicies`] of a loaded
+//! mesh or the fields of a [`MeshBuilder`], and returns new data to build a mesh from. Indices can be `u16` or
+//! `u32` and an empty index slice means every three vertices form a triangle, like a raylib mesh without an
+//! index buffer. Indices are checked against the vertex count up front, and out of range ones return
+//! [`MeshProcessingError::IndexOutOfRange`].
+//! ```ignore
+//! let welded = weld_vertices(mesh.vertices(), mesh.indicies(), 1e-4)?;
+//! let mut builder = MeshBuilder::new();
+//! builder.normals = smooth_normals(&welded.vertices, &welded.indices)?;
+//! builder.vertices = welded.vertices;
+//! builder.indices = simplify(&builder.vertices, &welded.indices, welded.indices.len() / 2)?;
+//! optimize_vertex_cache(&mut builder.indices, builder.vertices.len())?;
+//! ```
+//!
+//! [`RaylibMesh::vertices`]: crate::core::models::RaylibMesh::vertices
+//! [`RaylibMesh::indicies`]: crate::core::models::RaylibMesh::indicies
+//! [`MeshBuilder`]: crate::core::mesh_builder::MeshBuilder
+use std::collections::HashMap;
+
+use crate::core::math::{BoundingBox, Vector3};
+use crate::error::MeshProcessingError;
+
+fn check_indices<I: Copy + Into<u32>>(
+    indices: &[I],
+    vertex_count: usize,
+) -> Result<(), MeshProcessingError> {
+    match indices
+        .iter()
+        .map(|i| (*i).into())
+        .find(|i| *i as usize >= vertex_count)
+    {
+        Some(index) => Err(MeshProcessingError::IndexOutOfRange {
+            index,
+            vertices: vertex_count,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn sequential_triangles(vertex_count: usize) -> Vec<[u32; 3]> {
+    (0..(vertex_count / 3) as u32)
+        .map(|t| [t * 3, t * 3 + 1, t * 3 + 2])
+        .collect()
+}
+
+/// The triangles of `indices`, checked against `vertex_count` so they can be used to index vertex data.
+fn triangle_list<I: Copy + Into<u32>>(
+    indices: &[I],
+    vertex_count: usize,
+) -> Result<Vec<[u32; 3]>, MeshProcessingError> {
+    if indices.is_empty() {
+        return Ok(sequential_triangles(vertex_count));
+    }
+    check_indices(indices, vertex_count)?;
+    Ok(indices
+        .chunks_exact(3)
+        .map(|t| [t[0].into(), t[1].into(), t[2].into()])
+        .collect())
+}
+
+fn face_normal(vertices: &[Vector3], [a, b, c]: [u32; 3]) -> Vector3 {
+    let a = vertices[a as usize];
+    (vertices[b as usize] - a).cross(vertices[c as usize] - a)
+}
+
+/// Per-vertex normals averaged over the triangles around each vertex, weighted by triangle area.
+/// Vertices without a triangle get a zero normal.
+pub fn smooth_normals<I: Copy + Into<u32>>(
+    vertices: &[Vector3],
+    indices: &[I],
+) -> Result<Vec<Vector3>, MeshProcessingError> {
+    let mut normals = vec![Vector3::ZERO; vertices.len()];
+    for triangle in triangle_list(indices, vertices.len())? {
+        let normal = face_normal(vertices, triangle);
+        for v in triangle {
+            normals[v as usize] += normal;
+        }
+    }
+    for n in &mut normals {
+        *n = n.normalize_or_zero();
+    }
+    Ok(normals)
+}
+
+/// Per-vertex face normals. A vertex shared by several triangles gets the normal of the last one, so unweld
+/// indexed meshes first (see [`unweld`]) for faceted shading.
+pub fn flat_normals<I: Copy + Into<u32>>(
+    vertices: &[Vector3],
+    indices: &[I],
+) -> Result<Vec<Vector3>, MeshProcessingError> {
+    let mut normals = vec![Vector3::ZERO; vertices.len()];
+    for triangle in triangle_list(indices, vertices.len())? {
+        let normal = face_normal(vertices, triangle).normalize_or_zero();
+        for v in triangle {
+            normals[v as usize] = normal;
+        }
+    }
+    Ok(normals)
+}
+
+/// Expands an indexed attribute to one value per triangle corner, giving a mesh without indices.
+pub fn unweld<T: Copy, I: Copy + Into<u32>>(
+    attribute: &[T],
+    indices: &[I],
+) -> Result<Vec<T>, MeshProcessingError> {
+    check_indices(indices, attribute.len())?;
+    Ok(indices
+        .iter()
+        .map(|i| attribute[(*i).into() as usize])
+        .collect())
+}
+
+/// Result of [`weld_vertices`] and [`generate_indices`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeldedMesh {
+    pub vertices: Vec<Vector3>,
+    pub indices: Vec<u32>,
+    /// New index of every original vertex, for carrying other attributes over with [`remap_attribute`]
+    pub remap: Vec<u32>,
+}
+
+/// Merges vertices less than `epsilon` apart and rewrites the indices to match. Triangles that collapse because
+/// two of their corners merged are dropped.
+///
+/// Only positions are compared, so welding joins UV and normal seams. Vertices merge into the first vertex of
+/// their group.
+pub fn weld_vertices<I: Copy + Into<u32>>(
+    vertices: &[Vector3],
+    indices: &[I],
+    epsilon: f32,
+) -> Result<WeldedMesh, MeshProcessingError> {
+    let triangles = triangle_list(indices, vertices.len())?;
+    Ok(weld(vertices, triangles, epsilon))
+}
+
+fn weld(vertices: &[Vector3], triangles: Vec<[u32; 3]>, epsilon: f32) -> WeldedMesh {
+    let mut welded = WeldedMesh {
+        remap: Vec::with_capacity(vertices.len()),
+        ..Default::default()
+    };
+    if epsilon > 0.0 {
+        let cell = |p: Vector3| (p / epsilon).floor().as_i64vec3().to_array();
+        let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+        for &p in vertices {
+            let [x, y, z] = cell(p);
+            let neighbours = (x - 1..=x + 1).flat_map(|x| {
+                (y - 1..=y + 1).flat_map(move |y| (z - 1..=z + 1).map(move |z| [x, y, z]))
+            });
+            let found = neighbours
+                .filter_map(|key| grid.get(&key))
+                .flatten()
+                .find(|i| welded.vertices[**i as usize].distance_squared(p) <= epsilon * epsilon)
+                .copied();
+            let index = found.unwrap_or_else(|| {
+                let index = welded.vertices.len() as u32;
+                welded.vertices.push(p);
+                grid.entry(cell(p)).or_default().push(index);
+                index
+            });
+            welded.remap.push(index);
+        }
+    } else {
+        let mut seen: HashMap<[u32; 3], u32> = HashMap::new();
+        for &p in vertices {
+            let index = *seen
+                .entry(p.to_array().map(f32::to_bits))
+                .or_insert_with(|| {
+                    welded.vertices.push(p);
+                    welded.vertices.len() as u32 - 1
+                });
+            welded.remap.push(index);
+        }
+    }
+
+    for triangle in triangles {
+        let [a, b, c] = triangle.map(|v| welded.remap[v as usize]);
+        if a != b && b != c && a != c {
+            welded.indices.extend([a, b, c]);
+        }
+    }
+    welded
+}
+
+/// Turns a mesh without indices into an indexed one by merging vertices at exactly the same position.
+pub fn generate_indices(vertices: &[Vector3]) -> WeldedMesh {
+    weld(vertices, sequential_triangles(vertices.len()), 0.0)
+}
+
+/// Carries a per-vertex attribute over to welded vertices. Each new vertex takes the value of the first
+/// original vertex that maps to it.
+pub fn remap_attribute<T: Copy>(
+    attribute: &[T],
+    remap: &[u32],
+    vertex_count: usize,
+) -> Result<Vec<T>, MeshProcessingError> {
+    check_indices(remap, vertex_count)?;
+    let mut out: Vec<Option<T>> = vec![None; vertex_count];
+    for (value, &to) in attribute.iter().zip(remap) {
+        out[to as usize].get_or_insert(*value);
+    }
+    out.into_iter()
+        .enumerate()
+        .map(|(vertex, v)| v.ok_or(MeshProcessingError::Unmapped { vertex }))
+        .collect()
+}
+
+/// Axis-aligned bounds of `vertices`, zero sized at the origin if there are none.
+pub fn bounding_box(vertices: &[Vector3]) -> BoundingBox {
+    if vertices.is_empty() {
+        return BoundingBox::new(Vector3::ZERO, Vector3::ZERO);
+    }
+    let (min, max) = vertices.iter().fold(
+        (Vector3::splat(f32::MAX), Vector3::splat(f32::MIN)),
+        |(min, max), v| (min.min(*v), max.max(*v)),
+    );
+    BoundingBox::new(min, max)
+}
+
+/// A sphere containing every vertex, returned as center and radius. It is at most a few percent larger than the
+/// smallest one (Ritter's algorithm).
+pub fn bounding_sphere(vertices: &[Vector3]) -> (Vector3, f32) {
+    let Some(&first) = vertices.first() else {
+        return (Vector3::ZERO, 0.0);
+    };
+    let farthest = |from: Vector3| {
+        vertices
+            .iter()
+            .copied()
+            .max_by(|a, b| {
+                a.distance_squared(from)
+                    .total_cmp(&b.distance_squared(from))
+            })
+            .unwrap()
+    };
+    let a = farthest(first);
+    let b = farthest(a);
+    let mut center = (a + b) * 0.5;
+    let mut radius = a.distance(b) * 0.5;
+    for &v in vertices {
+        let distance = v.distance(center);
+        if distance > radius {
+            let grown = (radius + distance) * 0.5;
+            center += (v - center) * ((grown - radius) / distance);
+            radius = grown;
+        }
+    }
+    (center, radius)
+}
+
+const CACHE_SIZE: usize = 32;
+
+/// Score of a vertex for [`optimize_vertex_cache`], from Tom Forsyth's "Linear-Speed Vertex Cache Optimisation"
+fn vertex_score(cache_position: Option<usize>, live_triangles: usize) -> f32 {
+    if live_triangles == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1.0 - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+        None => 0.0,
+    };
+    cache_score + 2.0 * (live_triangles as f32).powf(-0.5)
+}
+
+/// Reorders triangles so vertices are reused while they are still in the GPU's post-transform cache. The
+/// triangles and their winding are unchanged.
+pub fn optimize_vertex_cache<I: Copy + Into<u32>>(
+    indices: &mut [I],
+    vertex_count: usize,
+) -> Result<(), MeshProcessingError> {
+    check_indices(indices, vertex_count)?;
+    let triangle_count = indices.len() / 3;
+    let mut vertex_triangles: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for (t, triangle) in indices.chunks_exact(3).enumerate() {
+        for v in triangle {
+            vertex_triangles[(*v).into() as usize].push(t);
+        }
+    }
+    let mut cache_position: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut vertex_scores: Vec<f32> = vertex_triangles
+        .iter()
+        .map(|t| vertex_score(None, t.len()))
+        .collect();
+    let triangle_score = |t: usize, scores: &[f32]| -> f32 {
+        indices[t * 3..t * 3 + 3]
+            .iter()
+            .map(|v| scores[(*v).into() as usize])
+            .sum()
+    };
+    let mut triangle_scores: Vec<f32> = (0..triangle_count)
+        .map(|t| triangle_score(t, &vertex_scores))
+        .collect();
+    let mut emitted = vec![false; triangle_count];
+
+    let mut order = Vec::with_capacity(triangle_count);
+    let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut best: Option<usize> = None;
+    while order.len() < triangle_count {
+        // nothing in the cache has triangles left, start over at the best remaining triangle
+        let t = best.unwrap_or_else(|| {
+            (0..triangle_count)
+                .filter(|t| !emitted[*t])
+                .max_by(|a, b| triangle_scores[*a].total_cmp(&triangle_scores[*b]))
+                .unwrap()
+        });
+        emitted[t] = true;
+        order.push(t);
+
+        let corners: Vec<usize> = indices[t * 3..t * 3 + 3]
+            .iter()
+            .map(|v| (*v).into() as usize)
+            .collect();
+        for &v in &corners {
+            let triangles = &mut vertex_triangles[v];
+            if let Some(i) = triangles.iter().position(|x| *x == t) {
+                triangles.swap_remove(i);
+            }
+        }
+        // degenerate triangles repeat a corner, so skip anything already listed
+        let mut touched: Vec<usize> = Vec::with_capacity(cache.len() + 3);
+        for &v in corners.iter().chain(&cache) {
+            if !touched.contains(&v) {
+                touched.push(v);
+            }
+        }
+        cache.clear();
+        for (position, &v) in touched.iter().enumerate() {
+            if position < CACHE_SIZE {
+                cache.push(v);
+                cache_position[v] = Some(position);
+            } else {
+                cache_position[v] = None;
+            }
+            vertex_scores[v] = vertex_score(cache_position[v], vertex_triangles[v].len());
+        }
+
+        best = None;
+        let mut best_score = f32::MIN;
+        for &v in &touched {
+            for &t in &vertex_triangles[v] {
+                triangle_scores[t] = triangle_score(t, &vertex_scores);
+                if triangle_scores[t] > best_score {
+                    best_score = triangle_scores[t];
+                    best = Some(t);
+                }
+            }
+        }
+    }
+
+    let reordered: Vec<I> = order
+        .iter()
+        .flat_map(|t| indices[t * 3..t * 3 + 3].to_vec())
+        .collect();
+    indices[..reordered.len()].copy_from_slice(&reordered);
+    Ok(())
+}
+
+/// Average number of vertices transformed per triangle with a FIFO post-transform cache of `cache_size`
+/// entries (the ACMR). 3 is the worst case, 0.5 about the best a regular grid can do.
+pub fn cache_miss_ratio<I: Copy + Into<u32>>(indices: &[I], cache_size: usize) -> f32 {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return 0.0;
+    }
+    let mut cache = std::collections::VecDeque::with_capacity(cache_size);
+    let mut misses = 0;
+    for &v in &indices[..triangle_count * 3] {
+        let v: u32 = v.into();
+        if !cache.contains(&v) {
+            misses += 1;
+            if cache.len() == cache_size {
+                cache.pop_front();
+            }
+            cache.push_back(v);
+        }
+    }
+    misses as f32 / triangle_count as f32
+}
+
+/// Symmetric 4x4 error quadric of the squared distance to a set of planes
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: Vector3, point: Vector3, weight: f64) -> Quadric {
+        let [a, b, c] = normal.to_array().map(f64::from);
+        let d = -(a * point.x as f64 + b * point.y as f64 + c * point.z as f64);
+        Quadric(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|q| q * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    fn error(&self, p: Vector3) -> f64 {
+        let [x, y, z] = p.to_array().map(f64::from);
+        let q = &self.0;
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// Vertices that can't move without tearing the mesh: those on open borders and those sharing their position
+/// with another vertex (UV or normal seams).
+fn locked_vertices(vertices: &[Vector3], triangles: &[[u32; 3]]) -> Vec<bool> {
+    let mut locked = vec![false; vertices.len()];
+    let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+    for &[a, b, c] in triangles {
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            *edges.entry((u.min(v), u.max(v))).or_default() += 1;
+        }
+    }
+    for ((u, v), count) in edges {
+        if count == 1 {
+            locked[u as usize] = true;
+            locked[v as usize] = true;
+        }
+    }
+
+    let mut positions: HashMap<[u32; 3], u32> = HashMap::new();
+    for (i, p) in vertices.iter().enumerate() {
+        if let Some(&other) = positions.get(&p.to_array().map(f32::to_bits)) {
+            locked[i] = true;
+            locked[other as usize] = true;
+        } else {
+            positions.insert(p.to_array().map(f32::to_bits), i as u32);
+        }
+    }
+    locked
+}
+
+/// Reduces a mesh to about `target_index_count` indices by collapsing edges in order of least quadric error
+/// (Garland and Heckbert). Vertices only ever move onto other existing vertices, so the result indexes the
+/// original vertex buffer and LOD levels can share it.
+///
+/// Open borders and seams are kept, so the target may not be reached.
+pub fn simplify<I: Copy + Into<u32>>(
+    vertices: &[Vector3],
+    indices: &[I],
+    target_index_count: usize,
+) -> Result<Vec<u32>, MeshProcessingError> {
+    let triangles = triangle_list(indices, vertices.len())?;
+    Ok(simplify_triangles(vertices, &triangles, target_index_count)
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+fn simplify_triangles(
+    vertices: &[Vector3],
+    triangles: &[[u32; 3]],
+    target_index_count: usize,
+) -> Vec<[u32; 3]> {
+    let mut triangles: Vec<[u32; 3]> = triangles
+        .iter()
+        .copied()
+        .filter(|[a, b, c]| a != b && b != c && a != c)
+        .collect();
+    let target = target_index_count / 3;
+
+    let mut quadrics = vec![Quadric::default(); vertices.len()];
+    for &triangle in &triangles {
+        let normal = face_normal(vertices, triangle);
+        let area = normal.length() as f64 * 0.5;
+        let quadric = Quadric::from_plane(
+            normal.normalize_or_zero(),
+            vertices[triangle[0] as usize],
+            area,
+        );
+        for v in triangle {
+            quadrics[v as usize].add(&quadric);
+        }
+    }
+    let locked = locked_vertices(vertices, &triangles);
+
+    while triangles.len() > target {
+        let mut around: Vec<Vec<usize>> = vec![Vec::new(); vertices.len()];
+        let mut collapses = Vec::with_capacity(triangles.len() * 3);
+        for (t, &[a, b, c]) in triangles.iter().enumerate() {
+            for (from, to) in [(a, b), (b, c), (c, a), (b, a), (c, b), (a, c)] {
+                if !locked[from as usize] {
+                    let mut quadric = quadrics[from as usize];
+                    quadric.add(&quadrics[to as usize]);
+                    collapses.push((quadric.error(vertices[to as usize]), from, to));
+                }
+            }
+            for v in [a, b, c] {
+                around[v as usize].push(t);
+            }
+        }
+        collapses.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut remap: Vec<u32> = (0..vertices.len() as u32).collect();
+        let mut touched = vec![false; vertices.len()];
+        let mut remaining = triangles.len();
+        for (_, from, to) in collapses {
+            if remaining <= target {
+                break;
+            }
+            if touched[from as usize] || touched[to as usize] {
+                continue;
+            }
+            // moving `from` onto `to` must not turn any triangle around
+            let flips = around[from as usize].iter().any(|&t| {
+                let triangle = triangles[t];
+                if triangle.contains(&to) {
+                    return false;
+                }
+                let before = face_normal(vertices, triangle);
+                let after = face_normal(vertices, triangle.map(|v| if v == from { to } else { v }));
+                before.dot(after) <= 0.0
+            });
+            if flips {
+                continue;
+            }
+
+            remap[from as usize] = to;
+            let quadric = quadrics[from as usize];
+            quadrics[to as usize].add(&quadric);
+            for &t in &around[from as usize] {
+                if triangles[t].contains(&to) {
+                    remaining -= 1;
+                }
+                for v in triangles[t] {
+                    touched[v as usize] = true;
+                }
+            }
+        }
+        if remaining == triangles.len() {
+            break;
+        }
+        triangles = triangles
+            .into_iter()
+            .map(|t| t.map(|v| remap[v as usize]))
+            .filter(|[a, b, c]| a != b && b != c && a != c)
+            .collect();
+    }
+    triangles
+}
+
+/// Simplifies a mesh once per entry of `ratios`, each a fraction of the original index count, e.g.
+/// `&[0.5, 0.25, 0.1]`. Every level indexes the original vertex buffer.
+pub fn generate_lods<I: Copy + Into<u32>>(
+    vertices: &[Vector3],
+    indices: &[I],
+    ratios: &[f32],
+) -> Result<Vec<Vec<u32>>, MeshProcessingError> {
+    let mut previous = triangle_list(indices, vertices.len())?;
+    let full = previous.len() * 3;
+    Ok(ratios
+        .iter()
+        .map(|ratio| {
+            let target = (full as f32 * ratio.clamp(0.0, 1.0)) as usize;
+            // each level starts from the previous one, which is already closer to it
+            if target < previous.len() * 3 {
+                previous = simplify_triangles(vertices, &previous, target);
+            }
+            previous.iter().flatten().copied().collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod mesh_processing_test {
+    use super::*;
+
+    /// Flat grid of `n` by `n` quads in the XZ plane, facing up
+    fn grid(n: u32) -> (Vec<Vector3>, Vec<u32>) {
+        let vertices = (0..=n)
+            .flat_map(|z| (0..=n).map(move |x| Vector3::new(x as f32, 0.0, z as f32)))
+            .collect();
+        let indices = (0..n)
+            .flat_map(|z| {
+                (0..n).flat_map(move |x| {
+                    let i = z * (n + 1) + x;
+                    [i, i + n + 1, i + 1, i + 1, i + n + 1, i + n + 2]
+                })
+            })
+            .collect();
+        (vertices, indices)
+    }
+
+    #[test]
+    fn test_normals() {
+        let (vertices, indices) = grid(2);
+        for n in smooth_normals(&vertices, &indices).unwrap() {
+            assert!(n.abs_diff_eq(Vector3::Y, 1e-6), "{n}");
+        }
+
+        let vertices = [
+            Vector3::ZERO,
+            Vector3::X,
+            Vector3::Y,
+            Vector3::ZERO,
+            Vector3::Z,
+            Vector3::X,
+        ];
+        let normals = flat_normals::<u16>(&vertices, &[]).unwrap();
+        assert_eq!(normals[..3], [Vector3::Z; 3]);
+        assert_eq!(normals[3..], [Vector3::Y; 3]);
+    }
+
+    #[test]
+    fn test_weld_and_generate_indices() {
+        let (vertices, indices) = grid(2);
+        let unwelded = unweld(&vertices, &indices).unwrap();
+        assert_eq!(unwelded.len(), 24);
+
+        let exact = generate_indices(&unwelded);
+        assert_eq!(exact.vertices.len(), vertices.len());
+        assert_eq!(unweld(&exact.vertices, &exact.indices).unwrap(), unwelded);
+
+        let jittered: Vec<Vector3> = unwelded
+            .iter()
+            .enumerate()
+            .map(|(i, v)| *v + Vector3::splat(i as f32 * 1e-5))
+            .collect();
+        let welded = weld_vertices::<u16>(&jittered, &[], 1e-3).unwrap();
+        assert_eq!(welded.vertices.len(), 9);
+        assert_eq!(welded.indices.len(), 24);
+        assert_eq!(
+            remap_attribute(&jittered, &welded.remap, 9).unwrap(),
+            welded.vertices
+        );
+
+        // welding everything collapses every triangle
+        assert!(
+            weld_vertices(&vertices, &indices, 10.0)
+                .unwrap()
+                .indices
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let (vertices, _) = grid(4);
+        let bounds = bounding_box(&vertices);
+        assert_eq!(bounds.min, Vector3::ZERO);
+        assert_eq!(bounds.max, Vector3::new(4.0, 0.0, 4.0));
+
+        let (center, radius) = bounding_sphere(&vertices);
+        assert!(vertices.iter().all(|v| v.distance(center) <= radius + 1e-4));
+        assert!(radius < 32f32.sqrt() * 1.05);
+    }
+
+    #[test]
+    fn test_vertex_cache() {
+        let (vertices, indices) = grid(16);
+        // scramble the triangle order
+        let mut triangles: Vec<[u32; 3]> = indices.chunks(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let mut seed = 1u32;
+        for i in (1..triangles.len()).rev() {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            triangles.swap(i, seed as usize % (i + 1));
+        }
+        let mut scrambled: Vec<u32> = triangles.concat();
+        let before = cache_miss_ratio(&scrambled, 16);
+
+        optimize_vertex_cache(&mut scrambled, vertices.len()).unwrap();
+        let after = cache_miss_ratio(&scrambled, 16);
+        assert!(after < 0.8 && after < before * 0.5, "{before} -> {after}");
+
+        let mut sorted: Vec<[u32; 3]> = scrambled.chunks(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let mut original = triangles;
+        sorted.sort();
+        original.sort();
+        assert_eq!(sorted, original);
+
+        // a degenerate triangle repeats a corner, which must not take two cache entries
+        let mut degenerate = [0u16, 1, 0, 0, 1, 2, 2, 1, 3];
+        optimize_vertex_cache(&mut degenerate, 4).unwrap();
+        let mut triangles: Vec<&[u16]> = degenerate.chunks(3).collect();
+        triangles.sort();
+        assert_eq!(triangles, [[0, 1, 0], [0, 1, 2], [2, 1, 3]]);
+    }
+
+    #[test]
+    fn test_out_of_range_indices() {
+        let (vertices, mut indices) = grid(2);
+        indices[4] = 9;
+        let out_of_range = |result: Result<_, MeshProcessingError>| {
+            matches!(
+                result,
+                Err(MeshProcessingError::IndexOutOfRange {
+                    index: 9,
+                    vertices: 9
+                })
+            )
+        };
+        assert!(out_of_range(smooth_normals(&vertices, &indices).map(drop)));
+        assert!(out_of_range(flat_normals(&vertices, &indices).map(drop)));
+        assert!(out_of_range(unweld(&vertices, &indices).map(drop)));
+        assert!(out_of_range(
+            weld_vertices(&vertices, &indices, 0.1).map(drop)
+        ));
+        assert!(out_of_range(simplify(&vertices, &indices, 6).map(drop)));
+        assert!(out_of_range(
+            generate_lods(&vertices, &indices, &[0.5]).map(drop)
+        ));
+        assert!(out_of_range(optimize_vertex_cache(&mut indices, 9)));
+        assert!(out_of_range(remap_attribute(&vertices, &[9], 9).map(drop)));
+        assert!(matches!(
+            remap_attribute(&vertices, &[0, 2], 3),
+            Err(MeshProcessingError::Unmapped { vertex: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_simplify() {
+        let (vertices, indices) = grid(8);
+        let simplified = simplify(&vertices, &indices, indices.len() / 4).unwrap();
+        assert!(simplified.len() < indices.len() / 2, "{}", simplified.len());
+        // a flat grid stays flat and facing up
+        for triangle in simplified.chunks(3) {
+            assert!(face_normal(&vertices, [triangle[0], triangle[1], triangle[2]]).y > 0.0);
+        }
+        // the border is locked, so the outline is unchanged
+        let area: f32 = simplified
+            .chunks(3)
+            .map(|t| face_normal(&vertices, [t[0], t[1], t[2]]).length() * 0.5)
+            .sum();
+        assert!((area - 64.0).abs() < 1e-3, "{area}");
+
+        let lods = generate_lods(&vertices, &indices, &[1.0, 0.5, 0.25]).unwrap();
+        assert_eq!(lods[0], indices);
+        assert!(lods[1].len() <= indices.len() / 2);
+        assert!(lods[2].len() <= lods[1].len());
+    }
+}
@@ -0,0 +1,670 @@
+//! Buses, ducking and voice limits on top of raylib's per-sound volume and pan.
+//!
+//! An [`AudioMixer`] routes [`Sound`]s, [`SoundAlias`]es and [`Music`] to buses. Every bus has a parent up to
+//! [`BusId::MASTER`], and a source is heard at the product of the volumes on its way there. raylib still does
+//! the mixing; [`AudioMixer::update`] pushes the resulting volume and pan to each source once per frame.
+//! ```ignore
+//! let mut mixer = AudioMixer::new();
+//! let footsteps = mixer.add_bus("footsteps", BusId::SFX);
+//! let voices = NonZeroUsize::new(4).unwrap();
+//! let step = mixer.add_sound(&step_sound, footsteps, voices, VoiceStealing::Oldest)?;
+//! let theme = mixer.add_music(&music, BusId::MUSIC);
+//! mixer.add_ducking(Ducking::new(BusId::VOICE, BusId::MUSIC));
+//! mixer.set_bus_volume(BusId::SFX, 0.8);
+//! while !rl.window_should_close() {
+//!     if walking {
+//!         mixer.play(step);
+//!     }
+//!     mixer.update(rl.get_frame_time());
+//! }
+//! ```
+use std::num::NonZeroUsize;
+
+use crate::core::audio::{Music, Sound, SoundAlias};
+use crate::core::error::LoadSoundError;
+
+/// A bus of an [`AudioMixer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BusId(usize);
+
+impl BusId {
+    /// Root of every other bus
+    pub const MASTER: BusId = BusId(0);
+    pub const MUSIC: BusId = BusId(1);
+    pub const SFX: BusId = BusId(2);
+    /// Dialogue, the usual trigger for [`Ducking`]
+    pub const VOICE: BusId = BusId(3);
+}
+
+/// A sound added with [`AudioMixer::add_sound`] or [`AudioMixer::add_sound_alias`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SoundId(usize);
+
+/// Music added with [`AudioMixer::add_music`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MusicId(usize);
+
+/// What [`AudioMixer::play`] does when all voices of a sound are busy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoiceStealing {
+    /// Restart the voice that started first
+    #[default]
+    Oldest,
+    /// Restart the voice played at the lowest volume, the oldest of them on a tie
+    Quietest,
+    /// Don't play
+    Reject,
+}
+
+/// Lowers a bus while another one plays, e.g. music under dialogue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ducking {
+    /// Bus whose playback ducks the target, including its child buses
+    pub trigger: BusId,
+    /// Bus to lower, and with it its child buses
+    pub target: BusId,
+    /// Gain of the target while ducked
+    pub level: f32,
+    /// Seconds to fade down to `level`
+    pub attack: f32,
+    /// Seconds to fade back up once the trigger is silent
+    pub release: f32,
+}
+
+impl Ducking {
+    /// Ducks to 0.3 in 50 ms and recovers in 400 ms
+    pub fn new(trigger: BusId, target: BusId) -> Self {
+        Self {
+            trigger,
+            target,
+            level: 0.3,
+            attack: 0.05,
+            release: 0.4,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Bus {
+    name: String,
+    parent: Option<BusId>,
+    volume: f32,
+    pan: f32,
+    muted: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Ducker {
+    config: Ducking,
+    gain: f32,
+}
+
+impl Ducker {
+    /// Ramps the gain linearly, so a full fade takes exactly the attack or release time
+    fn step(&mut self, active: bool, dt: f32) -> f32 {
+        let level = self.config.level.clamp(0.0, 1.0);
+        let (goal, time) = if active {
+            (level, self.config.attack)
+        } else {
+            (1.0, self.config.release)
+        };
+        let step = if time > 0.0 {
+            (1.0 - level) * dt / time
+        } else {
+            f32::INFINITY
+        };
+        self.gain = if self.gain > goal {
+            (self.gain - step).max(goal)
+        } else {
+            (self.gain + step).min(goal)
+        };
+        self.gain
+    }
+}
+
+enum VoiceHandle<'a, 'aud> {
+    Sound(&'a Sound<'aud>),
+    Alias(SoundAlias<'a, 'aud>),
+}
+
+impl VoiceHandle<'_, '_> {
+    fn play(&self) {
+        match self {
+            Self::Sound(sound) => sound.play(),
+            Self::Alias(alias) => alias.play(),
+        }
+    }
+
+    fn stop(&self) {
+        match self {
+            Self::Sound(sound) => sound.stop(),
+            Self::Alias(alias) => alias.stop(),
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        match self {
+            Self::Sound(sound) => sound.is_playing(),
+            Self::Alias(alias) => alias.is_playing(),
+        }
+    }
+
+    fn set_volume(&self, volume: f32) {
+        match self {
+            Self::Sound(sound) => sound.set_volume(volume),
+            Self::Alias(alias) => alias.set_volume(volume),
+        }
+    }
+
+    fn set_pan(&self, pan: f32) {
+        match self {
+            Self::Sound(sound) => sound.set_pan(pan),
+            Self::Alias(alias) => alias.set_pan(pan),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct VoiceState {
+    playing: bool,
+    /// Play order, higher is more recent
+    serial: u64,
+    volume: f32,
+    pan: f32,
+}
+
+impl Default for VoiceState {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            serial: 0,
+            volume: 1.0,
+            pan: 0.5,
+        }
+    }
+}
+
+/// Index of the voice to play next: an idle one if any, otherwise one picked by `stealing`
+fn pick_voice(voices: &[VoiceState], stealing: VoiceStealing) -> Option<usize> {
+    if let Some(idle) = voices.iter().position(|voice| !voice.playing) {
+        return Some(idle);
+    }
+    let oldest = |a: &(usize, &VoiceState), b: &(usize, &VoiceState)| a.1.serial.cmp(&b.1.serial);
+    match stealing {
+        VoiceStealing::Oldest => voices.iter().enumerate().min_by(oldest),
+        VoiceStealing::Quietest => voices
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.volume.total_cmp(&b.1.volume).then_with(|| oldest(a, b))),
+        VoiceStealing::Reject => None,
+    }
+    .map(|(index, _)| index)
+}
+
+struct SoundSource<'a, 'aud> {
+    bus: BusId,
+    volume: f32,
+    pan: f32,
+    stealing: VoiceStealing,
+    handles: Vec<VoiceHandle<'a, 'aud>>,
+    voices: Vec<VoiceState>,
+}
+
+struct MusicSource<'a, 'aud> {
+    music: &'a Music<'aud>,
+    bus: BusId,
+    volume: f32,
+    pan: f32,
+}
+
+/// Moves `pan` away from the center (0.5) by `offset`'s distance from it
+fn offset_pan(pan: f32, offset: f32) -> f32 {
+    pan + offset - 0.5
+}
+
+/// Routes sounds and music through buses. See the [module documentation](self).
+///
+/// Bus, sound and music ids are only valid for the mixer that returned them; methods panic on ids from another
+/// mixer that are out of range.
+pub struct AudioMixer<'a, 'aud> {
+    buses: Vec<Bus>,
+    duckers: Vec<Ducker>,
+    sounds: Vec<SoundSource<'a, 'aud>>,
+    music: Vec<MusicSource<'a, 'aud>>,
+    serial: u64,
+}
+
+impl std::fmt::Debug for AudioMixer<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioMixer")
+            .field("buses", &self.buses)
+            .field("duckers", &self.duckers)
+            .field("sounds", &self.sounds.len())
+            .field("music", &self.music.len())
+            .finish()
+    }
+}
+
+impl Default for AudioMixer<'_, '_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, 'aud> AudioMixer<'a, 'aud> {
+    /// A mixer with the `master`, `music`, `sfx` and `voice` buses
+    pub fn new() -> Self {
+        let mut mixer = Self {
+            buses: Vec::new(),
+            duckers: Vec::new(),
+            sounds: Vec::new(),
+            music: Vec::new(),
+            serial: 0,
+        };
+        mixer.push_bus("master", None);
+        mixer.push_bus("music", Some(BusId::MASTER));
+        mixer.push_bus("sfx", Some(BusId::MASTER));
+        mixer.push_bus("voice", Some(BusId::MASTER));
+        mixer
+    }
+
+    fn push_bus(&mut self, name: &str, parent: Option<BusId>) -> BusId {
+        self.buses.push(Bus {
+            name: name.to_owned(),
+            parent,
+            volume: 1.0,
+            pan: 0.5,
+            muted: false,
+        });
+        BusId(self.buses.len() - 1)
+    }
+
+    /// Adds a bus, a group of sounds under `parent` that can be leveled, panned and muted together
+    pub fn add_bus(&mut self, name: &str, parent: BusId) -> BusId {
+        assert!(parent.0 < self.buses.len(), "unknown parent bus");
+        self.push_bus(name, Some(parent))
+    }
+
+    /// Looks up a bus by the name it was added with
+    pub fn bus(&self, name: &str) -> Option<BusId> {
+        self.buses
+            .iter()
+            .position(|bus| bus.name == name)
+            .map(BusId)
+    }
+
+    pub fn bus_name(&self, bus: BusId) -> &str {
+        &self.buses[bus.0].name
+    }
+
+    pub fn bus_parent(&self, bus: BusId) -> Option<BusId> {
+        self.buses[bus.0].parent
+    }
+
+    pub fn bus_volume(&self, bus: BusId) -> f32 {
+        self.buses[bus.0].volume
+    }
+
+    /// Sets the volume of a bus (`1.0` is unchanged)
+    pub fn set_bus_volume(&mut self, bus: BusId, volume: f32) {
+        self.buses[bus.0].volume = volume.max(0.0);
+    }
+
+    pub fn bus_pan(&self, bus: BusId) -> f32 {
+        self.buses[bus.0].pan
+    }
+
+    /// Sets the pan of a bus (0.5 is center). Pans add up along the way to the master bus.
+    pub fn set_bus_pan(&mut self, bus: BusId, pan: f32) {
+        self.buses[bus.0].pan = pan.clamp(0.0, 1.0);
+    }
+
+    pub fn is_bus_muted(&self, bus: BusId) -> bool {
+        self.buses[bus.0].muted
+    }
+
+    /// Silences a bus and its children without losing its volume
+    pub fn set_bus_muted(&mut self, bus: BusId, muted: bool) {
+        self.buses[bus.0].muted = muted;
+    }
+
+    /// Whether `bus` is `ancestor` or one of its descendants
+    fn is_within(&self, mut bus: BusId, ancestor: BusId) -> bool {
+        loop {
+            if bus == ancestor {
+                return true;
+            }
+            match self.buses[bus.0].parent {
+                Some(parent) => bus = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Gain and pan a bus contributes, with its ancestors and their ducking
+    fn bus_mix(&self, mut bus: BusId) -> (f32, f32) {
+        let (mut gain, mut pan) = (1.0, 0.5);
+        loop {
+            let state = &self.buses[bus.0];
+            gain *= if state.muted { 0.0 } else { state.volume };
+            pan = offset_pan(pan, state.pan);
+            for ducker in self.duckers.iter().filter(|d| d.config.target == bus) {
+                gain *= ducker.gain;
+            }
+            match state.parent {
+                Some(parent) => bus = parent,
+                None => return (gain, pan),
+            }
+        }
+    }
+
+    /// Ducks `target` whenever something on `trigger` plays, see [`Ducking`]
+    pub fn add_ducking(&mut self, ducking: Ducking) {
+        assert!(
+            ducking.trigger.0 < self.buses.len() && ducking.target.0 < self.buses.len(),
+            "unknown ducking bus"
+        );
+        self.duckers.push(Ducker {
+            config: ducking,
+            gain: 1.0,
+        });
+    }
+
+    /// Removes every ducking with this target
+    pub fn remove_ducking(&mut self, target: BusId) {
+        self.duckers.retain(|ducker| ducker.config.target != target);
+    }
+
+    /// Current ducking gain of a bus, `1.0` when it isn't ducked
+    pub fn ducking_gain(&self, bus: BusId) -> f32 {
+        self.duckers
+            .iter()
+            .filter(|ducker| ducker.config.target == bus)
+            .map(|ducker| ducker.gain)
+            .product()
+    }
+
+    /// Routes a sound to a bus, playable `max_voices` times at once. The sound itself is the first voice; the
+    /// others are aliases sharing its samples.
+    pub fn add_sound(
+        &mut self,
+        sound: &'a Sound<'aud>,
+        bus: BusId,
+        max_voices: NonZeroUsize,
+        stealing: VoiceStealing,
+    ) -> Result<SoundId, LoadSoundError> {
+        let mut handles = vec![VoiceHandle::Sound(sound)];
+        for _ in 1..max_voices.get() {
+            handles.push(VoiceHandle::Alias(sound.alias()?));
+        }
+        Ok(self.push_sound(bus, stealing, handles))
+    }
+
+    /// Routes an alias to a bus as a sound with a single voice
+    pub fn add_sound_alias(&mut self, alias: SoundAlias<'a, 'aud>, bus: BusId) -> SoundId {
+        self.push_sound(bus, VoiceStealing::Oldest, vec![VoiceHandle::Alias(alias)])
+    }
+
+    fn push_sound(
+        &mut self,
+        bus: BusId,
+        stealing: VoiceStealing,
+        handles: Vec<VoiceHandle<'a, 'aud>>,
+    ) -> SoundId {
+        assert!(bus.0 < self.buses.len(), "unknown bus");
+        self.sounds.push(SoundSource {
+            bus,
+            volume: 1.0,
+            pan: 0.5,
+            stealing,
+            voices: vec![VoiceState::default(); handles.len()],
+            handles,
+        });
+        SoundId(self.sounds.len() - 1)
+    }
+
+    /// Routes music to a bus. Streaming stays up to the caller (`play_stream`, `update_stream`).
+    pub fn add_music(&mut self, music: &'a Music<'aud>, bus: BusId) -> MusicId {
+        assert!(bus.0 < self.buses.len(), "unknown bus");
+        self.music.push(MusicSource {
+            music,
+            bus,
+            volume: 1.0,
+            pan: 0.5,
+        });
+        MusicId(self.music.len() - 1)
+    }
+
+    /// Moves a sound to another bus
+    pub fn set_sound_bus(&mut self, sound: SoundId, bus: BusId) {
+        assert!(bus.0 < self.buses.len(), "unknown bus");
+        self.sounds[sound.0].bus = bus;
+    }
+
+    /// Sets the base volume of every voice of a sound
+    pub fn set_sound_volume(&mut self, sound: SoundId, volume: f32) {
+        self.sounds[sound.0].volume = volume.max(0.0);
+    }
+
+    /// Sets the base pan of every voice of a sound (0.5 is center)
+    pub fn set_sound_pan(&mut self, sound: SoundId, pan: f32) {
+        self.sounds[sound.0].pan = pan.clamp(0.0, 1.0);
+    }
+
+    pub fn set_voice_stealing(&mut self, sound: SoundId, stealing: VoiceStealing) {
+        self.sounds[sound.0].stealing = stealing;
+    }
+
+    /// Moves music to another bus
+    pub fn set_music_bus(&mut self, music: MusicId, bus: BusId) {
+        assert!(bus.0 < self.buses.len(), "unknown bus");
+        self.music[music.0].bus = bus;
+    }
+
+    pub fn set_music_volume(&mut self, music: MusicId, volume: f32) {
+        self.music[music.0].volume = volume.max(0.0);
+    }
+
+    pub fn set_music_pan(&mut self, music: MusicId, pan: f32) {
+        self.music[music.0].pan = pan.clamp(0.0, 1.0);
+    }
+
+    /// Plays a sound on a free voice, or steals one. Returns false if the sound's [`VoiceStealing`] rejected it.
+    pub fn play(&mut self, sound: SoundId) -> bool {
+        self.play_with(sound, 1.0, 0.5)
+    }
+
+    /// Plays a sound like [`play`](AudioMixer::play), with a volume and pan for this voice only
+    pub fn play_with(&mut self, sound: SoundId, volume: f32, pan: f32) -> bool {
+        let (gain, bus_pan) = self.bus_mix(self.sounds[sound.0].bus);
+        let source = &mut self.sounds[sound.0];
+        for (state, handle) in source.voices.iter_mut().zip(&source.handles) {
+            state.playing = handle.is_playing();
+        }
+        let Some(index) = pick_voice(&source.voices, source.stealing) else {
+            return false;
+        };
+        self.serial += 1;
+        let state = &mut source.voices[index];
+        *state = VoiceState {
+            playing: true,
+            serial: self.serial,
+            volume: volume.max(0.0),
+            pan: pan.clamp(0.0, 1.0),
+        };
+        let handle = &source.handles[index];
+        if handle.is_playing() {
+            handle.stop();
+        }
+        handle.set_volume(gain * source.volume * state.volume);
+        handle.set_pan(offset_pan(offset_pan(bus_pan, source.pan), state.pan).clamp(0.0, 1.0));
+        handle.play();
+        true
+    }
+
+    /// Stops every voice of a sound
+    pub fn stop(&mut self, sound: SoundId) {
+        let source = &mut self.sounds[sound.0];
+        for (state, handle) in source.voices.iter_mut().zip(&source.handles) {
+            handle.stop();
+            state.playing = false;
+        }
+    }
+
+    /// Stops every sound routed to a bus or its children. Music is left to the caller.
+    pub fn stop_bus(&mut self, bus: BusId) {
+        for index in 0..self.sounds.len() {
+            if self.is_within(self.sounds[index].bus, bus) {
+                self.stop(SoundId(index));
+            }
+        }
+    }
+
+    /// Number of voices of a sound playing as of the last [`update`](AudioMixer::update) or play
+    pub fn playing_voices(&self, sound: SoundId) -> usize {
+        self.sounds[sound.0]
+            .voices
+            .iter()
+            .filter(|voice| voice.playing)
+            .count()
+    }
+
+    /// Advances ducking by `dt` seconds and applies bus volumes and pans to every source. Call once per frame.
+    pub fn update(&mut self, dt: f32) {
+        for source in &mut self.sounds {
+            for (state, handle) in source.voices.iter_mut().zip(&source.handles) {
+                state.playing = handle.is_playing();
+            }
+        }
+        let active: Vec<bool> = self
+            .duckers
+            .iter()
+            .map(|ducker| {
+                let trigger = ducker.config.trigger;
+                self.sounds.iter().any(|source| {
+                    self.is_within(source.bus, trigger) && source.voices.iter().any(|v| v.playing)
+                }) || self.music.iter().any(|source| {
+                    self.is_within(source.bus, trigger) && source.music.is_stream_playing()
+                })
+            })
+            .collect();
+        for (ducker, active) in self.duckers.iter_mut().zip(active) {
+            ducker.step(active, dt);
+        }
+
+        for source in &self.sounds {
+            let (gain, bus_pan) = self.bus_mix(source.bus);
+            let pan = offset_pan(bus_pan, source.pan);
+            for (state, handle) in source.voices.iter().zip(&source.handles) {
+                handle.set_volume(gain * source.volume * state.volume);
+                handle.set_pan(offset_pan(pan, state.pan).clamp(0.0, 1.0));
+            }
+        }
+        for source in &self.music {
+            let (gain, bus_pan) = self.bus_mix(source.bus);
+            source.music.set_volume(gain * source.volume);
+            source
+                .music
+                .set_pan(offset_pan(bus_pan, source.pan).clamp(0.0, 1.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod mixer_test {
+    use super::*;
+
+    fn voice(playing: bool, serial: u64, volume: f32) -> VoiceState {
+        VoiceState {
+            playing,
+            serial,
+            volume,
+            pan: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_default_buses() {
+        let mixer = AudioMixer::new();
+        assert_eq!(mixer.bus("master"), Some(BusId::MASTER));
+        assert_eq!(mixer.bus("music"), Some(BusId::MUSIC));
+        assert_eq!(mixer.bus("sfx"), Some(BusId::SFX));
+        assert_eq!(mixer.bus("voice"), Some(BusId::VOICE));
+        assert_eq!(mixer.bus_parent(BusId::VOICE), Some(BusId::MASTER));
+        assert_eq!(mixer.bus_parent(BusId::MASTER), None);
+        assert_eq!(mixer.bus("ambience"), None);
+    }
+
+    #[test]
+    fn test_bus_mix() {
+        let mut mixer = AudioMixer::new();
+        let footsteps = mixer.add_bus("footsteps", BusId::SFX);
+        mixer.set_bus_volume(BusId::MASTER, 0.5);
+        mixer.set_bus_volume(BusId::SFX, 0.8);
+        mixer.set_bus_volume(footsteps, 0.5);
+        mixer.set_bus_pan(BusId::SFX, 0.75);
+        mixer.set_bus_pan(footsteps, 0.5);
+        let (gain, pan) = mixer.bus_mix(footsteps);
+        assert!((gain - 0.2).abs() < 1e-6);
+        assert_eq!(pan, 0.75);
+        assert!(mixer.is_within(footsteps, BusId::MASTER));
+        assert!(!mixer.is_within(footsteps, BusId::MUSIC));
+
+        mixer.set_bus_muted(BusId::SFX, true);
+        assert_eq!(mixer.bus_mix(footsteps).0, 0.0);
+        assert_eq!(mixer.bus_volume(BusId::SFX), 0.8);
+        assert_eq!(mixer.bus_mix(BusId::MUSIC).0, 0.5);
+    }
+
+    #[test]
+    fn test_ducking_envelope() {
+        let mut ducker = Ducker {
+            config: Ducking {
+                level: 0.2,
+                attack: 0.1,
+                release: 0.4,
+                ..Ducking::new(BusId::VOICE, BusId::MUSIC)
+            },
+            gain: 1.0,
+        };
+        assert!((ducker.step(true, 0.05) - 0.6).abs() < 1e-6);
+        assert_eq!(ducker.step(true, 0.05), 0.2);
+        assert_eq!(ducker.step(true, 1.0), 0.2);
+        assert!((ducker.step(false, 0.2) - 0.6).abs() < 1e-6);
+        assert_eq!(ducker.step(false, 1.0), 1.0);
+
+        ducker.config.attack = 0.0;
+        assert_eq!(ducker.step(true, 0.0), 0.2);
+    }
+
+    #[test]
+    fn test_ducking_gain() {
+        let mut mixer = AudioMixer::new();
+        mixer.add_ducking(Ducking::new(BusId::VOICE, BusId::MUSIC));
+        mixer.duckers[0].gain = 0.5;
+        assert_eq!(mixer.ducking_gain(BusId::MUSIC), 0.5);
+        assert_eq!(mixer.bus_mix(BusId::MUSIC).0, 0.5);
+        assert_eq!(mixer.bus_mix(BusId::SFX).0, 1.0);
+        mixer.update(1.0);
+        assert_eq!(mixer.ducking_gain(BusId::MUSIC), 1.0);
+        mixer.remove_ducking(BusId::MUSIC);
+        assert_eq!(mixer.ducking_gain(BusId::MUSIC), 1.0);
+    }
+
+    #[test]
+    fn test_pick_voice() {
+        let busy = [
+            voice(true, 3, 0.2),
+            voice(true, 1, 0.9),
+            voice(true, 2, 0.2),
+        ];
+        assert_eq!(pick_voice(&busy, VoiceStealing::Oldest), Some(1));
+        assert_eq!(pick_voice(&busy, VoiceStealing::Quietest), Some(2));
+        assert_eq!(pick_voice(&busy, VoiceStealing::Reject), None);
+
+        let mut free = busy;
+        free[0].playing = false;
+        assert_eq!(pick_voice(&free, VoiceStealing::Reject), Some(0));
+        assert_eq!(pick_voice(&[], VoiceStealing::Oldest), None);
+    }
+}
@@ -1,7 +1,10 @@
 #[macro_use]
 mod macros;
 
+pub mod animation;
+pub mod assets;
 pub mod audio;
+pub mod audio_effects;
 pub mod automation;
 pub mod callbacks;
 pub mod camera;
@@ -12,17 +15,32 @@ pub mod color {
 }
 pub mod data;
 pub mod drawing;
+pub mod dynamic_font;
 pub mod error;
 pub mod file;
+pub mod game_loop;
+#[cfg(feature = "gltf")]
+pub mod gltf;
+pub mod hot_reload;
 
 pub mod input;
 pub mod logging;
 pub mod math;
+pub mod mesh_builder;
+pub mod mesh_processing;
 pub mod misc;
+pub mod mixer;
 pub mod models;
+pub mod replay;
+pub mod rlgl;
+pub mod sdf_font;
 pub mod shaders;
+#[cfg(feature = "shaping")]
+pub mod shaping;
 pub mod text;
+pub mod text_layout;
 pub mod texture;
+pub mod vfs;
 pub mod vr;
 pub mod window;
 
@@ -86,6 +104,7 @@ pub struct RaylibBuilder {
     window_transparent: bool,
     msaa_4x_hint: bool,
     vsync_hint: bool,
+    headless: bool,
     log_level: TraceLogLevel,
     width: i32,
     height: i32,
@@ -145,6 +164,19 @@ impl RaylibBuilder {
         self
     }
 
+    /// Runs without a visible window so rendering works on machines without a display or GPU.
+    ///
+    /// On desktop GLFW builds this selects GLFW's null platform, which creates its OpenGL context through
+    /// OSMesa (e.g. `libosmesa6` on Debian/Ubuntu must be installed at runtime). Rendering to the screen,
+    /// [`RaylibHandle::load_image_from_screen`] and `RenderTexture2D` readback keep working. Other platforms
+    /// only hide the window.
+    ///
+    /// Input and monitor functions return dummy values in headless mode.
+    pub fn headless(&mut self) -> &mut Self {
+        self.headless = true;
+        self
+    }
+
     /// Sets the window's width.
     pub fn width(&mut self, w: i32) -> &mut Self {
         self.width = w;
@@ -196,6 +228,19 @@ impl RaylibBuilder {
         if self.vsync_hint {
             flags |= FLAG_VSYNC_HINT as u32;
         }
+        if self.headless {
+            flags |= FLAG_WINDOW_HIDDEN as u32;
+            // must be set before glfwInit, which InitWindow calls
+            #[cfg(not(any(
+                feature = "sdl",
+                target_os = "android",
+                target_family = "wasm",
+                all(target_arch = "arm", target_os = "linux")
+            )))]
+            unsafe {
+                ffi::glfw::glfwInitHint(ffi::glfw::GLFW_PLATFORM, ffi::glfw::GLFW_PLATFORM_NULL);
+            }
+        }
 
         unsafe {
             ffi::SetConfigFlags(flags as u32);
@@ -1,16 +1,19 @@
 //! 3D Model, Mesh, and Animation
 
 use crate::MintVec3;
+use crate::core::animation::{skin_vertices, skinning_matrices};
 use crate::core::math::BoundingBox;
 use crate::core::math::Matrix;
 use crate::core::math::Transform;
-use crate::core::math::Vector3;
+use crate::core::math::{Vector2, Vector3};
 use crate::core::texture::Image;
 use crate::core::{RaylibHandle, RaylibThread};
 use crate::ffi::Color;
 use crate::{
     consts,
-    error::{LoadMaterialError, LoadModelAnimError, LoadModelError, SetMaterialError},
+    error::{
+        LoadMaterialError, LoadModelAnimError, LoadModelError, SetMaterialError, UpdateMeshError,
+    },
     ffi,
 };
 use std::ffi::CString;
@@ -295,6 +298,37 @@ pub trait RaylibModel: AsRef<ffi::Model> + AsMut<ffi::Model> {
         }
         Some(unsafe { std::mem::transmute(self.as_mut().bindPose) })
     }
+    /// Bones base transformation (pose), one per bone
+    #[inline]
+    #[must_use]
+    fn bind_poses(&self) -> &[Transform] {
+        if self.as_ref().bindPose.is_null() {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(
+                self.as_ref().bindPose as *const Transform,
+                self.as_ref().boneCount as usize,
+            )
+        }
+    }
+    /// Positions of mesh `mesh` skinned on the CPU into `pose`, e.g. a frame of
+    /// [`RaylibModelAnimation::frame_poses`]. The model itself is left untouched.
+    ///
+    /// Returns the unskinned positions if the mesh has no bone weights, and nothing if there is no such mesh.
+    #[must_use]
+    fn skinned_vertices(&self, mesh: usize, pose: &[Transform]) -> Vec<Vector3> {
+        let Some(mesh) = self.meshes().get(mesh) else {
+            return Vec::new();
+        };
+        let matrices = skinning_matrices(self.bind_poses(), pose);
+        skin_vertices(
+            mesh.vertices(),
+            mesh.bone_ids(),
+            mesh.bone_weights(),
+            &matrices,
+        )
+    }
     #[inline]
     #[must_use]
     /// Check model animation skeleton match
@@ -451,25 +485,59 @@ pub trait RaylibMesh: AsRef<ffi::Mesh> + AsMut<ffi::Mesh> {
             )
         }
     }
-    /// Vertex indices (in case vertex data comes indexed)
+    /// Vertex bone ids, up to 4 bones influence a vertex (skinning), empty if the mesh isn't skinned
+    #[inline]
+    #[must_use]
+    fn bone_ids(&self) -> &[[u8; 4]] {
+        if self.as_ref().boneIds.is_null() {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(
+                self.as_ref().boneIds as *const [u8; 4],
+                self.as_ref().vertexCount as usize,
+            )
+        }
+    }
+    /// Vertex bone weight, up to 4 bones influence a vertex (skinning), empty if the mesh isn't skinned
+    #[inline]
+    #[must_use]
+    fn bone_weights(&self) -> &[[f32; 4]] {
+        if self.as_ref().boneWeights.is_null() {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(
+                self.as_ref().boneWeights as *const [f32; 4],
+                self.as_ref().vertexCount as usize,
+            )
+        }
+    }
+    /// Vertex indices (in case vertex data comes indexed), empty if the mesh has no index buffer
     #[inline]
     #[must_use]
     fn indicies(&self) -> &[u16] {
+        if self.as_ref().indices.is_null() {
+            return &[];
+        }
         unsafe {
             std::slice::from_raw_parts(
                 self.as_ref().indices as *const u16,
-                self.as_ref().vertexCount as usize,
+                self.as_ref().triangleCount as usize * 3,
             )
         }
     }
-    /// Vertex indices (in case vertex data comes indexed)
+    /// Vertex indices (in case vertex data comes indexed), empty if the mesh has no index buffer
     #[inline]
     #[must_use]
     fn indicies_mut(&mut self) -> &mut [u16] {
+        if self.as_ref().indices.is_null() {
+            return &mut [];
+        }
         unsafe {
             std::slice::from_raw_parts_mut(
                 self.as_mut().indices as *mut u16,
-                self.as_mut().vertexCount as usize,
+                self.as_mut().triangleCount as usize * 3,
             )
         }
     }
@@ -571,15 +639,36 @@ pub trait RaylibMesh: AsRef<ffi::Mesh> + AsMut<ffi::Mesh> {
         }
     }
 
-    /// Exports mesh as an OBJ file.
+    /// Exports mesh as an OBJ file, or as glTF if `filename` ends in `.gltf` or `.glb` and the `gltf` feature
+    /// is enabled. Use [RaylibMesh::export_gltf] to see why a glTF export failed.
     #[inline]
     fn export(&self, filename: &str) {
+        #[cfg(feature = "gltf")]
+        if std::path::Path::new(filename)
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("gltf") || e.eq_ignore_ascii_case("glb"))
+        {
+            if let Err(e) = self.export_gltf(filename) {
+                crate::core::logging::trace_log(
+                    crate::consts::TraceLogLevel::LOG_WARNING,
+                    &format!("MESH: {e}"),
+                );
+            }
+            return;
+        }
         let c_filename = CString::new(filename).unwrap();
         unsafe {
             ffi::ExportMesh(*self.as_ref(), c_filename.as_ptr());
         }
     }
 
+    /// Exports mesh as glTF, binary if `filename` ends in `.glb`. Tangents, colors, both texcoord sets and
+    /// bone weights are written along with positions, normals and indices.
+    #[cfg(feature = "gltf")]
+    fn export_gltf(&self, filename: &str) -> Result<(), crate::error::GltfError> {
+        crate::core::gltf::export_mesh(self.as_ref(), filename)
+    }
+
     /// Export mesh as code file (.h) defining multiple arrays of vertex attributes
     #[inline]
     fn export_as_code(&self, filename: &str) {
@@ -590,6 +679,173 @@ pub trait RaylibMesh: AsRef<ffi::Mesh> + AsMut<ffi::Mesh> {
     }
 }
 
+// vertex buffer slots set up by UploadMesh, which indexes `vboId` with rlgl's default attribute locations
+const MESH_BUFFER_POSITION: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_POSITION as i32;
+const MESH_BUFFER_TEXCOORD: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_TEXCOORD as i32;
+const MESH_BUFFER_NORMAL: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_NORMAL as i32;
+const MESH_BUFFER_COLOR: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_COLOR as i32;
+const MESH_BUFFER_TANGENT: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_TANGENT as i32;
+const MESH_BUFFER_TEXCOORD2: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_TEXCOORD2 as i32;
+const MESH_BUFFER_INDICES: i32 = ffi::RL_DEFAULT_SHADER_ATTRIB_LOCATION_INDICES as i32;
+
+/// A mesh uploaded with dynamic buffers, for vertex data that changes every frame (cloth, trails, deformable
+/// terrain).
+///
+/// Every `update_*` method writes the mesh's CPU copy and its GPU buffer starting at element `offset`.
+#[derive(Debug)]
+pub struct DynamicMesh(Mesh);
+
+impl DynamicMesh {
+    /// Uploads `mesh` with dynamic buffers. The mesh must not have been uploaded yet, e.g. one from
+    /// [`MeshBuilder::build_cpu`](crate::core::mesh_builder::MeshBuilder::build_cpu).
+    pub fn new(_: &RaylibThread, mut mesh: Mesh) -> Result<DynamicMesh, UpdateMeshError> {
+        if mesh.0.vaoId != 0 || !mesh.0.vboId.is_null() {
+            return Err(UpdateMeshError::AlreadyUploaded);
+        }
+        unsafe { ffi::UploadMesh(&mut mesh.0, true) };
+        Ok(DynamicMesh(mesh))
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Mesh {
+        self.0
+    }
+
+    /// Updates vertex positions.
+    pub fn update_vertices(
+        &mut self,
+        data: &[Vector3],
+        offset: usize,
+    ) -> Result<(), UpdateMeshError> {
+        let cpu = self.0.0.vertices.cast();
+        self.update(MESH_BUFFER_POSITION, "vertices", cpu, data, offset)
+    }
+
+    /// Updates vertex normals.
+    pub fn update_normals(
+        &mut self,
+        data: &[Vector3],
+        offset: usize,
+    ) -> Result<(), UpdateMeshError> {
+        let cpu = self.0.0.normals.cast();
+        self.update(MESH_BUFFER_NORMAL, "normals", cpu, data, offset)
+    }
+
+    /// Updates the first set of texture coordinates.
+    pub fn update_texcoords(
+        &mut self,
+        data: &[Vector2],
+        offset: usize,
+    ) -> Result<(), UpdateMeshError> {
+        let cpu = self.0.0.texcoords.cast();
+        self.update(MESH_BUFFER_TEXCOORD, "texcoords", cpu, data, offset)
+    }
+
+    /// Updates the second set of texture coordinates.
+    pub fn update_texcoords2(
+        &mut self,
+        data: &[Vector2],
+        offset: usize,
+    ) -> Result<(), UpdateMeshError> {
+        let cpu = self.0.0.texcoords2.cast();
+        self.update(MESH_BUFFER_TEXCOORD2, "texcoords2", cpu, data, offset)
+    }
+
+    /// Updates vertex colors.
+    pub fn update_colors(&mut self, data: &[Color], offset: usize) -> Result<(), UpdateMeshError> {
+        let cpu = self.0.0.colors.cast();
+        self.update(MESH_BUFFER_COLOR, "colors", cpu, data, offset)
+    }
+
+    /// Updates vertex tangents, `[x, y, z, w]` with `w` the bitangent sign.
+    pub fn update_tangents(
+        &mut self,
+        data: &[[f32; 4]],
+        offset: usize,
+    ) -> Result<(), UpdateMeshError> {
+        let cpu = self.0.0.tangents.cast();
+        self.update(MESH_BUFFER_TANGENT, "tangents", cpu, data, offset)
+    }
+
+    /// Updates triangle indices. Bounds are checked against the index count, `triangleCount * 3`, and every
+    /// index must refer to an existing vertex.
+    pub fn update_indices(&mut self, data: &[u16], offset: usize) -> Result<(), UpdateMeshError> {
+        let vertices = self.0.0.vertexCount.max(0) as usize;
+        if let Some(&index) = data.iter().find(|i| **i as usize >= vertices) {
+            return Err(UpdateMeshError::IndexOutOfRange { index, vertices });
+        }
+        let cpu = self.0.0.indices;
+        self.update(MESH_BUFFER_INDICES, "indices", cpu, data, offset)
+    }
+
+    fn update<T: Copy>(
+        &mut self,
+        buffer: i32,
+        attribute: &'static str,
+        cpu: *mut T,
+        data: &[T],
+        offset: usize,
+    ) -> Result<(), UpdateMeshError> {
+        let mesh = &self.0.0;
+        let count = if buffer == MESH_BUFFER_INDICES {
+            mesh.triangleCount.max(0) as usize * 3
+        } else {
+            mesh.vertexCount.max(0) as usize
+        };
+        let vbo = if mesh.vboId.is_null() {
+            0
+        } else {
+            unsafe { *mesh.vboId.add(buffer as usize) }
+        };
+        if vbo == 0 || cpu.is_null() {
+            return Err(UpdateMeshError::MissingAttribute { attribute });
+        }
+        if offset.checked_add(data.len()).is_none_or(|end| end > count) {
+            return Err(UpdateMeshError::OutOfBounds {
+                attribute,
+                offset,
+                len: data.len(),
+                count,
+            });
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let size = std::mem::size_of::<T>();
+        unsafe {
+            // byte copy: raylib's buffers are only as aligned as its allocator, which may be less than `T`
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr() as *const u8,
+                (cpu as *mut u8).add(offset * size),
+                std::mem::size_of_val(data),
+            );
+            ffi::UpdateMeshBuffer(
+                *mesh,
+                buffer,
+                data.as_ptr() as *const c_void,
+                std::mem::size_of_val(data) as i32,
+                (offset * size) as i32,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for DynamicMesh {
+    type Target = Mesh;
+
+    fn deref(&self) -> &Mesh {
+        &self.0
+    }
+}
+
+impl AsRef<ffi::Mesh> for DynamicMesh {
+    fn as_ref(&self) -> &ffi::Mesh {
+        &self.0.0
+    }
+}
+
 impl Material {
     #[must_use]
     #[inline]
@@ -851,6 +1107,28 @@ impl ModelAnimation {
     }
 }
 
+impl BoneInfo {
+    /// Bone name
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> String {
+        let bytes: Vec<u8> = self
+            .0
+            .name
+            .iter()
+            .take_while(|c| **c != 0)
+            .map(|c| *c as u8)
+            .collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+    /// Index of the parent bone, `None` for a root bone
+    #[inline]
+    #[must_use]
+    pub fn parent(&self) -> Option<usize> {
+        usize::try_from(self.0.parent).ok()
+    }
+}
+
 pub trait RaylibModelAnimation: AsRef<ffi::ModelAnimation> + AsMut<ffi::ModelAnimation> {
     /// Bones information (skeleton)
     #[inline]
@@ -876,6 +1154,36 @@ pub trait RaylibModelAnimation: AsRef<ffi::ModelAnimation> + AsMut<ffi::ModelAni
         }
     }
 
+    /// Index of the bone called `name`
+    #[inline]
+    #[must_use]
+    fn bone_index(&self, name: &str) -> Option<usize> {
+        self.bones().iter().position(|b| b.name() == name)
+    }
+
+    /// World-space transform of the bone called `name` at `frame`, for attaching a weapon or hit-box to it.
+    /// `model_transform` places the model in the world: the model's [`transform`](RaylibModel::transform)
+    /// followed by where it is drawn, e.g. `*model.transform() * Matrix::translate(x, y, z)` for
+    /// `draw_model` at `(x, y, z)`. Pass [`Matrix::identity`] for the model-space transform.
+    ///
+    /// `None` if there is no such bone or frame.
+    #[must_use]
+    fn bone_world_transform(
+        &self,
+        name: &str,
+        frame: usize,
+        model_transform: Matrix,
+    ) -> Option<Matrix> {
+        let bone = self.bone_index(name)?;
+        if frame >= self.as_ref().frameCount.max(0) as usize {
+            return None;
+        }
+        self.frame_poses_iter()
+            .nth(frame)
+            .and_then(|pose| pose.get(bone))
+            .map(|pose| pose.to_matrix() * model_transform)
+    }
+
     #[must_use]
     /// Poses array by frame
     fn frame_poses(&self) -> Vec<&[Transform]> {
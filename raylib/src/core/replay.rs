@@ -0,0 +1,376 @@
+//! Deterministic input replays built on automation events.
+//!
+//! A [`ReplayRecorder`] records input into an [`AutomationEventList`] and stores a hash of the game state at
+//! chosen frames. A [`ReplaySession`] plays the events back frame by frame and reports the first checkpoint
+//! where the state hash no longer matches.
+//!
+//! Frames are counted by the session, not by raylib, so replays only reproduce if the game advances by a fixed
+//! time step per frame instead of using [`RaylibHandle::get_frame_time`].
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::core::RaylibHandle;
+use crate::core::automation::{AutomationEvent, AutomationEventList};
+
+/// Hashes `value` with [`ReplayHasher`]. Floats don't implement `Hash`, hash their `to_bits()` instead.
+#[must_use]
+pub fn state_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = ReplayHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a over little-endian integers, with `usize` and `isize` widened to 64 bits.
+///
+/// Unlike `DefaultHasher` the algorithm is fixed, so hashes of the same bytes match on every platform and Rust
+/// release. What bytes a type feeds it is up to its `Hash` impl: primitives and your own impls that hash fields
+/// one by one are stable, but std makes no promise for the layout of its collections' impls.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayHasher(u64);
+
+impl Default for ReplayHasher {
+    fn default() -> Self {
+        ReplayHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for ReplayHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    /// Also receives the length prefixes of slices and collections
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64);
+    }
+}
+
+/// A checkpoint whose state hash did not match the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayDivergence {
+    pub frame: u32,
+    pub expected: u64,
+    /// `None` if no hash was reported on that frame
+    pub actual: Option<u64>,
+}
+
+/// Outcome of a replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Number of frames stepped
+    pub frames: u32,
+    /// Number of checkpoints that were compared
+    pub checkpoints_checked: usize,
+    /// The earliest checkpoint that did not match, if any
+    pub first_divergence: Option<ReplayDivergence>,
+}
+
+impl ReplayReport {
+    /// True if every checkpoint matched
+    #[inline]
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.first_divergence.is_none()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.first_divergence {
+            None => write!(
+                f,
+                "replay matched: {} frames, {} checkpoints",
+                self.frames, self.checkpoints_checked
+            ),
+            Some(ReplayDivergence {
+                frame,
+                expected,
+                actual: Some(actual),
+            }) => write!(
+                f,
+                "replay diverged at frame {}: expected state {:#018x}, got {:#018x}",
+                frame, expected, actual
+            ),
+            Some(ReplayDivergence {
+                frame, expected, ..
+            }) => write!(
+                f,
+                "replay diverged at frame {}: expected state {:#018x}, no state was reported",
+                frame, expected
+            ),
+        }
+    }
+}
+
+/// Records input and state checkpoints for a later [`ReplaySession`].
+#[derive(Debug, Default)]
+pub struct ReplayRecorder {
+    frame: u32,
+    checkpoints: BTreeMap<u32, u64>,
+}
+
+impl ReplayRecorder {
+    /// Sets `list` as the recording target, resets the automation base frame and starts recording.
+    pub fn start(rl: &RaylibHandle, list: &mut AutomationEventList) -> Self {
+        rl.set_automation_event_list(list);
+        rl.set_automation_event_base_frame(0);
+        rl.start_automation_event_recording();
+        Self::default()
+    }
+
+    /// The current frame, counted from the start of the recording
+    #[inline]
+    #[must_use]
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Stores the state hash for the current frame.
+    #[inline]
+    pub fn checkpoint(&mut self, hash: u64) {
+        self.checkpoints.insert(self.frame, hash);
+    }
+
+    /// Advances to the next frame. Call once per frame after drawing.
+    #[inline]
+    pub fn end_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Stops recording and returns a session that replays `list` against the recorded checkpoints.
+    #[must_use]
+    pub fn finish(self, rl: &RaylibHandle, list: &AutomationEventList) -> ReplaySession {
+        rl.stop_automation_event_recording();
+        ReplaySession::new(list.events(), self.checkpoints)
+    }
+}
+
+/// Plays automation events back frame by frame and compares state hashes at checkpoints.
+///
+/// ```ignore
+/// let mut session = ReplaySession::from_list(&list, checkpoints);
+/// while !session.is_finished() {
+///     session.play_frame();
+///     game.update(FIXED_DT, &rl);
+///     session.checkpoint(state_hash(&game));
+///     game.draw(&mut rl, &thread);
+///     session.end_frame();
+/// }
+/// assert!(session.report().is_ok(), "{}", session.report());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplaySession {
+    events: Vec<AutomationEvent>,
+    next_event: usize,
+    frame: u32,
+    checkpoints: BTreeMap<u32, u64>,
+    checked_this_frame: bool,
+    checkpoints_checked: usize,
+    first_divergence: Option<ReplayDivergence>,
+}
+
+impl ReplaySession {
+    /// Creates a session from events and `(frame, hash)` checkpoints. Events are ordered by frame.
+    #[must_use]
+    pub fn new(
+        mut events: Vec<AutomationEvent>,
+        checkpoints: impl IntoIterator<Item = (u32, u64)>,
+    ) -> Self {
+        events.sort_by_key(AutomationEvent::frame);
+        Self {
+            events,
+            next_event: 0,
+            frame: 0,
+            checkpoints: checkpoints.into_iter().collect(),
+            checked_this_frame: false,
+            checkpoints_checked: 0,
+            first_divergence: None,
+        }
+    }
+
+    /// Creates a session from the events in `list`.
+    #[must_use]
+    pub fn from_list(
+        list: &AutomationEventList,
+        checkpoints: impl IntoIterator<Item = (u32, u64)>,
+    ) -> Self {
+        Self::new(list.events(), checkpoints)
+    }
+
+    /// The current frame, counted from the start of the replay
+    #[inline]
+    #[must_use]
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// The recorded `(frame, hash)` checkpoints
+    #[inline]
+    #[must_use]
+    pub fn checkpoints(&self) -> &BTreeMap<u32, u64> {
+        &self.checkpoints
+    }
+
+    /// True once every event has been played and every checkpoint has passed
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        let last_checkpoint = self.checkpoints.keys().next_back().copied();
+        self.next_event >= self.events.len() && last_checkpoint.is_none_or(|f| f < self.frame)
+    }
+
+    /// Plays every event recorded for the current frame. Call at the start of the frame, before the game reads input.
+    pub fn play_frame(&mut self) {
+        while let Some(event) = self.events.get(self.next_event) {
+            if event.frame() > self.frame {
+                break;
+            }
+            event.play();
+            self.next_event += 1;
+        }
+    }
+
+    /// Compares `hash` against the checkpoint for the current frame, if there is one.
+    /// Returns false if the state diverged.
+    pub fn checkpoint(&mut self, hash: u64) -> bool {
+        let Some(&expected) = self.checkpoints.get(&self.frame) else {
+            return true;
+        };
+        if self.checked_this_frame {
+            return self.first_divergence.is_none_or(|d| d.frame != self.frame);
+        }
+        self.checked_this_frame = true;
+        self.checkpoints_checked += 1;
+        if expected != hash {
+            self.diverge(expected, Some(hash));
+            return false;
+        }
+        true
+    }
+
+    /// Advances to the next frame. A checkpoint on this frame that was never reported counts as a divergence.
+    pub fn end_frame(&mut self) {
+        if !self.checked_this_frame {
+            if let Some(&expected) = self.checkpoints.get(&self.frame) {
+                self.diverge(expected, None);
+            }
+        }
+        self.checked_this_frame = false;
+        self.frame += 1;
+    }
+
+    /// Runs the whole replay. `frame` is called once per frame after that frame's events were played and returns
+    /// the state hash for checkpoint frames (or every frame, extra hashes are ignored).
+    pub fn run(&mut self, mut frame: impl FnMut(u32) -> Option<u64>) -> ReplayReport {
+        while !self.is_finished() {
+            self.play_frame();
+            if let Some(hash) = frame(self.frame) {
+                self.checkpoint(hash);
+            }
+            self.end_frame();
+        }
+        self.report()
+    }
+
+    /// Summary of the replay so far
+    #[must_use]
+    pub fn report(&self) -> ReplayReport {
+        ReplayReport {
+            frames: self.frame,
+            checkpoints_checked: self.checkpoints_checked,
+            first_divergence: self.first_divergence,
+        }
+    }
+
+    fn diverge(&mut self, expected: u64, actual: Option<u64>) {
+        if self.first_divergence.is_none() {
+            self.first_divergence = Some(ReplayDivergence {
+                frame: self.frame,
+                expected,
+                actual,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod replay_test {
+    use super::*;
+
+    #[test]
+    fn test_state_hash_is_stable() {
+        assert_eq!(state_hash(&()), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(state_hash(&1u32), 0xad2a_ca77_4798_5764);
+        assert_eq!(state_hash(&7usize), 0x4bd7_a317_074c_5b62);
+        // length prefix as a 64-bit integer, then the bytes
+        assert_eq!(state_hash(&[1u8, 2][..]), 0x9b19_e1d3_2794_4d90);
+        assert_eq!(state_hash(&(0x0102u16, 5isize)), 0xaac2_9f5a_722e_5fa9);
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let mut session = ReplaySession::new(Vec::new(), [(2, 20), (5, 50), (7, 70)]);
+        let report = session.run(|frame| Some(if frame == 5 { 0 } else { frame as u64 * 10 }));
+        assert_eq!(report.frames, 8);
+        assert_eq!(report.checkpoints_checked, 3);
+        assert_eq!(
+            report.first_divergence,
+            Some(ReplayDivergence {
+                frame: 5,
+                expected: 50,
+                actual: Some(0),
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_checkpoint() {
+        let mut session = ReplaySession::new(Vec::new(), [(1, 10)]);
+        let report = session.run(|_| None);
+        assert!(!report.is_ok());
+        assert_eq!(report.first_divergence.unwrap().actual, None);
+    }
+}
@@ -0,0 +1,570 @@
+//! Safe wrappers around the rlgl immediate-mode layer.
+//!
+//! Every guard in this module can only be created from something that implements [`RaylibDraw`], so rlgl
+//! calls are always issued while a frame (and its render batch) is active.
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+
+use crate::core::drawing::{RaylibDraw, RaylibDraw3D};
+use crate::core::math::Matrix;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::ffi;
+use crate::{MintVec2, MintVec3};
+
+make_thin_wrapper!(
+    /// A custom rlgl render batch. Unloaded with `rlUnloadRenderBatch` when dropped.
+    RlglRenderBatch,
+    ffi::rlRenderBatch,
+    ffi::rlUnloadRenderBatch
+);
+make_thin_wrapper!(
+    /// A vertex array object (VAO). Unloaded with `rlUnloadVertexArray` when dropped.
+    RlglVertexArray,
+    u32,
+    ffi::rlUnloadVertexArray
+);
+make_thin_wrapper!(
+    /// A vertex or element buffer object (VBO/EBO). Unloaded with `rlUnloadVertexBuffer` when dropped.
+    RlglVertexBuffer,
+    u32,
+    ffi::rlUnloadVertexBuffer
+);
+
+/// Primitive type used by [`RaylibRlglExt::begin_rl_primitive`]
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RlglDrawMode {
+    Lines = ffi::RL_LINES as i32,
+    Triangles = ffi::RL_TRIANGLES as i32,
+    Quads = ffi::RL_QUADS as i32,
+}
+
+impl RaylibHandle {
+    /// Load a new render batch with `num_buffers` buffers of `buffer_elements` quads each.
+    #[inline]
+    #[must_use]
+    pub fn load_render_batch(
+        &mut self,
+        _: &RaylibThread,
+        num_buffers: i32,
+        buffer_elements: i32,
+    ) -> RlglRenderBatch {
+        RlglRenderBatch(unsafe { ffi::rlLoadRenderBatch(num_buffers, buffer_elements) })
+    }
+
+    /// Load a vertex array (VAO). Returns `None` if VAOs are not supported by the current OpenGL version.
+    #[inline]
+    #[must_use]
+    pub fn load_vertex_array(&mut self, _: &RaylibThread) -> Option<RlglVertexArray> {
+        let id = unsafe { ffi::rlLoadVertexArray() };
+        if id == 0 {
+            return None;
+        }
+        Some(RlglVertexArray(id))
+    }
+
+    /// Load a vertex buffer (VBO) from `data`. Set `dynamic` if the buffer will be updated often.
+    #[inline]
+    #[must_use]
+    pub fn load_vertex_buffer<T: Copy>(
+        &mut self,
+        _: &RaylibThread,
+        data: &[T],
+        dynamic: bool,
+    ) -> RlglVertexBuffer {
+        RlglVertexBuffer(unsafe {
+            ffi::rlLoadVertexBuffer(
+                data.as_ptr() as *const c_void,
+                std::mem::size_of_val(data) as i32,
+                dynamic,
+            )
+        })
+    }
+
+    /// Load an element buffer (EBO) from `indices`. Set `dynamic` if the buffer will be updated often.
+    #[inline]
+    #[must_use]
+    pub fn load_vertex_buffer_element<T: Copy>(
+        &mut self,
+        _: &RaylibThread,
+        indices: &[T],
+        dynamic: bool,
+    ) -> RlglVertexBuffer {
+        RlglVertexBuffer(unsafe {
+            ffi::rlLoadVertexBufferElement(
+                indices.as_ptr() as *const c_void,
+                std::mem::size_of_val(indices) as i32,
+                dynamic,
+            )
+        })
+    }
+}
+
+impl RlglVertexBuffer {
+    /// Update the buffer contents starting at `offset` bytes.
+    #[inline]
+    pub fn update<T: Copy>(&mut self, data: &[T], offset: i32) {
+        unsafe {
+            ffi::rlUpdateVertexBuffer(
+                self.0,
+                data.as_ptr() as *const c_void,
+                std::mem::size_of_val(data) as i32,
+                offset,
+            );
+        }
+    }
+}
+
+// Matrix scope
+
+/// A pushed rlgl matrix. Transformations applied through this guard are undone with `rlPopMatrix` when it is dropped.
+pub struct RlglMatrixScope<'a, T: 'a>(&'a mut T);
+
+impl<'a, T: 'a> Drop for RlglMatrixScope<'a, T> {
+    fn drop(&mut self) {
+        unsafe { ffi::rlPopMatrix() }
+    }
+}
+impl<'a, T: 'a> std::ops::Deref for RlglMatrixScope<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<'a, T: 'a> std::ops::DerefMut for RlglMatrixScope<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0
+    }
+}
+
+impl<'a, T: 'a> RlglMatrixScope<'a, T> {
+    /// Reset the current matrix to identity.
+    #[inline]
+    pub fn load_identity(&mut self) {
+        unsafe { ffi::rlLoadIdentity() }
+    }
+
+    /// Multiply the current matrix by a translation matrix.
+    #[inline]
+    pub fn translate(&mut self, x: f32, y: f32, z: f32) {
+        unsafe { ffi::rlTranslatef(x, y, z) }
+    }
+
+    /// Multiply the current matrix by a rotation matrix. `angle` is in degrees.
+    #[inline]
+    pub fn rotate(&mut self, angle: f32, axis: impl Into<MintVec3>) {
+        let axis = axis.into();
+        unsafe { ffi::rlRotatef(angle, axis.x, axis.y, axis.z) }
+    }
+
+    /// Multiply the current matrix by a scaling matrix.
+    #[inline]
+    pub fn scale(&mut self, x: f32, y: f32, z: f32) {
+        unsafe { ffi::rlScalef(x, y, z) }
+    }
+
+    /// Multiply the current matrix by another matrix.
+    #[inline]
+    pub fn mult_matrix(&mut self, mat: impl Into<Matrix>) {
+        let m = mat.into().to_array();
+        unsafe { ffi::rlMultMatrixf(m.as_ptr()) }
+    }
+}
+
+impl<'a, T: 'a + RaylibDraw> RaylibDraw for RlglMatrixScope<'a, T> {}
+impl<'a, T: 'a + RaylibDraw3D> RaylibDraw3D for RlglMatrixScope<'a, T> {}
+
+// Primitive
+
+/// An open `rlBegin` block. Only vertex data can be submitted until the guard is dropped, which calls `rlEnd`.
+pub struct RlglPrimitive<'a, 'b, T: 'a>(
+    PhantomData<&'a mut T>,
+    PhantomData<&'b ffi::Texture2D>,
+    bool,
+);
+
+impl<'a, 'b, T: 'a> Drop for RlglPrimitive<'a, 'b, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rlEnd();
+            if self.2 {
+                ffi::rlSetTexture(0);
+            }
+        }
+    }
+}
+
+impl<'a, 'b, T: 'a> RlglPrimitive<'a, 'b, T> {
+    /// Define one vertex (position) with 2 int components.
+    #[inline]
+    pub fn vertex2i(&mut self, x: i32, y: i32) {
+        unsafe { ffi::rlVertex2i(x, y) }
+    }
+
+    /// Define one vertex (position) with 2 float components.
+    #[inline]
+    pub fn vertex2(&mut self, position: impl Into<MintVec2>) {
+        let v = position.into();
+        unsafe { ffi::rlVertex2f(v.x, v.y) }
+    }
+
+    /// Define one vertex (position) with 3 float components.
+    #[inline]
+    pub fn vertex3(&mut self, position: impl Into<MintVec3>) {
+        let v = position.into();
+        unsafe { ffi::rlVertex3f(v.x, v.y, v.z) }
+    }
+
+    /// Define one vertex (texture coordinate). Must be called before the vertex it belongs to.
+    #[inline]
+    pub fn tex_coord(&mut self, texcoord: impl Into<MintVec2>) {
+        let v = texcoord.into();
+        unsafe { ffi::rlTexCoord2f(v.x, v.y) }
+    }
+
+    /// Define one vertex (normal). Must be called before the vertex it belongs to.
+    #[inline]
+    pub fn normal(&mut self, normal: impl Into<MintVec3>) {
+        let v = normal.into();
+        unsafe { ffi::rlNormal3f(v.x, v.y, v.z) }
+    }
+
+    /// Define one vertex (color). Must be called before the vertex it belongs to.
+    #[inline]
+    pub fn color(&mut self, color: impl Into<ffi::Color>) {
+        let c = color.into();
+        unsafe { ffi::rlColor4ub(c.r, c.g, c.b, c.a) }
+    }
+}
+
+// Render batch
+
+/// A custom render batch set as the active batch. The default batch is restored (and the custom one flushed) when dropped.
+pub struct RlglRenderBatchMode<'a, 'b, T: 'a>(
+    &'a mut T,
+    PhantomData<&'b mut RlglRenderBatch>,
+    bool,
+);
+
+impl<'a, 'b, T: 'a> Drop for RlglRenderBatchMode<'a, 'b, T> {
+    fn drop(&mut self) {
+        if self.2 {
+            unsafe { ffi::rlSetRenderBatchActive(std::ptr::null_mut()) }
+        }
+    }
+}
+impl<'a, 'b, T: 'a> std::ops::Deref for RlglRenderBatchMode<'a, 'b, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<'a, 'b, T: 'a> std::ops::DerefMut for RlglRenderBatchMode<'a, 'b, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0
+    }
+}
+impl<'a, 'b, T: 'a + RaylibDraw> RaylibDraw for RlglRenderBatchMode<'a, 'b, T> {}
+impl<'a, 'b, T: 'a + RaylibDraw3D> RaylibDraw3D for RlglRenderBatchMode<'a, 'b, T> {}
+
+// Vertex array
+
+/// A bound vertex array. `rlDisableVertexArray` is called when dropped.
+pub struct RlglVertexArrayMode<'a, 'b, T: 'a>(&'a mut T, PhantomData<&'b RlglVertexArray>);
+
+impl<'a, 'b, T: 'a> Drop for RlglVertexArrayMode<'a, 'b, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rlDisableVertexBuffer();
+            ffi::rlDisableVertexBufferElement();
+            ffi::rlDisableVertexArray();
+        }
+    }
+}
+
+impl<'a, 'b, T: 'a> RlglVertexArrayMode<'a, 'b, T> {
+    /// Bind `buffer` and describe one of its vertex attributes. `ty` is a GL type such as [`ffi::RL_FLOAT`].
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn set_vertex_attribute(
+        &mut self,
+        buffer: &RlglVertexBuffer,
+        index: u32,
+        comp_size: i32,
+        ty: u32,
+        normalized: bool,
+        stride: i32,
+        offset: i32,
+    ) {
+        unsafe {
+            ffi::rlEnableVertexBuffer(buffer.0);
+            ffi::rlSetVertexAttribute(index, comp_size, ty as i32, normalized, stride, offset);
+        }
+    }
+
+    /// Enable a vertex attribute previously described with [`Self::set_vertex_attribute`].
+    #[inline]
+    pub fn enable_vertex_attribute(&mut self, index: u32) {
+        unsafe { ffi::rlEnableVertexAttribute(index) }
+    }
+
+    /// Disable a vertex attribute.
+    #[inline]
+    pub fn disable_vertex_attribute(&mut self, index: u32) {
+        unsafe { ffi::rlDisableVertexAttribute(index) }
+    }
+
+    /// Bind an element buffer to this vertex array.
+    #[inline]
+    pub fn set_element_buffer(&mut self, buffer: &RlglVertexBuffer) {
+        unsafe { ffi::rlEnableVertexBufferElement(buffer.0) }
+    }
+
+    /// Draw `count` vertices starting at `offset`.
+    #[inline]
+    pub fn draw(&mut self, offset: i32, count: i32) {
+        unsafe { ffi::rlDrawVertexArray(offset, count) }
+    }
+
+    /// Draw `count` indices from the bound element buffer starting at `offset`.
+    #[inline]
+    pub fn draw_elements(&mut self, offset: i32, count: i32) {
+        unsafe { ffi::rlDrawVertexArrayElements(offset, count, std::ptr::null()) }
+    }
+
+    /// Draw `count` vertices starting at `offset`, `instances` times.
+    #[inline]
+    pub fn draw_instanced(&mut self, offset: i32, count: i32, instances: i32) {
+        unsafe { ffi::rlDrawVertexArrayInstanced(offset, count, instances) }
+    }
+}
+
+pub trait RaylibRlglExt
+where
+    Self: Sized,
+{
+    /// Push the current matrix to the stack.
+    /// Prefer using the closure version, [RaylibRlglExt::draw_rl_matrix]. This version returns a handle that calls [raylib_sys::rlPopMatrix] at the end of the scope and is provided as a fallback incase you run into issues with closures(such as lifetime or performance reasons)
+    #[inline]
+    #[must_use]
+    fn begin_rl_matrix(&mut self) -> RlglMatrixScope<'_, Self> {
+        unsafe { ffi::rlPushMatrix() }
+        RlglMatrixScope(self)
+    }
+
+    /// Push the current matrix to the stack.
+    fn draw_rl_matrix<'a>(&'a mut self, func: impl FnOnce(RlglMatrixScope<'a, Self>)) {
+        unsafe { ffi::rlPushMatrix() }
+        func(RlglMatrixScope(self));
+    }
+
+    /// Start drawing a primitive with `rlBegin`. Makes sure the render batch has room for `vertex_count` vertices first.
+    /// Prefer using the closure version, [RaylibRlglExt::draw_rl_primitive]. This version returns a handle that calls [raylib_sys::rlEnd] at the end of the scope and is provided as a fallback incase you run into issues with closures(such as lifetime or performance reasons)
+    #[inline]
+    #[must_use]
+    fn begin_rl_primitive(
+        &mut self,
+        mode: RlglDrawMode,
+        vertex_count: i32,
+    ) -> RlglPrimitive<'_, 'static, Self> {
+        unsafe {
+            ffi::rlCheckRenderBatchLimit(vertex_count);
+            ffi::rlBegin(mode as i32);
+        }
+        RlglPrimitive(PhantomData, PhantomData, false)
+    }
+
+    /// Start drawing a primitive with `rlBegin`. Makes sure the render batch has room for `vertex_count` vertices first.
+    fn draw_rl_primitive<'a>(
+        &'a mut self,
+        mode: RlglDrawMode,
+        vertex_count: i32,
+        func: impl FnOnce(RlglPrimitive<'a, 'static, Self>),
+    ) {
+        unsafe {
+            ffi::rlCheckRenderBatchLimit(vertex_count);
+            ffi::rlBegin(mode as i32);
+        }
+        func(RlglPrimitive(PhantomData, PhantomData, false));
+    }
+
+    /// Start drawing a textured primitive. The texture is unset again when the returned guard is dropped.
+    #[inline]
+    #[must_use]
+    fn begin_rl_primitive_textured<'a, 'b>(
+        &'a mut self,
+        mode: RlglDrawMode,
+        vertex_count: i32,
+        texture: &'b impl AsRef<ffi::Texture2D>,
+    ) -> RlglPrimitive<'a, 'b, Self> {
+        unsafe {
+            ffi::rlCheckRenderBatchLimit(vertex_count);
+            ffi::rlSetTexture(texture.as_ref().id);
+            ffi::rlBegin(mode as i32);
+        }
+        RlglPrimitive(PhantomData, PhantomData, true)
+    }
+
+    /// Make `batch` the active render batch until the returned guard is dropped.
+    /// Prefer using the closure version, [RaylibRlglExt::draw_rl_render_batch]. This version returns a handle that restores the default batch at the end of the scope and is provided as a fallback incase you run into issues with closures(such as lifetime or performance reasons)
+    /// # Safety
+    /// raylib keeps a pointer to `batch` until the guard is dropped. The guard must not be leaked (e.g. with
+    /// [`std::mem::forget`]), or `batch` could be moved or unloaded while it is still the active batch.
+    #[inline]
+    #[must_use]
+    unsafe fn begin_rl_render_batch<'a, 'b>(
+        &'a mut self,
+        batch: &'b mut RlglRenderBatch,
+    ) -> RlglRenderBatchMode<'a, 'b, Self> {
+        unsafe { ffi::rlSetRenderBatchActive(&mut batch.0) }
+        RlglRenderBatchMode(self, PhantomData, true)
+    }
+
+    /// Make `batch` the active render batch for the duration of `func`. The default batch is restored when
+    /// `func` returns, even if the guard was leaked.
+    fn draw_rl_render_batch<'a, 'b>(
+        &'a mut self,
+        batch: &'b mut RlglRenderBatch,
+        func: impl FnOnce(RlglRenderBatchMode<'a, 'b, Self>),
+    ) {
+        unsafe { ffi::rlSetRenderBatchActive(&mut batch.0) }
+        func(RlglRenderBatchMode(self, PhantomData, false));
+        unsafe { ffi::rlSetRenderBatchActive(std::ptr::null_mut()) }
+    }
+
+    /// Bind a vertex array. Returns `None` if the vertex array could not be enabled.
+    #[inline]
+    #[must_use]
+    fn begin_rl_vertex_array<'a, 'b>(
+        &'a mut self,
+        vao: &'b RlglVertexArray,
+    ) -> Option<RlglVertexArrayMode<'a, 'b, Self>> {
+        if unsafe { ffi::rlEnableVertexArray(vao.0) } {
+            Some(RlglVertexArrayMode(self, PhantomData))
+        } else {
+            None
+        }
+    }
+
+    /// Flush the active render batch (draw and reset).
+    #[inline]
+    fn rl_draw_render_batch_active(&mut self) {
+        unsafe { ffi::rlDrawRenderBatchActive() }
+    }
+
+    /// Enable depth testing. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_enable_depth_test(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlEnableDepthTest();
+        }
+    }
+
+    /// Disable depth testing. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_disable_depth_test(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlDisableDepthTest();
+        }
+    }
+
+    /// Enable depth writes. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_enable_depth_mask(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlEnableDepthMask();
+        }
+    }
+
+    /// Disable depth writes. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_disable_depth_mask(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlDisableDepthMask();
+        }
+    }
+
+    /// Enable backface culling. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_enable_backface_culling(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlEnableBackfaceCulling();
+        }
+    }
+
+    /// Disable backface culling. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_disable_backface_culling(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlDisableBackfaceCulling();
+        }
+    }
+
+    /// Set which face is culled when backface culling is enabled.
+    #[inline]
+    fn rl_set_cull_face(&mut self, mode: ffi::rlCullMode) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlSetCullFace(mode as i32);
+        }
+    }
+
+    /// Enable wireframe rendering. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_enable_wire_mode(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlEnableWireMode();
+        }
+    }
+
+    /// Disable wireframe (and point) rendering. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_disable_wire_mode(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlDisableWireMode();
+        }
+    }
+
+    /// Enable color blending. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_enable_color_blend(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlEnableColorBlend();
+        }
+    }
+
+    /// Disable color blending. Flushes the render batch so previously queued vertices are unaffected.
+    #[inline]
+    fn rl_disable_color_blend(&mut self) {
+        unsafe {
+            ffi::rlDrawRenderBatchActive();
+            ffi::rlDisableColorBlend();
+        }
+    }
+
+    /// Set the line drawing width.
+    #[inline]
+    fn rl_set_line_width(&mut self, width: f32) {
+        unsafe { ffi::rlSetLineWidth(width) }
+    }
+
+    /// Get the line drawing width.
+    #[inline]
+    #[must_use]
+    fn rl_get_line_width(&self) -> f32 {
+        unsafe { ffi::rlGetLineWidth() }
+    }
+}
+
+impl<D: RaylibDraw> RaylibRlglExt for D {}
@@ -0,0 +1,314 @@
+//! Signed distance field fonts.
+//!
+//! An SDF atlas stores the distance to the glyph outline instead of coverage, so one small atlas stays sharp at
+//! any scale or [`Camera2D`] zoom and can be outlined, blurred or shadowed in the shader. [`Font::load_sdf`]
+//! loads the glyphs with `FONT_SDF` and bundles the font with the shader that draws it, and
+//! [`RaylibDraw::draw_text_sdf`] draws with it.
+//! ```ignore
+//! let font = Font::load_sdf(&thread, "resources/anonymous_pro_bold.ttf", 32, None)?;
+//! let style = SdfTextStyle {
+//!     outline_width: 1.5,
+//!     outline_color: Color::BLACK,
+//!     shadow: Some(SdfShadow::default()),
+//!     ..Default::default()
+//! };
+//! d.draw_text_sdf(&font, "Game Over", Vector2::new(100.0, 100.0), 128.0, 0.0, Color::RED, &style);
+//! ```
+//!
+//! [`Camera2D`]: crate::core::camera::Camera2D
+//! [`RaylibDraw::draw_text_sdf`]: crate::core::drawing::RaylibDraw::draw_text_sdf
+use std::collections::HashSet;
+use std::ffi::CString;
+
+use crate::consts::TextureFilter;
+use crate::core::RaylibThread;
+use crate::core::color::Color;
+use crate::core::math::Vector2;
+use crate::core::shaders::{RaylibShader, Shader, compiled_shader};
+use crate::core::text::{Font, RaylibFont};
+use crate::error::LoadFontError;
+use crate::ffi;
+
+/// raylib's `FONT_SDF_PIXEL_DIST_SCALE`: one pixel at the base size changes the stored distance by 64 / 255
+const DISTANCE_PER_PIXEL: f32 = 64.0 / 255.0;
+
+#[cfg(not(any(
+    feature = "opengl_es_20",
+    feature = "opengl_es_30",
+    target_os = "android",
+    target_family = "wasm"
+)))]
+const SDF_FRAGMENT_SHADER: &str = r#"#version 330
+in vec2 fragTexCoord;
+in vec4 fragColor;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+uniform vec4 outlineColor;
+uniform float outlineWidth;
+uniform float softness;
+out vec4 finalColor;
+void main()
+{
+    float distance = texture(texture0, fragTexCoord).a - 0.5;
+    float smoothing = length(vec2(dFdx(distance), dFdy(distance))) + softness;
+    float fill = smoothstep(-smoothing, smoothing, distance);
+    float outline = smoothstep(-smoothing, smoothing, distance + outlineWidth);
+    vec4 color = mix(outlineColor, fragColor*colDiffuse, fill);
+    finalColor = vec4(color.rgb, color.a*outline);
+}
+"#;
+
+#[cfg(feature = "opengl_es_30")]
+const SDF_FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+in vec2 fragTexCoord;
+in vec4 fragColor;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+uniform vec4 outlineColor;
+uniform float outlineWidth;
+uniform float softness;
+out vec4 finalColor;
+void main()
+{
+    float distance = texture(texture0, fragTexCoord).a - 0.5;
+    float smoothing = length(vec2(dFdx(distance), dFdy(distance))) + softness;
+    float fill = smoothstep(-smoothing, smoothing, distance);
+    float outline = smoothstep(-smoothing, smoothing, distance + outlineWidth);
+    vec4 color = mix(outlineColor, fragColor*colDiffuse, fill);
+    finalColor = vec4(color.rgb, color.a*outline);
+}
+"#;
+
+#[cfg(all(
+    not(feature = "opengl_es_30"),
+    any(
+        feature = "opengl_es_20",
+        target_os = "android",
+        target_family = "wasm"
+    )
+))]
+const SDF_FRAGMENT_SHADER: &str = r#"#version 100
+#extension GL_OES_standard_derivatives : enable
+precision mediump float;
+varying vec2 fragTexCoord;
+varying vec4 fragColor;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+uniform vec4 outlineColor;
+uniform float outlineWidth;
+uniform float softness;
+void main()
+{
+    float distance = texture2D(texture0, fragTexCoord).a - 0.5;
+    float smoothing = length(vec2(dFdx(distance), dFdy(distance))) + softness;
+    float fill = smoothstep(-smoothing, smoothing, distance);
+    float outline = smoothstep(-smoothing, smoothing, distance + outlineWidth);
+    vec4 color = mix(outlineColor, fragColor*colDiffuse, fill);
+    gl_FragColor = vec4(color.rgb, color.a*outline);
+}
+"#;
+
+/// Drop shadow of [`SdfTextStyle`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfShadow {
+    /// Offset in screen pixels at the drawn font size
+    pub offset: Vector2,
+    pub color: Color,
+    /// Blur in pixels at the font's base size
+    pub softness: f32,
+}
+
+impl Default for SdfShadow {
+    fn default() -> Self {
+        Self {
+            offset: Vector2::new(2.0, 2.0),
+            color: Color::new(0, 0, 0, 128),
+            softness: 0.5,
+        }
+    }
+}
+
+/// Effects for [`RaylibDraw::draw_text_sdf`](crate::core::drawing::RaylibDraw::draw_text_sdf). Widths are in
+/// pixels at the font's base size and scale with the drawn size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfTextStyle {
+    /// The distance field only reaches about 2 pixels outside the glyphs, wider outlines are cut off there
+    pub outline_width: f32,
+    pub outline_color: Color,
+    /// Extra blur of the edges, 0 for crisp text
+    pub softness: f32,
+    pub shadow: Option<SdfShadow>,
+}
+
+impl Default for SdfTextStyle {
+    fn default() -> Self {
+        Self {
+            outline_width: 0.0,
+            outline_color: Color::BLACK,
+            softness: 0.0,
+            shadow: None,
+        }
+    }
+}
+
+/// A font with a signed distance field atlas and the shader that draws it. Drawing it with the regular text
+/// functions shows the raw distance field, use `draw_text_sdf` instead.
+#[derive(Debug)]
+pub struct SdfFont {
+    font: Font,
+    shader: Shader,
+    outline_color_loc: i32,
+    outline_width_loc: i32,
+    softness_loc: i32,
+}
+
+impl Font {
+    /// Loads an SDF font from a TTF/OTF file with one glyph per distinct character of `chars`. Supplying `None`
+    /// for `chars` loads ASCII 32 to 126.
+    ///
+    /// `font_size` is the size glyphs are rasterized at; SDF text stays sharp far above it, so 32 to 64 is
+    /// usually plenty.
+    pub fn load_sdf(
+        _: &RaylibThread,
+        filename: &str,
+        font_size: i32,
+        chars: Option<&str>,
+    ) -> Result<SdfFont, LoadFontError> {
+        let error = || LoadFontError::LoadFromFileFailed {
+            path: filename.into(),
+        };
+        let mut codepoints: Vec<i32> = match chars {
+            Some(chars) => {
+                let mut seen = HashSet::new();
+                chars
+                    .chars()
+                    .filter(|c| seen.insert(*c))
+                    .map(|c| c as i32)
+                    .collect()
+            }
+            None => (32..127).collect(),
+        };
+        let c_filename = CString::new(filename).unwrap();
+        let font = unsafe {
+            let mut size = 0;
+            let data = ffi::LoadFileData(c_filename.as_ptr(), &mut size);
+            if data.is_null() {
+                return Err(error());
+            }
+            let glyphs = ffi::LoadFontData(
+                data,
+                size,
+                font_size,
+                codepoints.as_mut_ptr(),
+                codepoints.len() as i32,
+                ffi::FontType::FONT_SDF as i32,
+            );
+            ffi::UnloadFileData(data);
+            if glyphs.is_null() {
+                return Err(error());
+            }
+
+            let mut font: ffi::Font = std::mem::zeroed();
+            font.baseSize = font_size;
+            font.glyphCount = codepoints.len() as i32;
+            font.glyphs = glyphs;
+            // the distance field already has its own padding around each glyph
+            let atlas = ffi::GenImageFontAtlas(
+                font.glyphs,
+                &mut font.recs,
+                font.glyphCount,
+                font.baseSize,
+                0,
+                1,
+            );
+            font.texture = ffi::LoadTextureFromImage(atlas);
+            ffi::UnloadImage(atlas);
+            Font(font)
+        };
+        if font.texture.id == 0 {
+            return Err(error());
+        }
+        unsafe {
+            ffi::SetTextureFilter(font.texture, TextureFilter::TEXTURE_FILTER_BILINEAR as i32)
+        };
+
+        let c_shader = CString::new(SDF_FRAGMENT_SHADER).unwrap();
+        let shader = compiled_shader(unsafe {
+            ffi::LoadShaderFromMemory(std::ptr::null(), c_shader.as_ptr())
+        })
+        .ok_or(LoadFontError::SdfShaderFailed)?;
+        Ok(SdfFont {
+            outline_color_loc: shader.get_shader_location("outlineColor"),
+            outline_width_loc: shader.get_shader_location("outlineWidth"),
+            softness_loc: shader.get_shader_location("softness"),
+            font,
+            shader,
+        })
+    }
+}
+
+impl SdfFont {
+    #[inline]
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    /// The shader `draw_text_sdf` draws with, for drawing SDF text in other ways such as a `TextLayout`
+    #[inline]
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    /// Sets the shader uniforms for one pass
+    pub(crate) fn set_style(&self, outline_width: f32, outline_color: Color, softness: f32) {
+        let outline_width = (outline_width * DISTANCE_PER_PIXEL).clamp(0.0, 0.5);
+        let softness = softness.max(0.0) * DISTANCE_PER_PIXEL;
+        let outline_color = [
+            outline_color.r as f32 / 255.0,
+            outline_color.g as f32 / 255.0,
+            outline_color.b as f32 / 255.0,
+            outline_color.a as f32 / 255.0,
+        ];
+        unsafe {
+            ffi::SetShaderValue(
+                self.shader.0,
+                self.outline_width_loc,
+                &outline_width as *const f32 as *const _,
+                ffi::ShaderUniformDataType::SHADER_UNIFORM_FLOAT as i32,
+            );
+            ffi::SetShaderValue(
+                self.shader.0,
+                self.softness_loc,
+                &softness as *const f32 as *const _,
+                ffi::ShaderUniformDataType::SHADER_UNIFORM_FLOAT as i32,
+            );
+            ffi::SetShaderValue(
+                self.shader.0,
+                self.outline_color_loc,
+                outline_color.as_ptr() as *const _,
+                ffi::ShaderUniformDataType::SHADER_UNIFORM_VEC4 as i32,
+            );
+        }
+    }
+}
+
+impl AsRef<ffi::Font> for SdfFont {
+    fn as_ref(&self) -> &ffi::Font {
+        &self.font.0
+    }
+}
+
+impl AsMut<ffi::Font> for SdfFont {
+    fn as_mut(&mut self) -> &mut ffi::Font {
+        &mut self.font.0
+    }
+}
+
+impl AsRef<ffi::Shader> for SdfFont {
+    fn as_ref(&self) -> &ffi::Shader {
+        &self.shader.0
+    }
+}
+
+impl RaylibFont for SdfFont {}
@@ -4,39 +4,78 @@ use crate::consts::ShaderUniformDataType;
 use crate::core::math::Matrix;
 use crate::core::math::{Vector2, Vector3, Vector4};
 use crate::core::{RaylibHandle, RaylibThread};
+use crate::error::{LoadShaderError, ShaderUniformError};
 use crate::{ffi, MintMatrix};
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::os::raw::c_void;
 
+pub use raylib_derive::ShaderUniforms;
+
 fn no_drop<T>(_thing: T) {}
 make_thin_wrapper!(Shader, ffi::Shader, ffi::UnloadShader);
 make_thin_wrapper!(WeakShader, ffi::Shader, no_drop);
 
+/// Wraps a shader loaded from at least one custom stage, or returns `None` if it failed to compile or link and
+/// raylib fell back to its default shader.
+pub(crate) fn compiled_shader(shader: ffi::Shader) -> Option<Shader> {
+    let default_id = unsafe { ffi::rlGetShaderIdDefault() };
+    if shader.id != 0 && shader.id != default_id {
+        return Some(Shader(shader));
+    }
+    // the fallback still gets its own locs array, which UnloadShader skips for the default program
+    if shader.id == default_id && !shader.locs.is_null() {
+        unsafe { ffi::MemFree(shader.locs as *mut c_void) };
+    }
+    None
+}
+
 // #[cfg(feature = "nightly")]
 // impl !Send for Shader {}
 // #[cfg(feature = "nightly")]
 // unsafe impl Sync for Shader {}
 
 impl RaylibHandle {
-    #[must_use]
-    /// Loads a custom shader and binds default locations.
+    /// Loads a custom shader and binds default locations. A `None` stage uses raylib's default for it, and
+    /// without any file this returns the default shader.
+    /// Fails if a given file can't be read or the program doesn't compile or link, where raylib itself would
+    /// silently fall back to its default shader.
     pub fn load_shader(
         &mut self,
         _: &RaylibThread,
         vs_filename: Option<&str>,
         fs_filename: Option<&str>,
-    ) -> Shader {
-        let c_vs_filename = vs_filename.map(|f| CString::new(f).unwrap());
-        let c_fs_filename = fs_filename.map(|f| CString::new(f).unwrap());
+    ) -> Result<Shader, LoadShaderError> {
+        // read the files here rather than in LoadShader, which replaces a missing file with the default stage
+        let read = |path: &str| -> Result<CString, LoadShaderError> {
+            let c_path = CString::new(path).unwrap();
+            let text = unsafe { ffi::LoadFileText(c_path.as_ptr()) };
+            if text.is_null() {
+                return Err(LoadShaderError::ReadFailed {
+                    path: path.to_owned(),
+                });
+            }
+            let code = unsafe { CStr::from_ptr(text) }.to_owned();
+            unsafe { ffi::UnloadFileText(text) };
+            Ok(code)
+        };
+        let c_vs_code = vs_filename.map(read).transpose()?;
+        let c_fs_code = fs_filename.map(read).transpose()?;
 
-        let vs = c_vs_filename
+        let vs = c_vs_code
             .as_ref()
             .map_or_else(std::ptr::null, |s| s.as_ptr());
-        let fs = c_fs_filename
+        let fs = c_fs_code
             .as_ref()
             .map_or_else(std::ptr::null, |s| s.as_ptr());
 
-        Shader(unsafe { ffi::LoadShader(vs, fs) })
+        let shader = unsafe { ffi::LoadShaderFromMemory(vs, fs) };
+        if vs_filename.is_none() && fs_filename.is_none() {
+            return Ok(Shader(shader));
+        }
+        compiled_shader(shader).ok_or_else(|| LoadShaderError::LoadFailed {
+            vs: vs_filename.map(str::to_owned),
+            fs: fs_filename.map(str::to_owned),
+        })
     }
 
     #[must_use]
@@ -344,3 +383,106 @@ pub trait RaylibShader: AsRef<ffi::Shader> + AsMut<ffi::Shader> {
         }
     }
 }
+
+/// A value that can be uploaded to a single shader uniform.
+pub trait UniformValue {
+    fn set_uniform(&self, shader: &mut impl RaylibShader, uniform_loc: i32);
+}
+
+impl<S: ShaderV + Copy> UniformValue for S {
+    #[inline]
+    fn set_uniform(&self, shader: &mut impl RaylibShader, uniform_loc: i32) {
+        shader.set_shader_value(uniform_loc, *self);
+    }
+}
+
+impl UniformValue for Matrix {
+    #[inline]
+    fn set_uniform(&self, shader: &mut impl RaylibShader, uniform_loc: i32) {
+        shader.set_shader_value_matrix(uniform_loc, *self);
+    }
+}
+
+/// A set of uniforms uploaded together. Derive it with `#[derive(ShaderUniforms)]`:
+/// ```ignore
+/// #[derive(ShaderUniforms)]
+/// struct Lighting {
+///     #[uniform(name = "lightPos")]
+///     light_pos: Vector3,
+///     ambient: Vector4,
+///     count: i32,
+/// }
+///
+/// let mut lighting = Lighting { .. }.bind(&shader)?;
+/// lighting.count = 2;
+/// lighting.apply(&mut shader);
+/// ```
+pub trait ShaderUniforms: Sized {
+    /// Uniform names, in upload order
+    const UNIFORM_NAMES: &'static [&'static str];
+
+    /// Uploads every value to the location at the same index as its name in [ShaderUniforms::UNIFORM_NAMES].
+    fn upload(&self, shader: &mut impl RaylibShader, locations: &[i32]);
+
+    /// Looks up every uniform location in `shader`. Fails with all names the shader doesn't have; note that
+    /// GLSL compilers remove uniforms that are declared but never used.
+    fn bind(self, shader: &impl RaylibShader) -> Result<BoundUniforms<Self>, ShaderUniformError> {
+        let locations: Vec<i32> = Self::UNIFORM_NAMES
+            .iter()
+            .map(|name| shader.get_shader_location(name))
+            .collect();
+        let missing: Vec<String> = Self::UNIFORM_NAMES
+            .iter()
+            .zip(&locations)
+            .filter(|(_, loc)| **loc < 0)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(ShaderUniformError::MissingUniforms { names: missing });
+        }
+        Ok(BoundUniforms {
+            values: self,
+            locations,
+        })
+    }
+}
+
+/// Uniform values together with their locations in one shader. Derefs to the values.
+#[derive(Debug, Clone)]
+pub struct BoundUniforms<U> {
+    values: U,
+    locations: Vec<i32>,
+}
+
+impl<U: ShaderUniforms> BoundUniforms<U> {
+    /// Uploads all values to `shader`, which must be the shader they were bound to.
+    #[inline]
+    pub fn apply(&self, shader: &mut impl RaylibShader) {
+        self.values.upload(shader, &self.locations);
+    }
+
+    /// Locations in the order of [ShaderUniforms::UNIFORM_NAMES]
+    #[inline]
+    pub fn locations(&self) -> &[i32] {
+        &self.locations
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> U {
+        self.values
+    }
+}
+
+impl<U> std::ops::Deref for BoundUniforms<U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        &self.values
+    }
+}
+
+impl<U> std::ops::DerefMut for BoundUniforms<U> {
+    fn deref_mut(&mut self) -> &mut U {
+        &mut self.values
+    }
+}
@@ -0,0 +1,392 @@
+//! Bidirectional and complex-script text shaping, behind the `shaping` feature.
+//!
+//! raylib draws codepoints one after another from left to right, which breaks right-to-left scripts and scripts
+//! whose glyphs depend on their neighbours, like Arabic joining, Devanagari conjuncts and combining marks.
+//! [`ShapingFont::shape`] splits each line into left-to-right and right-to-left runs with [`unicode_bidi`],
+//! shapes every run with [`rustybuzz`] and returns the glyphs in visual order. Shaped glyphs often have no
+//! codepoint of their own, so the font rasterizes them by glyph id into its atlas and files them under private
+//! use codepoints (see [`glyph_codepoint`]), which keeps it a regular [`RaylibFont`].
+//! ```ignore
+//! let mut font = rl.load_shaping_font(&thread, "resources/noto_sans_arabic.ttf", 32)?;
+//! let text = font.shape(&thread, "Score: 120 نقطة");
+//! d.draw_shaped_text(&font, &text, Vector2::new(10.0, 10.0), 32.0, Color::WHITE);
+//! ```
+use std::collections::HashMap;
+use std::ops::Range;
+
+use ab_glyph_rasterizer::{Point, Rasterizer, point};
+use rustybuzz::ttf_parser::{self, GlyphId, OutlineBuilder};
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+
+use crate::core::dynamic_font::{AtlasFont, GLYPH_PADDING};
+use crate::core::math::Vector2;
+use crate::core::text::RaylibFont;
+use crate::core::{RaylibHandle, RaylibThread};
+use crate::error::LoadFontError;
+use crate::ffi;
+use crate::ffi::Rectangle;
+
+/// Start of Supplementary Private Use Area-A, where glyph ids are mapped to codepoints
+const GLYPH_CODEPOINT_BASE: u32 = 0xF0000;
+
+/// Codepoint a [`ShapingFont`] stores glyph `glyph_id` under
+#[inline]
+pub fn glyph_codepoint(glyph_id: u16) -> char {
+    char::from_u32(GLYPH_CODEPOINT_BASE + glyph_id as u32).unwrap()
+}
+
+/// A glyph placed by the shaper
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    pub glyph_id: u16,
+    /// [`glyph_codepoint`] of the glyph, for drawing and [`RaylibFont`] lookups
+    pub codepoint: char,
+    /// Byte index in the shaped text of the cluster the glyph belongs to
+    pub cluster: usize,
+    /// Pen position in pixels at the font's base size, relative to the top left corner of the text, with the
+    /// shaper's offsets applied
+    pub position: Vector2,
+    pub advance: f32,
+}
+
+/// Glyphs of one direction run, in visual order
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+    /// Byte range in the shaped text
+    pub text: Range<usize>,
+    pub rtl: bool,
+    pub line: usize,
+    pub glyphs: Vec<ShapedGlyph>,
+}
+
+/// Output of [`ShapingFont::shape`], drawn with `RaylibDraw::draw_shaped_text`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShapedText {
+    /// Runs of each line from left to right, lines from top to bottom
+    pub runs: Vec<GlyphRun>,
+    /// Size in pixels at the font's base size
+    pub size: Vector2,
+}
+
+impl ShapedText {
+    /// All glyphs in drawing order
+    pub fn glyphs(&self) -> impl Iterator<Item = &ShapedGlyph> {
+        self.runs.iter().flat_map(|r| r.glyphs.iter())
+    }
+}
+
+/// Splits `text` into lines at paragraph separators and each line into direction runs in visual order.
+/// Returns the byte range, whether it's right-to-left and the line of every run.
+fn visual_runs(text: &str) -> Vec<(Range<usize>, bool, usize)> {
+    let bidi = BidiInfo::new(text, None);
+    let mut runs = Vec::new();
+    for (line, paragraph) in bidi.paragraphs.iter().enumerate() {
+        let content =
+            text[paragraph.range.clone()].trim_end_matches(['\n', '\r', '\u{85}', '\u{2029}']);
+        let range = paragraph.range.start..paragraph.range.start + content.len();
+        let (levels, line_runs) = bidi.visual_runs(paragraph, range);
+        runs.extend(
+            line_runs
+                .into_iter()
+                .filter(|run| !run.is_empty())
+                .map(|run| (run.clone(), levels[run.start].is_rtl(), line)),
+        );
+    }
+    runs
+}
+
+/// Pixel scale for a font `size` like raylib's, which is the distance from the lowest descender to the highest
+/// ascender
+fn font_scale(face: &ttf_parser::Face, size: f32) -> f32 {
+    let height = face.ascender() as i32 - face.descender() as i32;
+    size / if height > 0 {
+        height as f32
+    } else {
+        face.units_per_em() as f32
+    }
+}
+
+fn shape_text(face: &Face, text: &str, size: f32) -> ShapedText {
+    let scale = font_scale(face, size);
+    let mut shaped = ShapedText::default();
+    let mut pen = Vector2::ZERO;
+    let mut current_line = 0;
+    for (range, rtl, line) in visual_runs(text) {
+        if line != current_line {
+            pen = Vector2::new(0.0, line as f32 * size);
+            current_line = line;
+        }
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(&text[range.clone()]);
+        buffer.set_direction(if rtl {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        });
+        buffer.guess_segment_properties();
+        let output = rustybuzz::shape(face, &[], buffer);
+
+        let glyphs = output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, position)| {
+                let glyph_id = info.glyph_id as u16;
+                let glyph = ShapedGlyph {
+                    glyph_id,
+                    codepoint: glyph_codepoint(glyph_id),
+                    cluster: range.start + info.cluster as usize,
+                    position: pen
+                        + Vector2::new(position.x_offset as f32, -(position.y_offset as f32))
+                            * scale,
+                    advance: position.x_advance as f32 * scale,
+                };
+                pen.x += glyph.advance;
+                glyph
+            })
+            .collect();
+        shaped.size.x = shaped.size.x.max(pen.x);
+        shaped.runs.push(GlyphRun {
+            text: range,
+            rtl,
+            line,
+            glyphs,
+        });
+    }
+    shaped.size.y = (current_line + 1) as f32 * size;
+    shaped
+}
+
+/// Feeds a glyph outline into the rasterizer, flipped to y down and moved to the glyph's pixel box
+struct Outline {
+    rasterizer: Rasterizer,
+    scale: f32,
+    origin: Vector2,
+    start: Point,
+    last: Point,
+}
+
+impl Outline {
+    fn point(&self, x: f32, y: f32) -> Point {
+        point(
+            x * self.scale - self.origin.x,
+            -y * self.scale - self.origin.y,
+        )
+    }
+}
+
+impl OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.point(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.rasterizer.draw_line(self.last, p);
+        self.last = p;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p1, p) = (self.point(x1, y1), self.point(x, y));
+        self.rasterizer.draw_quad(self.last, p1, p);
+        self.last = p;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p1, p2, p) = (self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+        self.rasterizer.draw_cubic(self.last, p1, p2, p);
+        self.last = p;
+    }
+
+    fn close(&mut self) {
+        if self.last != self.start {
+            self.rasterizer.draw_line(self.last, self.start);
+        }
+        self.last = self.start;
+    }
+}
+
+/// Glyph info with raylib's conventions for offsets, plus the size and 8-bit coverage of the glyph image
+fn rasterize_glyph(
+    face: &ttf_parser::Face,
+    glyph_id: u16,
+    size: f32,
+) -> (ffi::GlyphInfo, i32, i32, Vec<u8>) {
+    let scale = font_scale(face, size);
+    let id = GlyphId(glyph_id);
+    let mut glyph: ffi::GlyphInfo = unsafe { std::mem::zeroed() };
+    glyph.value = glyph_codepoint(glyph_id) as i32;
+    glyph.advanceX = (face.glyph_hor_advance(id).unwrap_or(0) as f32 * scale).round() as i32;
+    let Some(bounds) = face.glyph_bounding_box(id) else {
+        return (glyph, 0, 0, Vec::new());
+    };
+    let x0 = (bounds.x_min as f32 * scale).floor();
+    let y0 = (-(bounds.y_max as f32) * scale).floor();
+    let width = (bounds.x_max as f32 * scale).ceil() - x0;
+    let height = (-(bounds.y_min as f32) * scale).ceil() - y0;
+    // the top of the line is the ascender, like stb_truetype's offsets in raylib
+    glyph.offsetX = x0 as i32;
+    glyph.offsetY = (face.ascender() as f32 * scale).round() as i32 + y0 as i32;
+
+    let mut outline = Outline {
+        rasterizer: Rasterizer::new(width as usize, height as usize),
+        scale,
+        origin: Vector2::new(x0, y0),
+        start: point(0.0, 0.0),
+        last: point(0.0, 0.0),
+    };
+    face.outline_glyph(id, &mut outline);
+    let mut coverage = vec![0; (width * height) as usize];
+    outline
+        .rasterizer
+        .for_each_pixel(|i, alpha| coverage[i] = (alpha.clamp(0.0, 1.0) * 255.0).round() as u8);
+    (glyph, width as i32, height as i32, coverage)
+}
+
+/// Font that shapes text with [`rustybuzz`] and rasterizes the shaped glyphs on demand. See the
+/// [module documentation](self).
+pub struct ShapingFont {
+    inner: AtlasFont,
+    cached: HashMap<u16, usize>,
+}
+
+impl std::fmt::Debug for ShapingFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShapingFont")
+            .field("font", &self.inner.font)
+            .field("glyph_count", &self.inner.glyphs.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl_atlas_font!(ShapingFont, inner);
+
+impl RaylibFont for ShapingFont {}
+
+impl RaylibHandle {
+    /// Loads a TTF/OTF font for shaped text at `font_size` pixels. The file is read through raylib, so custom
+    /// file loaders apply.
+    pub fn load_shaping_font(
+        &mut self,
+        thread: &RaylibThread,
+        filename: &str,
+        font_size: i32,
+    ) -> Result<ShapingFont, LoadFontError> {
+        let font = AtlasFont::new(AtlasFont::read_file(filename)?, font_size, 64)?;
+        ShapingFont::new(thread, font).ok_or_else(|| LoadFontError::LoadFromFileFailed {
+            path: filename.into(),
+        })
+    }
+
+    /// Like [`RaylibHandle::load_shaping_font`] with the contents of a TTF/OTF file
+    pub fn load_shaping_font_from_memory(
+        &mut self,
+        thread: &RaylibThread,
+        file_data: impl Into<Vec<u8>>,
+        font_size: i32,
+    ) -> Result<ShapingFont, LoadFontError> {
+        let font = AtlasFont::new(file_data.into(), font_size, 64)?;
+        ShapingFont::new(thread, font).ok_or(LoadFontError::LoadFromMemoryFailed)
+    }
+}
+
+impl ShapingFont {
+    fn new(thread: &RaylibThread, inner: AtlasFont) -> Option<Self> {
+        Face::from_slice(&inner.file_data, 0)?;
+        let mut font = ShapingFont {
+            inner,
+            cached: HashMap::new(),
+        };
+        // glyph 0 is the font's missing glyph box and the first glyph raylib falls back to
+        font.cache_glyphs(thread, [0]);
+        (font.inner.font.texture.id != 0).then_some(font)
+    }
+
+    /// TTF/OTF data the text is shaped and rasterized from
+    #[inline]
+    pub fn file_data(&self) -> &[u8] {
+        &self.inner.file_data
+    }
+
+    /// Shapes `text` and rasterizes the glyphs it needs. Lines break at `'\n'` and are `base_size` apart.
+    pub fn shape(&mut self, thread: &RaylibThread, text: &str) -> ShapedText {
+        let face = Face::from_slice(&self.inner.file_data, 0).unwrap();
+        let shaped = shape_text(&face, text, self.inner.font.baseSize as f32);
+        self.cache_glyphs(thread, shaped.glyphs().map(|g| g.glyph_id));
+        shaped
+    }
+
+    fn cache_glyphs(&mut self, _: &RaylibThread, glyph_ids: impl IntoIterator<Item = u16>) {
+        let face = Face::from_slice(&self.inner.file_data, 0).unwrap();
+        let mut dirty = Vec::new();
+        for glyph_id in glyph_ids {
+            if self.cached.contains_key(&glyph_id) {
+                continue;
+            }
+            let (glyph, width, height, coverage) =
+                rasterize_glyph(&face, glyph_id, self.inner.font.baseSize as f32);
+            let Some((x, y)) = self
+                .inner
+                .atlas
+                .allocate(width + 2 * GLYPH_PADDING, height + 2 * GLYPH_PADDING)
+            else {
+                continue;
+            };
+            let (x, y) = (x + GLYPH_PADDING, y + GLYPH_PADDING);
+            self.inner.atlas.blit(x, y, width, &coverage);
+            self.cached.insert(glyph_id, self.inner.glyphs.len());
+            self.inner.glyphs.push(glyph);
+            self.inner.recs.push(Rectangle::new(
+                x as f32,
+                y as f32,
+                width as f32,
+                height as f32,
+            ));
+            dirty.push(Rectangle::new(
+                (x - GLYPH_PADDING) as f32,
+                (y - GLYPH_PADDING) as f32,
+                (width + 2 * GLYPH_PADDING) as f32,
+                (height + 2 * GLYPH_PADDING) as f32,
+            ));
+        }
+        if dirty.is_empty() && self.inner.font.texture.id != 0 {
+            return;
+        }
+        self.inner.upload(&dirty);
+    }
+}
+
+#[cfg(test)]
+mod shaping_test {
+    use super::*;
+
+    fn run_texts(text: &str) -> Vec<(&str, bool, usize)> {
+        visual_runs(text)
+            .into_iter()
+            .map(|(range, rtl, line)| (&text[range], rtl, line))
+            .collect()
+    }
+
+    #[test]
+    fn test_visual_runs() {
+        assert_eq!(run_texts("hello"), [("hello", false, 0)]);
+        assert_eq!(
+            run_texts("abc שלום def"),
+            [("abc ", false, 0), ("שלום", true, 0), (" def", false, 0)]
+        );
+        // a right-to-left paragraph puts its runs right to left
+        assert_eq!(
+            run_texts("שלום abc\nxyz"),
+            [("abc", false, 0), ("שלום ", true, 0), ("xyz", false, 1)]
+        );
+        assert!(run_texts("").is_empty());
+    }
+
+    #[test]
+    fn test_glyph_codepoint() {
+        assert_eq!(glyph_codepoint(0), '\u{F0000}');
+        assert_eq!(glyph_codepoint(u16::MAX) as u32, 0xF0000 + 0xFFFF);
+    }
+}
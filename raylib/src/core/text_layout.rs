@@ -0,0 +1,629 @@
+//! Multi-line rich text layout.
+//!
+//! [`TextLayout`] places the glyphs of a list of [`TextSpan`]s, each with its own font, size, color and underline,
+//! word-wrapped to a width, aligned and clipped to a rectangle. Glyph metrics are the font's glyph info and atlas
+//! rectangles, the same data [`RaylibFont::get_glyph_info`] and [`RaylibFont::get_glyph_atlas_rec`] return, so a
+//! single line is placed exactly like [`RaylibDraw::draw_text_ex`] places it.
+//! ```ignore
+//! let layout = TextLayout::new(
+//!     vec![
+//!         TextSpan::new("Press ", &font, 20.0, Color::WHITE),
+//!         TextSpan::new("E", &bold, 24.0, Color::GOLD).underline(true),
+//!         TextSpan::new(" to talk to the innkeeper.", &font, 20.0, Color::WHITE),
+//!     ],
+//!     LayoutOptions {
+//!         max_width: Some(300.0),
+//!         align: TextAlign::Justify,
+//!         ..Default::default()
+//!     },
+//! );
+//! d.draw_text_layout(&layout, Vector2::new(20.0, 400.0));
+//! let caret = layout.caret_rect(layout.hit_test(mouse - Vector2::new(20.0, 400.0)));
+//! ```
+//!
+//! [`RaylibDraw::draw_text_ex`]: crate::core::drawing::RaylibDraw::draw_text_ex
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::core::color::Color;
+use crate::core::math::Vector2;
+use crate::core::text::RaylibFont;
+use crate::ffi;
+use crate::ffi::Rectangle;
+
+/// A run of text sharing one font and style
+#[derive(Debug, Clone)]
+pub struct TextSpan<'a> {
+    pub text: Cow<'a, str>,
+    pub font: &'a ffi::Font,
+    /// Font size in pixels
+    pub size: f32,
+    /// Extra space after every glyph, like the `spacing` of `draw_text_ex`
+    pub spacing: f32,
+    pub color: Color,
+    pub underline: bool,
+}
+
+impl<'a> TextSpan<'a> {
+    /// Span without spacing or underline
+    pub fn new(
+        text: impl Into<Cow<'a, str>>,
+        font: &'a impl RaylibFont,
+        size: f32,
+        color: impl Into<Color>,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            font: font.as_ref(),
+            size,
+            spacing: 0.0,
+            color: color.into(),
+            underline: false,
+        }
+    }
+
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+}
+
+/// Horizontal alignment of the lines of a [`TextLayout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretch the spaces of wrapped lines to fill the width. The last line of a paragraph is left aligned.
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LayoutOptions {
+    /// Wrap lines at word boundaries to fit this width. Words longer than a line are broken between characters.
+    /// With `None` lines only break at `'\n'` and are aligned to the widest line.
+    pub max_width: Option<f32>,
+    pub align: TextAlign,
+    /// Glyphs are trimmed to this rectangle, relative to the layout origin
+    pub clip: Option<Rectangle>,
+    /// Extra space between lines
+    pub line_spacing: f32,
+}
+
+/// One character of a [`TextLayout`]. Positions are relative to the layout origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutGlyph {
+    pub codepoint: char,
+    /// Byte index of the character in [`TextLayout::text`]
+    pub index: usize,
+    /// Index of the span the character comes from
+    pub span: usize,
+    pub line: usize,
+    /// Top left corner of the character cell
+    pub position: Vector2,
+    /// Distance to the next character, spacing and justification included
+    pub advance: f32,
+    /// Rectangle in the font atlas
+    pub source: Rectangle,
+    /// Where `source` is drawn, after clipping
+    pub dest: Rectangle,
+    /// False for whitespace, control characters and glyphs clipped away entirely
+    pub visible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLine {
+    /// Byte range in [`TextLayout::text`], without the line break
+    pub text: Range<usize>,
+    /// Range in [`TextLayout::glyphs`], with trailing whitespace and the line break
+    pub glyphs: Range<usize>,
+    /// Left edge after alignment and top edge
+    pub position: Vector2,
+    /// Width without trailing whitespace
+    pub width: f32,
+    /// Size of the largest span on the line. Glyphs of smaller spans sit on its bottom edge.
+    pub height: f32,
+}
+
+/// Laid out [`TextSpan`]s, ready for drawing with `RaylibDraw::draw_text_layout` and for hit testing.
+#[derive(Debug, Clone)]
+pub struct TextLayout<'a> {
+    spans: Vec<TextSpan<'a>>,
+    text: String,
+    glyphs: Vec<LayoutGlyph>,
+    lines: Vec<LayoutLine>,
+    underlines: Vec<(Rectangle, Color)>,
+    size: Vector2,
+    line_spacing: f32,
+}
+
+/// Same lookup as raylib's `GetGlyphIndex`: the glyph for `codepoint`, else `'?'`, else the first glyph
+fn glyph_index(font: &ffi::Font, codepoint: char) -> Option<usize> {
+    if font.glyphs.is_null() || font.recs.is_null() || font.glyphCount <= 0 {
+        return None;
+    }
+    let glyphs = unsafe { std::slice::from_raw_parts(font.glyphs, font.glyphCount as usize) };
+    glyphs
+        .iter()
+        .position(|g| g.value == codepoint as i32)
+        .or_else(|| glyphs.iter().rposition(|g| g.value == '?' as i32))
+        .or(Some(0))
+}
+
+/// Unspaced advance, padded atlas rectangle and destination relative to the cell, as in `DrawTextCodepoint`
+fn glyph_metrics(font: &ffi::Font, codepoint: char, size: f32) -> (f32, Rectangle, Rectangle) {
+    let Some(index) = glyph_index(font, codepoint) else {
+        return Default::default();
+    };
+    let (glyph, rec) = unsafe { (*font.glyphs.add(index), *font.recs.add(index)) };
+    let scale = if font.baseSize > 0 {
+        size / font.baseSize as f32
+    } else {
+        1.0
+    };
+    let padding = font.glyphPadding as f32;
+    let advance = if glyph.advanceX == 0 {
+        rec.width * scale
+    } else {
+        glyph.advanceX as f32 * scale
+    };
+    let source = Rectangle::new(
+        rec.x - padding,
+        rec.y - padding,
+        rec.width + 2.0 * padding,
+        rec.height + 2.0 * padding,
+    );
+    let dest = Rectangle::new(
+        (glyph.offsetX as f32 - padding) * scale,
+        (glyph.offsetY as f32 - padding) * scale,
+        source.width * scale,
+        source.height * scale,
+    );
+    (advance, source, dest)
+}
+
+fn intersect(a: Rectangle, b: Rectangle) -> Option<Rectangle> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let width = (a.x + a.width).min(b.x + b.width) - x;
+    let height = (a.y + a.height).min(b.y + b.height) - y;
+    (width > 0.0 && height > 0.0).then(|| Rectangle::new(x, y, width, height))
+}
+
+/// Trims `dest` to `clip` and `source` by the same proportion. Returns false if nothing is left.
+fn clip_glyph(source: &mut Rectangle, dest: &mut Rectangle, clip: Rectangle) -> bool {
+    let Some(clipped) = intersect(*dest, clip) else {
+        return false;
+    };
+    let sx = source.width / dest.width;
+    let sy = source.height / dest.height;
+    *source = Rectangle::new(
+        source.x + (clipped.x - dest.x) * sx,
+        source.y + (clipped.y - dest.y) * sy,
+        clipped.width * sx,
+        clipped.height * sy,
+    );
+    *dest = clipped;
+    true
+}
+
+impl<'a> TextLayout<'a> {
+    pub fn new(spans: Vec<TextSpan<'a>>, options: LayoutOptions) -> Self {
+        let mut text = String::new();
+        let mut glyphs = Vec::new();
+        for (s, span) in spans.iter().enumerate() {
+            for (i, codepoint) in span.text.char_indices() {
+                let (advance, source, dest) = if codepoint == '\n' {
+                    Default::default()
+                } else {
+                    glyph_metrics(span.font, codepoint, span.size)
+                };
+                glyphs.push(LayoutGlyph {
+                    codepoint,
+                    index: text.len() + i,
+                    span: s,
+                    line: 0,
+                    position: Vector2::ZERO,
+                    advance: advance + if codepoint == '\n' { 0.0 } else { span.spacing },
+                    source,
+                    dest,
+                    visible: !codepoint.is_whitespace()
+                        && !codepoint.is_control()
+                        && dest.width > 0.0
+                        && dest.height > 0.0,
+                });
+            }
+            text.push_str(&span.text);
+        }
+
+        let spacing = |g: &LayoutGlyph| {
+            if g.codepoint == '\n' {
+                0.0
+            } else {
+                spans[g.span].spacing
+            }
+        };
+        let mut lines = Vec::new();
+        for (range, paragraph_end) in break_lines(&glyphs, options.max_width, spacing) {
+            let line = &glyphs[range.clone()];
+            // characters up to the last non-whitespace one
+            let content = line
+                .iter()
+                .rposition(|g| !g.codepoint.is_whitespace())
+                .map_or(0, |p| p + 1);
+            let width = line[..content].iter().map(|g| g.advance).sum::<f32>()
+                - line[..content].last().map_or(0.0, spacing);
+            let height = match line.iter().map(|g| spans[g.span].size).reduce(f32::max) {
+                Some(height) => height,
+                None => glyphs.last().map_or_else(
+                    || spans.first().map_or(0.0, |s| s.size),
+                    |g| spans[g.span].size,
+                ),
+            };
+            let text_start = line.first().map_or(text.len(), |g| g.index);
+            let text_end = match line.last() {
+                Some(g) if g.codepoint == '\n' => g.index,
+                _ => glyphs.get(range.end).map_or(text.len(), |g| g.index),
+            };
+            let line = LayoutLine {
+                text: text_start..text_end,
+                glyphs: range,
+                position: Vector2::ZERO,
+                width,
+                height,
+            };
+            lines.push((line, content, paragraph_end));
+        }
+
+        let widest = lines.iter().map(|(l, ..)| l.width).fold(0.0, f32::max);
+        let available = options.max_width.unwrap_or(widest);
+        let mut underlines = Vec::new();
+        let mut y = 0.0;
+        for (l, (line, content, paragraph_end)) in lines.iter_mut().enumerate() {
+            let (range, height) = (line.glyphs.clone(), line.height);
+            let (content, paragraph_end) = (*content, *paragraph_end);
+            let free = (available - line.width).max(0.0);
+            let x = match options.align {
+                TextAlign::Left | TextAlign::Justify => 0.0,
+                TextAlign::Center => free / 2.0,
+                TextAlign::Right => free,
+            };
+            let interior = &glyphs[range.start..range.start + content];
+            let spaces = interior
+                .iter()
+                .filter(|g| g.codepoint.is_whitespace())
+                .count();
+            let stretch = if options.align == TextAlign::Justify && !paragraph_end && spaces > 0 {
+                free / spaces as f32
+            } else {
+                0.0
+            };
+
+            let mut cursor = x;
+            for (i, glyph) in glyphs[range.clone()].iter_mut().enumerate() {
+                if i < content && glyph.codepoint.is_whitespace() {
+                    glyph.advance += stretch;
+                }
+                glyph.line = l;
+                glyph.position = Vector2::new(cursor, y + height - spans[glyph.span].size);
+                glyph.dest.x += glyph.position.x;
+                glyph.dest.y += glyph.position.y;
+                if glyph.visible
+                    && let Some(clip) = options.clip
+                {
+                    glyph.visible = clip_glyph(&mut glyph.source, &mut glyph.dest, clip);
+                }
+                cursor += glyph.advance;
+            }
+
+            let mut start = 0;
+            let interior = &glyphs[range.start..range.start + content];
+            while start < interior.len() {
+                let span = &spans[interior[start].span];
+                let run = interior[start..]
+                    .iter()
+                    .take_while(|g| {
+                        spans[g.span].underline == span.underline
+                            && spans[g.span].color == span.color
+                    })
+                    .count();
+                if span.underline {
+                    let first = &interior[start];
+                    let last = &interior[start + run - 1];
+                    let size = interior[start..start + run]
+                        .iter()
+                        .map(|g| spans[g.span].size)
+                        .fold(0.0, f32::max);
+                    let thickness = (size / 16.0).max(1.0);
+                    let rec = Rectangle::new(
+                        first.position.x,
+                        y + height - thickness,
+                        last.position.x + last.advance - spacing(last) - first.position.x,
+                        thickness,
+                    );
+                    if let Some(rec) = options.clip.map_or(Some(rec), |clip| intersect(rec, clip)) {
+                        underlines.push((rec, span.color));
+                    }
+                }
+                start += run;
+            }
+
+            line.position = Vector2::new(x, y);
+            y += height + options.line_spacing;
+        }
+
+        Self {
+            spans,
+            text,
+            glyphs,
+            lines: lines.into_iter().map(|(line, ..)| line).collect(),
+            underlines,
+            size: Vector2::new(widest, y - options.line_spacing),
+            line_spacing: options.line_spacing,
+        }
+    }
+
+    #[inline]
+    pub fn spans(&self) -> &[TextSpan<'a>] {
+        &self.spans
+    }
+
+    /// Text of all spans joined together
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// One glyph per character of [`TextLayout::text`], in order
+    #[inline]
+    pub fn glyphs(&self) -> &[LayoutGlyph] {
+        &self.glyphs
+    }
+
+    /// Always at least one line, even for empty text
+    #[inline]
+    pub fn lines(&self) -> &[LayoutLine] {
+        &self.lines
+    }
+
+    /// Underline rectangles of underlined spans, after clipping
+    #[inline]
+    pub fn underlines(&self) -> &[(Rectangle, Color)] {
+        &self.underlines
+    }
+
+    /// Width of the widest line and height of all lines
+    #[inline]
+    pub fn size(&self) -> Vector2 {
+        self.size
+    }
+
+    /// Byte index in [`TextLayout::text`] of the caret position closest to `point`, relative to the layout
+    /// origin. Points above or below the text hit the first or last line.
+    pub fn hit_test(&self, point: impl Into<Vector2>) -> usize {
+        let point = point.into();
+        let line = self
+            .lines
+            .iter()
+            .find(|l| point.y < l.position.y + l.height + self.line_spacing)
+            .unwrap_or_else(|| self.lines.last().unwrap());
+        self.glyphs[line.glyphs.clone()]
+            .iter()
+            .take_while(|g| g.index < line.text.end)
+            .find(|g| point.x < g.position.x + g.advance / 2.0)
+            .map_or(line.text.end, |g| g.index)
+    }
+
+    /// One pixel wide caret in front of the character at byte `index`, or after the text if `index` is past the
+    /// end. A caret at the end of a wrapped line is drawn at the start of the next one.
+    pub fn caret_rect(&self, index: usize) -> Rectangle {
+        let next = self.glyphs.partition_point(|g| g.index < index);
+        let (x, line) = match self.glyphs.get(next) {
+            Some(glyph) => (glyph.position.x, &self.lines[glyph.line]),
+            None => {
+                let line = self.lines.last().unwrap();
+                let end = self.glyphs[line.glyphs.clone()]
+                    .last()
+                    .map_or(line.position.x, |g| g.position.x + g.advance);
+                (end, line)
+            }
+        };
+        Rectangle::new(x, line.position.y, 1.0, line.height)
+    }
+}
+
+/// Greedy word wrap. Returns the glyph range of every line, with trailing whitespace and the `'\n'`, and whether
+/// the line ends a paragraph.
+fn break_lines(
+    glyphs: &[LayoutGlyph],
+    max_width: Option<f32>,
+    spacing: impl Fn(&LayoutGlyph) -> f32,
+) -> Vec<(Range<usize>, bool)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    let mut x = 0.0;
+    let mut break_at = None;
+    while i < glyphs.len() {
+        let glyph = &glyphs[i];
+        if glyph.codepoint == '\n' {
+            lines.push((start..i + 1, true));
+            (start, i, x, break_at) = (i + 1, i + 1, 0.0, None);
+            continue;
+        }
+        if glyph.codepoint.is_whitespace() {
+            break_at = Some(i + 1);
+        } else if let Some(max_width) = max_width
+            && i > start
+            && x + glyph.advance - spacing(glyph) > max_width
+        {
+            let end = break_at.unwrap_or(i);
+            lines.push((start..end, false));
+            (start, i, x, break_at) = (end, end, 0.0, None);
+            continue;
+        }
+        x += glyph.advance;
+        i += 1;
+    }
+    lines.push((start..glyphs.len(), true));
+    lines
+}
+
+#[cfg(test)]
+mod text_layout_test {
+    use super::*;
+    use crate::core::text::WeakFont;
+
+    /// Monospace font with a base size of 10 and 10 pixel wide glyphs for `' '..='~'`
+    struct TestFont {
+        font: WeakFont,
+        _glyphs: Vec<ffi::GlyphInfo>,
+        _recs: Vec<Rectangle>,
+    }
+
+    fn test_font() -> TestFont {
+        let mut glyphs: Vec<ffi::GlyphInfo> = (' '..='~')
+            .map(|c| ffi::GlyphInfo {
+                value: c as i32,
+                offsetX: 0,
+                offsetY: 0,
+                advanceX: 10,
+                image: unsafe { std::mem::zeroed() },
+            })
+            .collect();
+        let mut recs: Vec<Rectangle> = (0..glyphs.len())
+            .map(|i| Rectangle::new(i as f32 * 10.0, 0.0, 10.0, 10.0))
+            .collect();
+        let mut font: ffi::Font = unsafe { std::mem::zeroed() };
+        font.baseSize = 10;
+        font.glyphCount = glyphs.len() as i32;
+        font.glyphs = glyphs.as_mut_ptr();
+        font.recs = recs.as_mut_ptr();
+        TestFont {
+            font: WeakFont(font),
+            _glyphs: glyphs,
+            _recs: recs,
+        }
+    }
+
+    fn layout<'a>(font: &'a TestFont, text: &'a str, options: LayoutOptions) -> TextLayout<'a> {
+        TextLayout::new(
+            vec![TextSpan::new(text, &font.font, 10.0, Color::WHITE)],
+            options,
+        )
+    }
+
+    fn line_texts<'a>(layout: &'a TextLayout) -> Vec<&'a str> {
+        layout
+            .lines()
+            .iter()
+            .map(|l| &layout.text()[l.text.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let font = test_font();
+        let wrapped = LayoutOptions {
+            max_width: Some(100.0),
+            ..Default::default()
+        };
+        let l = layout(&font, "the quick brown fox\njumps", wrapped);
+        assert_eq!(line_texts(&l), ["the quick ", "brown fox", "jumps"]);
+        assert_eq!(l.lines()[0].width, 90.0);
+        assert_eq!(l.lines()[2].position.y, 20.0);
+        assert_eq!(l.size(), Vector2::new(90.0, 30.0));
+
+        let l = layout(&font, "abcdefghijklmnopqrstuvwxyz", wrapped);
+        assert_eq!(line_texts(&l), ["abcdefghij", "klmnopqrst", "uvwxyz"]);
+
+        let l = layout(&font, "ab\n", LayoutOptions::default());
+        assert_eq!(line_texts(&l), ["ab", ""]);
+        assert_eq!(l.caret_rect(3), Rectangle::new(0.0, 10.0, 1.0, 10.0));
+    }
+
+    #[test]
+    fn test_alignment() {
+        let font = test_font();
+        let options = |align| LayoutOptions {
+            max_width: Some(100.0),
+            align,
+            ..Default::default()
+        };
+        let l = layout(&font, "aaaa bbb cc", options(TextAlign::Center));
+        assert_eq!(line_texts(&l), ["aaaa bbb ", "cc"]);
+        assert_eq!(l.lines()[0].position.x, 10.0);
+        assert_eq!(l.lines()[1].position.x, 40.0);
+
+        let l = layout(&font, "aaaa bbb cc", options(TextAlign::Right));
+        assert_eq!(l.lines()[0].position.x, 20.0);
+        assert_eq!(l.glyphs()[9].position.x, 80.0);
+
+        let l = layout(&font, "aa bb cc dddd", options(TextAlign::Justify));
+        assert_eq!(line_texts(&l), ["aa bb cc ", "dddd"]);
+        // 20 free pixels over two spaces
+        assert_eq!(l.glyphs()[2].advance, 20.0);
+        assert_eq!(l.glyphs()[7].position.x, 90.0);
+        assert_eq!(l.glyphs()[9].position.x, 0.0);
+    }
+
+    #[test]
+    fn test_spans_and_clipping() {
+        let font = test_font();
+        let l = TextLayout::new(
+            vec![
+                TextSpan::new("ab", &font.font, 10.0, Color::WHITE),
+                TextSpan::new("cd", &font.font, 20.0, Color::RED)
+                    .spacing(2.0)
+                    .underline(true),
+            ],
+            LayoutOptions {
+                clip: Some(Rectangle::new(0.0, 0.0, 45.0, 100.0)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(l.lines()[0].height, 20.0);
+        assert_eq!(l.lines()[0].width, 62.0);
+        assert_eq!(l.glyphs()[0].position, Vector2::new(0.0, 10.0));
+        assert_eq!(l.glyphs()[3].position, Vector2::new(42.0, 0.0));
+
+        let c = l.glyphs()[3];
+        assert!(c.visible);
+        assert_eq!(c.dest, Rectangle::new(42.0, 0.0, 3.0, 20.0));
+        assert_eq!(c.source, Rectangle::new(680.0, 0.0, 1.5, 10.0));
+        assert!(l.glyphs()[2].visible);
+
+        let (underline, color) = l.underlines()[0];
+        assert_eq!(color, Color::RED);
+        assert_eq!(underline, Rectangle::new(20.0, 18.75, 25.0, 1.25));
+    }
+
+    #[test]
+    fn test_hit_test_and_caret() {
+        let font = test_font();
+        let l = layout(
+            &font,
+            "hello world\nbye",
+            LayoutOptions {
+                max_width: Some(80.0),
+                ..Default::default()
+            },
+        );
+        assert_eq!(line_texts(&l), ["hello ", "world", "bye"]);
+        assert_eq!(l.hit_test(Vector2::new(-5.0, -5.0)), 0);
+        assert_eq!(l.hit_test(Vector2::new(14.0, 5.0)), 1);
+        assert_eq!(l.hit_test(Vector2::new(16.0, 5.0)), 2);
+        assert_eq!(l.hit_test(Vector2::new(500.0, 15.0)), 11);
+        assert_eq!(l.hit_test(Vector2::new(500.0, 500.0)), 15);
+
+        assert_eq!(l.caret_rect(7), Rectangle::new(10.0, 10.0, 1.0, 10.0));
+        assert_eq!(l.caret_rect(11), Rectangle::new(50.0, 10.0, 1.0, 10.0));
+        assert_eq!(l.caret_rect(15), Rectangle::new(30.0, 20.0, 1.0, 10.0));
+    }
+}
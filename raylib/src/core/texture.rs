@@ -255,13 +255,11 @@ impl Image {
     pub fn from_channel(&self, selected_channel: i32) -> Image {
         unsafe { Image(ffi::ImageFromChannel(self.0, selected_channel)) }
     }
-    /// Exports image as a PNG file.
+    /// Exports image as a PNG file. Returns false if raylib could not write it.
     #[inline]
-    pub fn export_image(&self, filename: &str) {
+    pub fn export_image(&self, filename: &str) -> bool {
         let c_filename = CString::new(filename).unwrap();
-        unsafe {
-            ffi::ExportImage(self.0, c_filename.as_ptr());
-        }
+        unsafe { ffi::ExportImage(self.0, c_filename.as_ptr()) }
     }
 
     /// Exports image as a PNG file.
@@ -0,0 +1,709 @@
+//! A virtual filesystem for raylib's file loaders.
+//!
+//! [`VfsOverlay`] mounts [`Vfs`] sources under path prefixes and [`VfsOverlay::install`] routes raylib's
+//! `LoadFileData` and `LoadFileText` through them, so `load_texture`, `load_font`, `new_sound`, `load_model`,
+//! `load_shader` and everything else that goes through those two functions reads from the mounted sources.
+//! Later mounts shadow earlier ones, which lets a mod replace single files of the base game.
+//! ```ignore
+//! let mut vfs = VfsOverlay::new();
+//! vfs.mount("", ArchiveVfs::open("assets.pak")?);
+//! vfs.mount("", DirectoryVfs::new("mods/hd_textures"));
+//! let _vfs = vfs.install();
+//! let texture = rl.load_texture(&thread, "textures/player.png")?;
+//! ```
+//!
+//! Once installed raylib no longer reads from disk on its own, so mount a [`DirectoryVfs`] first if loose files
+//! should still load. Music streams open their files directly and don't go through the VFS, load them with
+//! `load_music_stream_from_memory` instead.
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::consts::TraceLogLevel;
+use crate::core::callbacks::{
+    CallbackGuard, RustLoadFileDataCallback, RustLoadFileTextCallback, set_load_file_data_callback,
+    set_load_file_text_callback,
+};
+use crate::core::logging::trace_log;
+use crate::error::{ArchiveError, VfsError};
+
+/// A read-only source of files. Paths are relative to where the source is mounted, use `/` as separator and
+/// contain no `.` or `..` segments.
+pub trait Vfs: Send + Sync {
+    /// Contents of the file at `path`, [`VfsError::NotFound`] if the source doesn't have it
+    fn read(&self, path: &str) -> Result<Vec<u8>, VfsError>;
+
+    fn exists(&self, path: &str) -> bool {
+        self.read(path).is_ok()
+    }
+}
+
+/// Resolves `.` and `..` segments and turns `path` into `/` separated segments without a leading `/`. `..`
+/// can't climb above the root.
+pub fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// Files of a directory on disk. Absolute paths and paths with `..` segments are rejected with
+/// [`VfsError::InvalidPath`], so nothing outside the directory can be read through it.
+#[derive(Debug, Clone)]
+pub struct DirectoryVfs {
+    root: PathBuf,
+}
+
+impl DirectoryVfs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `path` on disk. Joining an absolute path or one with a drive prefix would replace the root instead.
+    fn resolve(&self, path: &str) -> Result<PathBuf, VfsError> {
+        let relative = Path::new(path);
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(VfsError::InvalidPath {
+                path: path.to_owned(),
+            });
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl Vfs for DirectoryVfs {
+    fn read(&self, path: &str) -> Result<Vec<u8>, VfsError> {
+        std::fs::read(self.resolve(path)?).map_err(|source| match source.kind() {
+            std::io::ErrorKind::NotFound => VfsError::NotFound {
+                path: path.to_owned(),
+            },
+            _ => VfsError::Io {
+                path: path.to_owned(),
+                source,
+            },
+        })
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.resolve(path).is_ok_and(|path| path.is_file())
+    }
+}
+
+/// Files kept in memory, for generated assets and tests
+#[derive(Debug, Clone, Default)]
+pub struct MemoryVfs {
+    files: HashMap<String, Arc<[u8]>>,
+}
+
+impl MemoryVfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces a file
+    pub fn insert(&mut self, path: &str, data: impl Into<Arc<[u8]>>) -> &mut Self {
+        self.files.insert(normalize_path(path), data.into());
+        self
+    }
+
+    pub fn remove(&mut self, path: &str) -> Option<Arc<[u8]>> {
+        self.files.remove(&normalize_path(path))
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+}
+
+impl Vfs for MemoryVfs {
+    fn read(&self, path: &str) -> Result<Vec<u8>, VfsError> {
+        self.files
+            .get(path)
+            .map(|data| data.to_vec())
+            .ok_or_else(|| VfsError::NotFound {
+                path: path.to_owned(),
+            })
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ArchiveEntry {
+    data: Range<usize>,
+    /// Zip compression method, 0 for stored data
+    method: u16,
+    size: usize,
+}
+
+/// Files of a tar or zip archive held in memory. Entries are found when the archive is opened and decompressed
+/// when read. Zip archives with deflated entries need the `zip` feature.
+#[derive(Debug, Clone)]
+pub struct ArchiveVfs {
+    data: Arc<[u8]>,
+    entries: HashMap<String, ArchiveEntry>,
+}
+
+const TAR_BLOCK: usize = 512;
+const ZIP_LOCAL_HEADER: u32 = 0x0403_4b50;
+const ZIP_CENTRAL_HEADER: u32 = 0x0201_4b50;
+const ZIP_END_OF_DIRECTORY: u32 = 0x0605_4b50;
+const ZIP_STORED: u16 = 0;
+const ZIP_DEFLATED: u16 = 8;
+
+fn le_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// A NUL terminated tar header field
+fn tar_str(field: &[u8]) -> &[u8] {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    &field[..end]
+}
+
+/// A numeric tar header field, octal or GNU base-256
+fn tar_number(field: &[u8]) -> Option<u64> {
+    if field.first().is_some_and(|&b| b & 0x80 != 0) {
+        return Some(field[1..].iter().fold(0u64, |n, &b| (n << 8) | b as u64));
+    }
+    let digits = std::str::from_utf8(tar_str(field)).ok()?.trim();
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+/// `path` record of a pax extended header
+fn pax_path(records: &[u8]) -> Option<String> {
+    let mut rest = records;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&b| b == b' ')?;
+        let length: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..length)?.strip_suffix(b"\n")?;
+        if let Some(path) = record.strip_prefix(b"path=") {
+            return Some(String::from_utf8_lossy(path).into_owned());
+        }
+        rest = &rest[length..];
+    }
+    None
+}
+
+impl ArchiveVfs {
+    /// Reads a tar or zip archive, telling them apart by their contents
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| ArchiveError::Io {
+            path: path.to_string_lossy().into_owned(),
+            source,
+        })?;
+        Self::from_bytes(data)
+    }
+
+    /// A tar or zip archive, telling them apart by their contents
+    pub fn from_bytes(data: impl Into<Arc<[u8]>>) -> Result<Self, ArchiveError> {
+        let data = data.into();
+        if le_u32(&data, 0)
+            .is_some_and(|sig| sig == ZIP_LOCAL_HEADER || sig == ZIP_END_OF_DIRECTORY)
+        {
+            Self::from_zip(data)
+        } else {
+            Self::from_tar(data)
+        }
+    }
+
+    /// A ustar, GNU or pax tar archive. Only regular files are kept, links and directories are skipped.
+    pub fn from_tar(data: impl Into<Arc<[u8]>>) -> Result<Self, ArchiveError> {
+        let data = data.into();
+        let invalid = |message: &str| ArchiveError::Tar(message.to_string());
+        let mut entries = HashMap::new();
+        let mut long_name: Option<String> = None;
+        let mut offset = 0;
+        while offset + TAR_BLOCK <= data.len() {
+            let header = &data[offset..offset + TAR_BLOCK];
+            if header.iter().all(|&b| b == 0) {
+                break;
+            }
+            let checksum =
+                tar_number(&header[148..156]).ok_or_else(|| invalid("bad checksum field"))?;
+            let sum: u64 = header
+                .iter()
+                .enumerate()
+                .map(|(i, &b)| {
+                    if (148..156).contains(&i) {
+                        b' ' as u64
+                    } else {
+                        b as u64
+                    }
+                })
+                .sum();
+            if sum != checksum {
+                return Err(invalid("header checksum mismatch"));
+            }
+            let size =
+                tar_number(&header[124..136]).ok_or_else(|| invalid("bad size field"))? as usize;
+            let start = offset + TAR_BLOCK;
+            let end = start
+                .checked_add(size)
+                .filter(|&end| end <= data.len())
+                .ok_or_else(|| invalid("entry runs past the end"))?;
+            offset = start + size.div_ceil(TAR_BLOCK) * TAR_BLOCK;
+
+            match header[156] {
+                b'L' => {
+                    long_name =
+                        Some(String::from_utf8_lossy(tar_str(&data[start..end])).into_owned());
+                }
+                b'x' => long_name = pax_path(&data[start..end]).or(long_name),
+                b'0' | b'\0' | b'7' => {
+                    let name = long_name.take().unwrap_or_else(|| {
+                        let name = String::from_utf8_lossy(tar_str(&header[0..100]));
+                        let prefix = String::from_utf8_lossy(tar_str(&header[345..500]));
+                        if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                            format!("{prefix}/{name}")
+                        } else {
+                            name.into_owned()
+                        }
+                    });
+                    entries.insert(
+                        normalize_path(&name),
+                        ArchiveEntry {
+                            data: start..end,
+                            method: ZIP_STORED,
+                            size,
+                        },
+                    );
+                }
+                _ => long_name = None,
+            }
+        }
+        Ok(Self { data, entries })
+    }
+
+    /// A zip archive. Entries have to be stored or deflated, and unencrypted; zip64 isn't supported.
+    pub fn from_zip(data: impl Into<Arc<[u8]>>) -> Result<Self, ArchiveError> {
+        let data = data.into();
+        let invalid = |message: &str| ArchiveError::Zip(message.to_string());
+        // the end of central directory record is 22 bytes plus a comment of up to 64 KiB
+        let end = (0..=data.len().saturating_sub(22))
+            .rev()
+            .take(u16::MAX as usize + 1)
+            .find(|&at| le_u32(&data, at) == Some(ZIP_END_OF_DIRECTORY))
+            .ok_or_else(|| invalid("no end of central directory record"))?;
+        let count = le_u16(&data, end + 10).ok_or_else(|| invalid("truncated directory record"))?;
+        let directory =
+            le_u32(&data, end + 16).ok_or_else(|| invalid("truncated directory record"))?;
+        if count == u16::MAX || directory == u32::MAX {
+            return Err(invalid("zip64 archives are not supported"));
+        }
+
+        let mut entries = HashMap::new();
+        let mut at = directory as usize;
+        for _ in 0..count {
+            let truncated = || invalid("truncated central directory");
+            if le_u32(&data, at) != Some(ZIP_CENTRAL_HEADER) {
+                return Err(invalid("bad central directory header"));
+            }
+            let flags = le_u16(&data, at + 8).ok_or_else(truncated)?;
+            let method = le_u16(&data, at + 10).ok_or_else(truncated)?;
+            let compressed = le_u32(&data, at + 20).ok_or_else(truncated)? as usize;
+            let size = le_u32(&data, at + 24).ok_or_else(truncated)? as usize;
+            let name_len = le_u16(&data, at + 28).ok_or_else(truncated)? as usize;
+            let extra_len = le_u16(&data, at + 30).ok_or_else(truncated)? as usize;
+            let comment_len = le_u16(&data, at + 32).ok_or_else(truncated)? as usize;
+            let local = le_u32(&data, at + 42).ok_or_else(truncated)? as usize;
+            let name = data
+                .get(at + 46..at + 46 + name_len)
+                .ok_or_else(truncated)?;
+            let name = String::from_utf8_lossy(name).into_owned();
+            at += 46 + name_len + extra_len + comment_len;
+            if name.ends_with('/') {
+                continue;
+            }
+
+            let unsupported = |reason: &str| ArchiveError::Unsupported {
+                path: name.clone(),
+                reason: reason.to_string(),
+            };
+            if flags & 1 != 0 {
+                return Err(unsupported("encrypted"));
+            }
+            match method {
+                ZIP_STORED => {}
+                #[cfg(feature = "zip")]
+                ZIP_DEFLATED => {}
+                #[cfg(not(feature = "zip"))]
+                ZIP_DEFLATED => return Err(unsupported("deflated entries need the `zip` feature")),
+                _ => return Err(unsupported(&format!("compression method {method}"))),
+            }
+            if le_u32(&data, local) != Some(ZIP_LOCAL_HEADER) {
+                return Err(invalid("bad local file header"));
+            }
+            let local_name_len = le_u16(&data, local + 26).ok_or_else(truncated)? as usize;
+            let local_extra_len = le_u16(&data, local + 28).ok_or_else(truncated)? as usize;
+            let start = local + 30 + local_name_len + local_extra_len;
+            if start + compressed > data.len() {
+                return Err(invalid("entry runs past the end"));
+            }
+            entries.insert(
+                normalize_path(&name),
+                ArchiveEntry {
+                    data: start..start + compressed,
+                    method,
+                    size,
+                },
+            );
+        }
+        Ok(Self { data, entries })
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+impl Vfs for ArchiveVfs {
+    fn read(&self, path: &str) -> Result<Vec<u8>, VfsError> {
+        let entry = self.entries.get(path).ok_or_else(|| VfsError::NotFound {
+            path: path.to_owned(),
+        })?;
+        let data = &self.data[entry.data.clone()];
+        match entry.method {
+            #[cfg(feature = "zip")]
+            ZIP_DEFLATED => miniz_oxide::inflate::decompress_to_vec_with_limit(data, entry.size)
+                .map_err(|_| VfsError::Corrupt {
+                    path: path.to_owned(),
+                }),
+            _ => Ok(data.to_vec()),
+        }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.entries.contains_key(path)
+    }
+}
+
+struct Mount {
+    prefix: String,
+    source: Box<dyn Vfs>,
+}
+
+/// [`Vfs`] sources mounted under path prefixes. A path is looked up in the most recent mount whose prefix
+/// matches first, so mount the base game before the mods that override it.
+#[derive(Default)]
+pub struct VfsOverlay {
+    mounts: Vec<Mount>,
+}
+
+impl std::fmt::Debug for VfsOverlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VfsOverlay")
+            .field(
+                "mounts",
+                &self.mounts.iter().map(|m| &m.prefix).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl VfsOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mounts `source` so that `prefix/path` reads `path` from it. An empty prefix mounts at the root.
+    pub fn mount(&mut self, prefix: &str, source: impl Vfs + 'static) -> &mut Self {
+        self.mounts.push(Mount {
+            prefix: normalize_path(prefix),
+            source: Box::new(source),
+        });
+        self
+    }
+
+    /// Removes the most recent mount at `prefix`, `false` if there is none
+    pub fn unmount(&mut self, prefix: &str) -> bool {
+        let prefix = normalize_path(prefix);
+        match self.mounts.iter().rposition(|m| m.prefix == prefix) {
+            Some(index) => {
+                self.mounts.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Mounts that may have `path`, newest first, each with the path relative to it
+    fn resolve<'a>(&'a self, path: &'a str) -> impl Iterator<Item = (&'a dyn Vfs, &'a str)> {
+        self.mounts.iter().rev().filter_map(move |mount| {
+            let relative = if mount.prefix.is_empty() {
+                path
+            } else {
+                path.strip_prefix(&mount.prefix)?.strip_prefix('/')?
+            };
+            Some((mount.source.as_ref(), relative))
+        })
+    }
+
+    /// Routes raylib's file loading through the overlay until the guard is dropped
+    pub fn install(self) -> VfsGuard {
+        let overlay = Arc::new(self);
+        let data = overlay.clone();
+        let text = overlay;
+        VfsGuard {
+            _data: set_load_file_data_callback(move |path| {
+                data.read_logged(path).unwrap_or_default()
+            }),
+            _text: set_load_file_text_callback(move |path| {
+                text.read_logged(path)
+                    .map(|data| String::from_utf8_lossy(&data).into_owned())
+                    .unwrap_or_default()
+            }),
+        }
+    }
+
+    /// Reads a path as raylib passes it, warning like raylib does when it's missing
+    fn read_logged(&self, path: &str) -> Option<Vec<u8>> {
+        let message = match self.read(&normalize_path(path)) {
+            Ok(data) => return Some(data),
+            Err(VfsError::NotFound { .. }) => "File not found in any mount".to_owned(),
+            Err(error) => error.to_string().replace('\n', " "),
+        };
+        trace_log(
+            TraceLogLevel::LOG_WARNING,
+            &format!("VFS: [{path}] {message}").replace('%', "%%"),
+        );
+        None
+    }
+}
+
+impl Vfs for VfsOverlay {
+    /// Reads from the newest mount that has `path`. Any error other than [`VfsError::NotFound`] stops the search.
+    fn read(&self, path: &str) -> Result<Vec<u8>, VfsError> {
+        for (source, relative) in self.resolve(path) {
+            match source.read(relative) {
+                Err(VfsError::NotFound { .. }) => continue,
+                result => return result,
+            }
+        }
+        Err(VfsError::NotFound {
+            path: path.to_owned(),
+        })
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.resolve(path)
+            .any(|(source, relative)| source.exists(relative))
+    }
+}
+
+/// Keeps a [`VfsOverlay`] installed; dropping it puts back the file loaders that were set before
+#[derive(Debug)]
+#[must_use = "the overlay is uninstalled as soon as the guard is dropped"]
+pub struct VfsGuard {
+    _data: CallbackGuard<RustLoadFileDataCallback>,
+    _text: CallbackGuard<RustLoadFileTextCallback>,
+}
+
+#[cfg(test)]
+mod vfs_test {
+    use super::*;
+
+    fn tar_entry(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut header = [0u8; TAR_BLOCK];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].fill(b' ');
+        let sum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        let mut entry = header.to_vec();
+        entry.extend_from_slice(data);
+        entry.resize(entry.len().div_ceil(TAR_BLOCK) * TAR_BLOCK, 0);
+        entry
+    }
+
+    fn stored_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        let mut directory = Vec::new();
+        for (name, data) in files {
+            let offset = zip.len() as u32;
+            let mut header = Vec::new();
+            header.extend_from_slice(&ZIP_LOCAL_HEADER.to_le_bytes());
+            header.extend_from_slice(&[20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&[0, 0]);
+            zip.extend_from_slice(&header);
+            zip.extend_from_slice(name.as_bytes());
+            zip.extend_from_slice(data);
+
+            directory.extend_from_slice(&ZIP_CENTRAL_HEADER.to_le_bytes());
+            directory.extend_from_slice(&[20, 0]);
+            directory.extend_from_slice(&header[4..30]);
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let offset = zip.len() as u32;
+        zip.extend_from_slice(&directory);
+        zip.extend_from_slice(&ZIP_END_OF_DIRECTORY.to_le_bytes());
+        zip.extend_from_slice(&[0; 4]);
+        zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&offset.to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("./textures/../fonts//a.ttf"), "fonts/a.ttf");
+        assert_eq!(
+            normalize_path("C:\\game\\assets\\a.png"),
+            "C:/game/assets/a.png"
+        );
+        assert_eq!(normalize_path("/../../etc/passwd"), "etc/passwd");
+        assert_eq!(normalize_path(""), "");
+    }
+
+    #[test]
+    fn test_overlay_order() {
+        let mut base = MemoryVfs::new();
+        base.insert("a.txt", &b"base a"[..])
+            .insert("b.txt", &b"base b"[..]);
+        let mut a_mod = MemoryVfs::new();
+        a_mod.insert("a.txt", &b"mod a"[..]);
+        let mut sounds = MemoryVfs::new();
+        sounds.insert("jump.wav", &b"jump"[..]);
+
+        let mut vfs = VfsOverlay::new();
+        vfs.mount("", base)
+            .mount("", a_mod)
+            .mount("./audio/sfx/", sounds);
+        assert_eq!(vfs.read("a.txt").unwrap(), b"mod a");
+        assert_eq!(vfs.read("b.txt").unwrap(), b"base b");
+        assert_eq!(vfs.read("audio/sfx/jump.wav").unwrap(), b"jump");
+        assert!(!vfs.exists("audio/sfxjump.wav"));
+        assert!(!vfs.exists("jump.wav"));
+
+        assert!(vfs.unmount(""));
+        assert_eq!(vfs.read("a.txt").unwrap(), b"base a");
+        assert!(!vfs.unmount("fonts"));
+    }
+
+    #[test]
+    fn test_directory_stays_inside_root() {
+        let dir = DirectoryVfs::new("src");
+        assert!(dir.read("lib.rs").is_ok());
+        assert!(dir.exists("./core/vfs.rs"));
+        assert!(matches!(
+            dir.read("missing.rs"),
+            Err(VfsError::NotFound { .. })
+        ));
+        let root = std::fs::canonicalize("Cargo.toml").unwrap();
+        for path in [
+            "../Cargo.toml",
+            "core/../../Cargo.toml",
+            root.to_str().unwrap(),
+        ] {
+            assert!(
+                matches!(dir.read(path), Err(VfsError::InvalidPath { .. })),
+                "{path}"
+            );
+            assert!(!dir.exists(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_tar() {
+        let mut tar = tar_entry("./shaders/", b"");
+        tar[156] = b'5';
+        let sum: u32 = tar[..TAR_BLOCK]
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                if (148..156).contains(&i) {
+                    b' ' as u32
+                } else {
+                    b as u32
+                }
+            })
+            .sum();
+        tar[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+        tar.extend(tar_entry("./shaders/blur.fs", b"#version 330"));
+        tar.extend(tar_entry("readme.txt", &[b'x'; 700]));
+        tar.extend([0; 2 * TAR_BLOCK]);
+
+        let archive = ArchiveVfs::from_bytes(tar.clone()).unwrap();
+        assert_eq!(archive.paths().count(), 2);
+        assert_eq!(archive.read("shaders/blur.fs").unwrap(), b"#version 330");
+        assert_eq!(archive.read("readme.txt").unwrap().len(), 700);
+        assert!(matches!(
+            archive.read("shaders"),
+            Err(VfsError::NotFound { .. })
+        ));
+
+        tar[0] = b'X';
+        assert!(matches!(
+            ArchiveVfs::from_tar(tar),
+            Err(ArchiveError::Tar(_))
+        ));
+    }
+
+    #[test]
+    fn test_pax_path() {
+        let long = "a/".repeat(80) + "file.png";
+        let record = format!(" path={long}\n");
+        let record = format!("{}{record}", record.len() + 3);
+        assert_eq!(pax_path(record.as_bytes()), Some(long));
+        assert_eq!(pax_path(b"20 mtime=1700000000\n"), None);
+    }
+
+    #[test]
+    fn test_zip() {
+        let zip = stored_zip(&[
+            ("fonts/", b""),
+            ("fonts/a.fnt", b"info"),
+            ("b.bin", &[1, 2, 3]),
+        ]);
+        let archive = ArchiveVfs::from_bytes(zip.clone()).unwrap();
+        assert_eq!(archive.paths().count(), 2);
+        assert_eq!(archive.read("fonts/a.fnt").unwrap(), b"info");
+        assert_eq!(archive.read("b.bin").unwrap(), [1, 2, 3]);
+
+        let mut vfs = VfsOverlay::new();
+        vfs.mount("pack", archive);
+        assert!(vfs.exists("pack/fonts/a.fnt"));
+        assert!(!vfs.exists("fonts/a.fnt"));
+
+        assert!(matches!(
+            ArchiveVfs::from_zip(&zip[..zip.len() - 22]),
+            Err(ArchiveError::Zip(_))
+        ));
+    }
+}
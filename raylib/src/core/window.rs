@@ -906,4 +906,113 @@ impl RaylibHandle {
     pub fn wait_time(&self, seconds: f64) {
         unsafe { ffi::WaitTime(seconds) }
     }
+
+    /// Polls input and starts a manually controlled frame. The screen buffer is swapped when the frame is dropped.
+    /// Prefer using the closure version, [RaylibHandle::manual_frame]. This version returns a handle that swaps
+    /// the screen buffer at the end of the scope and is provided as a fallback incase you run into issues with closures.
+    #[must_use]
+    pub fn begin_manual_frame<'a>(&'a mut self, _: &RaylibThread) -> ManualFrame<'a> {
+        unsafe {
+            ffi::PollInputEvents();
+        }
+        let start = unsafe { ffi::GetTime() };
+        ManualFrame {
+            rl: self,
+            start,
+            present: true,
+            target_frame_time: None,
+        }
+    }
+
+    /// Polls input, runs `func` and swaps the screen buffer.
+    pub fn manual_frame(&mut self, thread: &RaylibThread, func: impl FnOnce(&mut ManualFrame)) {
+        let mut frame = self.begin_manual_frame(thread);
+        func(&mut frame);
+    }
+}
+
+/// A frame whose input polling, buffer swap and pacing are done by the caller instead of `EndDrawing`.
+///
+/// Drawing still goes through [RaylibHandle::begin_drawing], which only flushes the batch in this mode.
+/// ```ignore
+/// while !rl.window_should_close() {
+///     let mut frame = rl.begin_manual_frame(&thread);
+///     frame.set_target_fps(60);
+///     world.update(1.0 / 60.0, &frame);
+///     let mut d = frame.begin_drawing(&thread);
+///     world.draw(&mut d);
+/// }
+/// ```
+#[cfg(feature = "SUPPORT_CUSTOM_FRAME_CONTROL")]
+pub struct ManualFrame<'a> {
+    rl: &'a mut RaylibHandle,
+    start: f64,
+    present: bool,
+    target_frame_time: Option<f64>,
+}
+
+#[cfg(feature = "SUPPORT_CUSTOM_FRAME_CONTROL")]
+impl<'a> ManualFrame<'a> {
+    /// Time in seconds at which the frame started
+    #[inline]
+    #[must_use]
+    pub fn start_time(&self) -> f64 {
+        self.start
+    }
+
+    /// Seconds since the frame started
+    #[inline]
+    #[must_use]
+    pub fn elapsed(&self) -> f64 {
+        unsafe { ffi::GetTime() - self.start }
+    }
+
+    /// Don't swap the screen buffer at the end of this frame, e.g. for simulation steps that are not shown.
+    #[inline]
+    pub fn skip_present(&mut self) {
+        self.present = false;
+    }
+
+    /// After the buffer swap, wait until `seconds` have passed since the frame started. `None` doesn't wait.
+    #[inline]
+    pub fn set_target_frame_time(&mut self, seconds: Option<f64>) {
+        self.target_frame_time = seconds;
+    }
+
+    /// After the buffer swap, wait until the frame took `1 / fps` seconds. 0 doesn't wait.
+    #[inline]
+    pub fn set_target_fps(&mut self, fps: u32) {
+        self.target_frame_time = (fps > 0).then(|| 1.0 / fps as f64);
+    }
+}
+
+#[cfg(feature = "SUPPORT_CUSTOM_FRAME_CONTROL")]
+impl<'a> Drop for ManualFrame<'a> {
+    fn drop(&mut self) {
+        if self.present {
+            unsafe { ffi::SwapScreenBuffer() }
+        }
+        if let Some(target) = self.target_frame_time {
+            let remaining = target - self.elapsed();
+            if remaining > 0.0 {
+                unsafe { ffi::WaitTime(remaining) }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "SUPPORT_CUSTOM_FRAME_CONTROL")]
+impl<'a> std::ops::Deref for ManualFrame<'a> {
+    type Target = RaylibHandle;
+
+    fn deref(&self) -> &Self::Target {
+        self.rl
+    }
+}
+
+#[cfg(feature = "SUPPORT_CUSTOM_FRAME_CONTROL")]
+impl<'a> std::ops::DerefMut for ManualFrame<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.rl
+    }
 }
@@ -26,22 +26,40 @@ Permission is granted to anyone to use this software for any purpose, including
 
 pub use crate::callbacks::*;
 pub use crate::consts::*;
+pub use crate::core::animation::*;
+pub use crate::core::assets::*;
 pub use crate::core::audio::*;
+pub use crate::core::audio_effects::*;
 pub use crate::core::automation::*;
 pub use crate::core::camera::*;
 pub use crate::core::collision::*;
 pub use crate::core::color::*;
 pub use crate::core::data::*;
 pub use crate::core::drawing::*;
+pub use crate::core::dynamic_font::*;
 pub use crate::core::file::*;
+pub use crate::core::game_loop::*;
+#[cfg(feature = "gltf")]
+pub use crate::core::gltf::*;
+pub use crate::core::hot_reload::*;
 pub use crate::core::input::*;
 pub use crate::core::logging::*;
 pub use crate::core::math::*;
+pub use crate::core::mesh_builder::*;
+pub use crate::core::mesh_processing::*;
 pub use crate::core::misc::*;
+pub use crate::core::mixer::*;
 pub use crate::core::models::*;
+pub use crate::core::replay::*;
+pub use crate::core::rlgl::*;
+pub use crate::core::sdf_font::*;
 pub use crate::core::shaders::*;
+#[cfg(feature = "shaping")]
+pub use crate::core::shaping::*;
 pub use crate::core::text::*;
+pub use crate::core::text_layout::*;
 pub use crate::core::texture::*;
+pub use crate::core::vfs::*;
 pub use crate::core::vr::*;
 pub use crate::core::window::*;
 pub use crate::core::*;
@@ -66,7 +66,10 @@ fn main() {
 
     let mut _keep_alive = vec![];
     for callback in effects.iter_mut() {
-        _keep_alive.push(attach_audio_stream_processor_to_music(&music, callback));
+        _keep_alive.push(
+            attach_audio_stream_processor_to_music(&music, callback)
+                .expect("no free audio callback slot"),
+        );
     }
 
     // run main loop (use key to control music and effects)
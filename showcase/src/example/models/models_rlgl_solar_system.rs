@@ -12,7 +12,6 @@
 ********************************************************************************************/
 
 pub use raylib::prelude::*;
-use raylib::ffi;
 
 
 //------------------------------------------------------------------------------------
@@ -76,33 +75,33 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut
             {
                 let mut d = d.begin_mode3D(&camera);
 
-                unsafe {
-                    ffi::rlPushMatrix();
-                        ffi::rlScalef(sunRadius, sunRadius, sunRadius);          // Scale Sun
-                        DrawSphereBasic(Color::GOLD);                              // Draw the Sun
-                    ffi::rlPopMatrix();
-
-                    ffi::rlPushMatrix();
-                        ffi::rlRotatef(earthOrbitRotation, 0.0, 1.0, 0.0);    // Rotation for Earth orbit around Sun
-                        ffi::rlTranslatef(earthOrbitRadius, 0.0, 0.0);         // Translation for Earth orbit
-                        ffi::rlRotatef(-earthOrbitRotation, 0.0, 1.0, 0.0);   // Rotation for Earth orbit around Sun inverted
-
-                        ffi::rlPushMatrix();
-                            ffi::rlRotatef(earthRotation, 0.25, 1.0, 0.0);       // Rotation for Earth itself
-                            ffi::rlScalef(earthRadius, earthRadius, earthRadius);// Scale Earth
-
-                            DrawSphereBasic(Color::BLUE);                          // Draw the Earth
-                        ffi::rlPopMatrix();
-
-                        ffi::rlRotatef(moonOrbitRotation, 0.0, 1.0, 0.0);     // Rotation for Moon orbit around Earth
-                        ffi::rlTranslatef(moonOrbitRadius, 0.0, 0.0);          // Translation for Moon orbit
-                        ffi::rlRotatef(-moonOrbitRotation, 0.0, 1.0, 0.0);    // Rotation for Moon orbit around Earth inverted
-                        ffi::rlRotatef(moonRotation, 0.0, 1.0, 0.0);          // Rotation for Moon itself
-                        ffi::rlScalef(moonRadius, moonRadius, moonRadius);       // Scale Moon
-
-                        DrawSphereBasic(Color::LIGHTGRAY);                         // Draw the Moon
-                    ffi::rlPopMatrix();
+                {
+                    let mut d = d.begin_rl_matrix();
+                    d.scale(sunRadius, sunRadius, sunRadius);          // Scale Sun
+                    DrawSphereBasic(&mut d, Color::GOLD);              // Draw the Sun
+                }
 
+                {
+                    let mut d = d.begin_rl_matrix();
+                    d.rotate(earthOrbitRotation, rvec3(0.0, 1.0, 0.0));    // Rotation for Earth orbit around Sun
+                    d.translate(earthOrbitRadius, 0.0, 0.0);              // Translation for Earth orbit
+                    d.rotate(-earthOrbitRotation, rvec3(0.0, 1.0, 0.0));   // Rotation for Earth orbit around Sun inverted
+
+                    {
+                        let mut d = d.begin_rl_matrix();
+                        d.rotate(earthRotation, rvec3(0.25, 1.0, 0.0));    // Rotation for Earth itself
+                        d.scale(earthRadius, earthRadius, earthRadius);   // Scale Earth
+
+                        DrawSphereBasic(&mut d, Color::BLUE);             // Draw the Earth
+                    }
+
+                    d.rotate(moonOrbitRotation, rvec3(0.0, 1.0, 0.0));     // Rotation for Moon orbit around Earth
+                    d.translate(moonOrbitRadius, 0.0, 0.0);               // Translation for Moon orbit
+                    d.rotate(-moonOrbitRotation, rvec3(0.0, 1.0, 0.0));    // Rotation for Moon orbit around Earth inverted
+                    d.rotate(moonRotation, rvec3(0.0, 1.0, 0.0));          // Rotation for Moon itself
+                    d.scale(moonRadius, moonRadius, moonRadius);          // Scale Moon
+
+                    DrawSphereBasic(&mut d, Color::LIGHTGRAY);            // Draw the Moon
                 }
     
                     // Some reference elements (not affected by previous matrix transformations)
@@ -123,16 +122,14 @@ pub fn run(rl: &mut RaylibHandle, thread: &RaylibThread) -> crate::SampleOut
 
 // Draw sphere without any matrix transformation
 // NOTE: Sphere is drawn in woffi::rld position ( 0, 0, 0 ) with radius 1.0
-fn DrawSphereBasic(color: Color)
+fn DrawSphereBasic(d: &mut impl RaylibDraw, color: Color)
 {
     let rings = 16;
     let slices = 16;
 
-    unsafe {
-        ffi::rlCheckRenderBatchLimit((rings + 2) * slices * 6);
-
-        ffi::rlBegin(ffi::RL_TRIANGLES as i32);
-            ffi::rlColor4ub(color.r, color.g, color.b, color.a);
+    {
+        let mut p = d.begin_rl_primitive(RlglDrawMode::Triangles, (rings + 2) * slices * 6);
+            p.color(color);
 
             for i in 0..(rings + 2)
             {
@@ -140,28 +137,27 @@ fn DrawSphereBasic(color: Color)
                 {
                     let deg2rad: f32 = consts::DEG2RAD as f32;
 
-                    ffi::rlVertex3f((deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).sin(),
+                    p.vertex3(rvec3((deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).sin(),
                                     (deg2rad*(270+(180/(rings + 1))*i) as f32).sin(),
-                                    (deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).cos());
-                    ffi::rlVertex3f((deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).sin(),
+                                    (deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).cos()));
+                    p.vertex3(rvec3((deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).sin(),
                                     (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).sin(),
-                                    (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).cos());
-                    ffi::rlVertex3f((deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*(j*360/slices) as f32).sin(),
+                                    (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).cos()));
+                    p.vertex3(rvec3((deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*(j*360/slices) as f32).sin(),
                                     (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).sin(),
-                                    (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*(j*360/slices) as f32).cos());
+                                    (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*(j*360/slices) as f32).cos()));
 
-                    ffi::rlVertex3f((deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).sin(),
+                    p.vertex3(rvec3((deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).sin(),
                                     (deg2rad*(270+(180/(rings + 1))*i) as f32).sin(),
-                                    (deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).cos());
-                    ffi::rlVertex3f((deg2rad*(270+(180/(rings + 1))*(i)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).sin(),
+                                    (deg2rad*(270+(180/(rings + 1))*i) as f32).cos()*(deg2rad*(j*360/slices) as f32).cos()));
+                    p.vertex3(rvec3((deg2rad*(270+(180/(rings + 1))*(i)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).sin(),
                                     (deg2rad*(270+(180/(rings + 1))*(i)) as f32).sin(),
-                                    (deg2rad*(270+(180/(rings + 1))*(i)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).cos());
-                    ffi::rlVertex3f((deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).sin(),
+                                    (deg2rad*(270+(180/(rings + 1))*(i)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).cos()));
+                    p.vertex3(rvec3((deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).sin(),
                                     (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).sin(),
-                                    (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).cos());
+                                    (deg2rad*(270+(180/(rings + 1))*(i+1)) as f32).cos()*(deg2rad*((j+1)*360/slices) as f32).cos()));
                 }
             }
-        ffi::rlEnd();
     }
 
 }